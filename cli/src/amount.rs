@@ -0,0 +1,56 @@
+use anyhow::{bail, Result};
+
+// "200000" or "200000.5" in whole tokens to base units
+pub fn parse_amount(input: &str, decimals: u8) -> Result<u64> {
+    let (whole, fraction) = input.split_once('.').unwrap_or((input, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        bail!("invalid amount: {:?}", input);
+    }
+    if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+        bail!("invalid amount: {:?}", input);
+    }
+    if fraction.len() > decimals as usize {
+        bail!("amount {} has more than {} decimal places", input, decimals);
+    }
+
+    let scale = 10u64.pow(decimals as u32);
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse()? };
+    let fraction: u64 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{:0<width$}", fraction, width = decimals as usize).parse()?
+    };
+
+    match whole.checked_mul(scale).and_then(|units| units.checked_add(fraction)) {
+        Some(units) => Ok(units),
+        None => bail!("amount {} is too large", input),
+    }
+}
+
+// Base units to whole tokens, trimming trailing zeros: 200_000_500_000 -> "200000.5"
+pub fn format_amount(units: u64, decimals: u8) -> String {
+    let scale = 10u64.pow(decimals as u32);
+    let whole = units / scale;
+    let fraction = units % scale;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    let fraction = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", whole, fraction.trim_end_matches('0'))
+}
+
+pub fn format_duration(seconds: i64) -> String {
+    if seconds <= 0 {
+        return "now".to_string();
+    }
+    let days = seconds / 86_400;
+    let hours = (seconds % 86_400) / 3_600;
+    let minutes = (seconds % 3_600) / 60;
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m {}s", minutes, seconds % 60)
+    }
+}
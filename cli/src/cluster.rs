@@ -0,0 +1,73 @@
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+
+// Everything the commands need from a cluster. RpcCluster talks to a validator;
+// tests can implement this over a program-test bank or an in-memory fake.
+pub trait Cluster {
+    // None when the account does not exist
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>>;
+
+    // Simulates a single view instruction and returns its return data
+    fn simulate_view(&self, ix: Instruction, fee_payer: &Pubkey) -> Result<Vec<u8>>;
+
+    fn send(&self, ixs: &[Instruction], signer: &Keypair) -> Result<Signature>;
+}
+
+pub struct RpcCluster {
+    client: RpcClient,
+}
+
+impl RpcCluster {
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+        }
+    }
+}
+
+impl Cluster for RpcCluster {
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let response = self
+            .client
+            .get_account_with_commitment(address, self.client.commitment())
+            .with_context(|| format!("failed to fetch account {}", address))?;
+        Ok(response.value.map(|account| account.data))
+    }
+
+    fn simulate_view(&self, ix: Instruction, fee_payer: &Pubkey) -> Result<Vec<u8>> {
+        let tx = Transaction::new_unsigned(Message::new(&[ix], Some(fee_payer)));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.client.commitment()),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let result = self.client.simulate_transaction_with_config(&tx, config)?.value;
+        if let Some(err) = result.err {
+            bail!(
+                "simulation failed: {}\n{}",
+                err,
+                result.logs.unwrap_or_default().join("\n")
+            );
+        }
+        let return_data = result
+            .return_data
+            .ok_or_else(|| anyhow!("view instruction returned no data"))?;
+        Ok(STANDARD.decode(&return_data.data.0)?)
+    }
+
+    fn send(&self, ixs: &[Instruction], signer: &Keypair) -> Result<Signature> {
+        let blockhash = self.client.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+        Ok(self.client.send_and_confirm_transaction_with_spinner(&tx)?)
+    }
+}
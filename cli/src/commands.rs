@@ -0,0 +1,558 @@
+use std::io::Write;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::Mint;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, bail, Result};
+use qst_staking_client::pda::{find_stake_account_address, find_staking_pool_address};
+use qst_staking_client::state::{
+    decode_return_data, decode_staking_pool, PenaltyTier, PoolInfo, StakeInfo, StakeWindowPhase,
+    StakingPool, UnclaimedBonusPolicy, UnstakeQuote,
+};
+use qst_staking_client::{instructions, math};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::amount::{format_amount, format_duration, parse_amount};
+use crate::cluster::Cluster;
+
+pub struct CommandContext<'a, C: Cluster> {
+    pub cluster: &'a C,
+    pub program_id: Pubkey,
+    pub signer: &'a Keypair,
+    // Defaults to the associated token accounts of the signer / pool PDA
+    pub user_token_account: Option<Pubkey>,
+    pub pool_token_account: Option<Pubkey>,
+}
+
+// Pool state plus the mint decimals every amount is printed with
+struct LoadedPool {
+    address: Pubkey,
+    pool: StakingPool,
+    decimals: u8,
+}
+
+impl<'a, C: Cluster> CommandContext<'a, C> {
+    fn load_pool(&self) -> Result<LoadedPool> {
+        let (address, _) = find_staking_pool_address(&self.program_id);
+        let data = self
+            .cluster
+            .account_data(&address)?
+            .ok_or_else(|| anyhow!("staking pool {} not found, run `qst-stake initialize` first", address))?;
+        let pool = decode_staking_pool(&data)?;
+        let decimals = self.mint_decimals(&pool.qst_mint)?;
+        Ok(LoadedPool { address, pool, decimals })
+    }
+
+    fn mint_decimals(&self, mint: &Pubkey) -> Result<u8> {
+        let data = self
+            .cluster
+            .account_data(mint)?
+            .ok_or_else(|| anyhow!("mint {} not found", mint))?;
+        Ok(Mint::try_deserialize(&mut &data[..])?.decimals)
+    }
+
+    fn user_token_account(&self, loaded: &LoadedPool) -> Pubkey {
+        self.user_token_account
+            .unwrap_or_else(|| get_associated_token_address(&self.signer.pubkey(), &loaded.pool.qst_mint))
+    }
+
+    fn pool_token_account(&self, loaded: &LoadedPool) -> Pubkey {
+        self.pool_token_account
+            .unwrap_or_else(|| get_associated_token_address(&loaded.address, &loaded.pool.qst_mint))
+    }
+
+    fn view<T: anchor_lang::AnchorDeserialize>(&self, ix: solana_sdk::instruction::Instruction) -> Result<T> {
+        let data = self.cluster.simulate_view(ix, &self.signer.pubkey())?;
+        Ok(decode_return_data(&data)?)
+    }
+
+    fn has_position(&self, wallet: &Pubkey) -> Result<bool> {
+        let (stake_account, _) = find_stake_account_address(&self.program_id, wallet);
+        Ok(self.cluster.account_data(&stake_account)?.is_some())
+    }
+}
+
+// === ADMIN ===
+
+pub fn initialize<C: Cluster>(
+    ctx: &CommandContext<C>,
+    mint: &Pubkey,
+    fee_recipient: Option<Pubkey>,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let payer = ctx.signer.pubkey();
+    let fee_recipient = fee_recipient.unwrap_or(payer);
+    let (pool_address, _) = find_staking_pool_address(&ctx.program_id);
+    let pool_token_account = ctx
+        .pool_token_account
+        .unwrap_or_else(|| get_associated_token_address(&pool_address, mint));
+
+    let mut ixs = vec![instructions::initialize(&ctx.program_id, &payer, mint, &payer, &fee_recipient)];
+    if ctx.pool_token_account.is_none() {
+        ixs.push(create_associated_token_account_idempotent(
+            &payer,
+            &pool_address,
+            mint,
+            &anchor_spl::token::ID,
+        ));
+    }
+    let signature = ctx.cluster.send(&ixs, ctx.signer)?;
+
+    writeln!(out, "Initialized staking pool {}", pool_address)?;
+    writeln!(out, "  Admin:              {}", payer)?;
+    writeln!(out, "  QST mint:           {}", mint)?;
+    writeln!(out, "  Fee recipient:      {}", fee_recipient)?;
+    writeln!(out, "  Pool token account: {}", pool_token_account)?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn start_window<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::start_stake_window(&ctx.program_id, &ctx.signer.pubkey());
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    let loaded = ctx.load_pool()?;
+    writeln!(out, "Stake window started")?;
+    writeln!(out, "  Window closes:      {}", loaded.pool.stake_window_end)?;
+    writeln!(out, "  Enrollment closes:  {}", loaded.pool.bonus_enrollment_deadline)?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn schedule_window<C: Cluster>(ctx: &CommandContext<C>, start: i64, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::schedule_stake_window(&ctx.program_id, &ctx.signer.pubkey(), start);
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    let loaded = ctx.load_pool()?;
+    writeln!(out, "Stake window scheduled")?;
+    writeln!(out, "  Window opens:       {}", loaded.pool.first_stake_timestamp)?;
+    writeln!(out, "  Window closes:      {}", loaded.pool.stake_window_end)?;
+    writeln!(out, "  Enrollment closes:  {}", loaded.pool.bonus_enrollment_deadline)?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn cancel_window_schedule<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::cancel_stake_window_schedule(&ctx.program_id, &ctx.signer.pubkey());
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Scheduled stake window cancelled")?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn extend_window<C: Cluster>(ctx: &CommandContext<C>, end: i64, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::extend_stake_window(&ctx.program_id, &ctx.signer.pubkey(), end);
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    let loaded = ctx.load_pool()?;
+    writeln!(out, "Stake window extended")?;
+    writeln!(out, "  Window closes:      {}", loaded.pool.stake_window_end)?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn close_window_early<C: Cluster>(ctx: &CommandContext<C>, end: i64, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::close_stake_window_early(&ctx.program_id, &ctx.signer.pubkey(), end);
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    let loaded = ctx.load_pool()?;
+    writeln!(out, "Stake window shortened")?;
+    writeln!(out, "  Window closes:      {}", loaded.pool.stake_window_end)?;
+    writeln!(out, "  Enrollment closes:  {}", loaded.pool.bonus_enrollment_deadline)?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn start_next_round<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::start_next_round(&ctx.program_id, &ctx.signer.pubkey());
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    let loaded = ctx.load_pool()?;
+    writeln!(out, "Round {} ready for a new stake window", loaded.pool.round)?;
+    writeln!(
+        out,
+        "  Rolled over:        {} QST",
+        format_amount(loaded.pool.penalty_vault_amount, loaded.decimals)
+    )?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn collect_dust<C: Cluster>(
+    ctx: &CommandContext<C>,
+    fee_recipient_token_account: Option<Pubkey>,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let fee_recipient_token_account = fee_recipient_token_account
+        .unwrap_or_else(|| get_associated_token_address(&loaded.pool.fee_recipient, &loaded.pool.qst_mint));
+
+    let ix = instructions::collect_dust(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &ctx.pool_token_account(&loaded),
+        &fee_recipient_token_account,
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Collected dust to {}", fee_recipient_token_account)?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn configure_unclaimed_bonus<C: Cluster>(
+    ctx: &CommandContext<C>,
+    policy: &str,
+    claim_period: i64,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let policy = parse_policy(policy)?;
+    let ix = instructions::configure_unclaimed_bonus(&ctx.program_id, &ctx.signer.pubkey(), policy, claim_period);
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Unclaimed bonus policy set to {}", policy_name(policy))?;
+    writeln!(out, "  Claim period:       {}", format_duration(claim_period))?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+// Funds come from the signer's token account (--token-account)
+pub fn fund_rewards<C: Cluster>(
+    ctx: &CommandContext<C>,
+    amount: &str,
+    start: i64,
+    end: i64,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let units = parse_amount(amount, loaded.decimals)?;
+    let ix = instructions::fund_rewards(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &ctx.user_token_account(&loaded),
+        &loaded.pool.qst_mint,
+        units,
+        start,
+        end,
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Funded {} QST of rewards", format_amount(units, loaded.decimals))?;
+    writeln!(out, "  Streams from:       {}", start)?;
+    writeln!(out, "  Streams until:      {} ({})", end, format_duration(end - start))?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+// Reclaimed emissions go to the signer's token account (--token-account)
+pub fn reclaim_rewards<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let ix = instructions::reclaim_unallocated_rewards(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &ctx.user_token_account(&loaded),
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Reclaimed unallocated rewards")?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn set_fee_recipient<C: Cluster>(ctx: &CommandContext<C>, wallet: &Pubkey, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::set_fee_recipient(&ctx.program_id, &ctx.signer.pubkey(), wallet);
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Fee recipient set to {}", wallet)?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+// === STAKERS ===
+
+pub fn stake<C: Cluster>(ctx: &CommandContext<C>, amount: &str, enroll: bool, out: &mut dyn Write) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let units = parse_amount(amount, loaded.decimals)?;
+    let Ok(node_keys) = math::node_keys_for(units) else {
+        bail!(
+            "stake must be a multiple of {} QST between {} and {} QST",
+            format_amount(math::MINIMUM_STAKE_AMOUNT, loaded.decimals),
+            format_amount(math::MINIMUM_STAKE_AMOUNT, loaded.decimals),
+            format_amount(math::MAXIMUM_STAKE_AMOUNT, loaded.decimals)
+        );
+    };
+
+    let build = if enroll { instructions::stake_and_enroll } else { instructions::stake_tokens };
+    let ix = build(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &ctx.user_token_account(&loaded),
+        &ctx.pool_token_account(&loaded),
+        units,
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(
+        out,
+        "Staked {} QST, earning {} node keys",
+        format_amount(units, loaded.decimals),
+        node_keys
+    )?;
+    if enroll {
+        writeln!(out, "Enrolled in the bonus program")?;
+    }
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn enroll<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let ix = instructions::enroll_in_bonus(&ctx.program_id, &ctx.signer.pubkey());
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Enrolled in the bonus program")?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+// Always prints the quote; only sends with `confirm`, and then with the quoted
+// penalty as max_penalty so a tier change in between fails instead of costing more
+pub fn unstake<C: Cluster>(
+    ctx: &CommandContext<C>,
+    amount: &str,
+    confirm: bool,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let units = parse_amount(amount, loaded.decimals)?;
+    let user = ctx.signer.pubkey();
+
+    let quote: UnstakeQuote = ctx.view(instructions::quote_unstake(&ctx.program_id, &user, units))?;
+    writeln!(out, "Unstake quote for {} QST", format_amount(quote.amount, loaded.decimals))?;
+    writeln!(out, "  Tier:               {}", tier_name(quote.tier))?;
+    writeln!(
+        out,
+        "  Penalty:            {} QST ({}%)",
+        format_amount(quote.penalty, loaded.decimals),
+        quote.penalty_rate
+    )?;
+    writeln!(out, "  You receive:        {} QST", format_amount(quote.net_amount, loaded.decimals))?;
+    if quote.time_until_next_tier > 0 {
+        writeln!(out, "  Next tier in:       {}", format_duration(quote.time_until_next_tier))?;
+    }
+
+    if !quote.unstake_allowed {
+        bail!("unstaking is not allowed for this position right now");
+    }
+    if !confirm {
+        writeln!(out, "Re-run with --yes to unstake")?;
+        return Ok(());
+    }
+
+    let ix = instructions::unstake_tokens(
+        &ctx.program_id,
+        &user,
+        &ctx.user_token_account(&loaded),
+        &ctx.pool_token_account(&loaded),
+        units,
+        quote.penalty,
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+    writeln!(out, "Unstaked; signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn withdraw<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let ix = instructions::withdraw_all(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &ctx.user_token_account(&loaded),
+        &ctx.pool_token_account(&loaded),
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Withdrew principal")?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn withdraw_bonus<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let ix = instructions::withdraw_bonus(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &ctx.user_token_account(&loaded),
+        &ctx.pool_token_account(&loaded),
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Withdrew bonus rewards")?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn withdraw_redistributed_bonus<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let ix = instructions::withdraw_redistributed_bonus(
+        &ctx.program_id,
+        &ctx.signer.pubkey(),
+        &ctx.user_token_account(&loaded),
+        &ctx.pool_token_account(&loaded),
+    );
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Withdrew redistributed bonus")?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+pub fn claim_rewards<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let ix = instructions::claim_rewards(&ctx.program_id, &ctx.signer.pubkey(), &ctx.user_token_account(&loaded));
+    let signature = ctx.cluster.send(&[ix], ctx.signer)?;
+
+    writeln!(out, "Claimed reward emissions")?;
+    writeln!(out, "Signature: {}", signature)?;
+    Ok(())
+}
+
+// === STATUS ===
+
+pub fn pool_status<C: Cluster>(ctx: &CommandContext<C>, out: &mut dyn Write) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let info: PoolInfo = ctx.view(instructions::get_pool_info(&ctx.program_id))?;
+    let qst = |units: u64| format_amount(units, loaded.decimals);
+    let cap = |units: u64| if units == u64::MAX { "uncapped".to_string() } else { format!("{} QST", qst(units)) };
+
+    writeln!(out, "Staking pool {}", loaded.address)?;
+    writeln!(out, "  Authority:          {}", info.authority)?;
+    writeln!(out, "  Fee recipient:      {}", info.fee_recipient)?;
+    writeln!(out, "  QST mint:           {}", info.qst_mint)?;
+    writeln!(out, "  Round:              {}", info.round)?;
+    writeln!(out, "  Window:             {}", phase_name(info.window_phase))?;
+    if info.window_phase == StakeWindowPhase::Scheduled {
+        writeln!(out, "  Window opens:       {}", info.first_stake_timestamp)?;
+    }
+    if info.window_phase != StakeWindowPhase::NotStarted {
+        writeln!(out, "  Window closes:      {}", info.stake_window_end)?;
+        writeln!(
+            out,
+            "  Enrollment:         {} (closes {})",
+            if info.bonus_enrollment_open { "open" } else { "closed" },
+            info.bonus_enrollment_deadline
+        )?;
+    }
+    writeln!(out, "  Total staked:       {} QST", qst(info.total_staked))?;
+    writeln!(out, "  Enrolled stake:     {} QST", qst(info.total_enrolled_stake))?;
+    writeln!(out, "  Penalty vault:      {} QST", qst(info.penalty_vault_amount))?;
+    writeln!(out, "  Rounding dust:      {} QST", qst(info.rounding_dust))?;
+    writeln!(out, "  Pool cap:           {}", cap(info.max_total_staked))?;
+    writeln!(out, "  Wallet cap:         {}", cap(info.max_user_stake))?;
+    writeln!(
+        out,
+        "  Bonus withdrawal:   {} ({})",
+        info.bonus_withdrawal_time,
+        if info.bonus_withdrawal_available { "available" } else { "not yet" }
+    )?;
+    writeln!(out, "  Unclaimed bonus:    {}", policy_name(info.unclaimed_bonus_policy))?;
+    if info.reward_stream_amount > 0 {
+        writeln!(
+            out,
+            "  Reward stream:      {} QST from {} to {} ({} QST released)",
+            qst(info.reward_stream_amount),
+            info.reward_start,
+            info.reward_end,
+            qst(info.rewards_released)
+        )?;
+        writeln!(out, "  Reward vault:       {} QST", qst(info.reward_vault_amount))?;
+    }
+    writeln!(
+        out,
+        "  Dust collectable:   {} ({})",
+        info.dust_claim_deadline,
+        if info.dust_collectable { "yes" } else { "not yet" }
+    )?;
+    if info.settlement_active {
+        writeln!(out, "  Settlement:         ACTIVE")?;
+    }
+    Ok(())
+}
+
+pub fn user_status<C: Cluster>(
+    ctx: &CommandContext<C>,
+    wallet: Option<Pubkey>,
+    out: &mut dyn Write,
+) -> Result<()> {
+    let loaded = ctx.load_pool()?;
+    let wallet = wallet.unwrap_or_else(|| ctx.signer.pubkey());
+    if !ctx.has_position(&wallet)? {
+        writeln!(out, "{} has no stake in this pool", wallet)?;
+        return Ok(());
+    }
+
+    let info: StakeInfo = ctx.view(instructions::get_stake_info(&ctx.program_id, &wallet))?;
+    let qst = |units: u64| format_amount(units, loaded.decimals);
+
+    writeln!(out, "Position of {}", wallet)?;
+    writeln!(out, "  Staked:             {} QST", qst(info.amount))?;
+    writeln!(out, "  Node keys:          {}", info.node_keys_earned)?;
+    writeln!(out, "  Enrolled in bonus:  {}", if info.enrolled_in_bonus { "yes" } else { "no" })?;
+    if info.bonus_claimed {
+        writeln!(out, "  Bonus:              claimed")?;
+    } else if info.enrolled_in_bonus {
+        writeln!(out, "  Potential bonus:    {} QST", qst(info.potential_bonus))?;
+    }
+    if info.accrued_rewards > 0 {
+        writeln!(out, "  Accrued rewards:    {} QST", qst(info.accrued_rewards))?;
+    }
+    writeln!(
+        out,
+        "  Unlock:             {} ({})",
+        info.unlock_time,
+        if info.is_unlocked { "unlocked".to_string() } else { format!("in {}", format_duration(info.time_until_unlock)) }
+    )?;
+    Ok(())
+}
+
+fn tier_name(tier: PenaltyTier) -> &'static str {
+    match tier {
+        PenaltyTier::Unlocked => "unlocked",
+        PenaltyTier::Late => "late",
+        PenaltyTier::Early => "early",
+        PenaltyTier::Blocked => "blocked",
+    }
+}
+
+fn parse_policy(input: &str) -> Result<UnclaimedBonusPolicy> {
+    match input {
+        "sweep" => Ok(UnclaimedBonusPolicy::Sweep),
+        "redistribute" => Ok(UnclaimedBonusPolicy::Redistribute),
+        "keep" => Ok(UnclaimedBonusPolicy::KeepClaimable),
+        "roll-over" => Ok(UnclaimedBonusPolicy::RollOver),
+        _ => bail!(
+            "unknown unclaimed bonus policy {:?} (expected sweep, redistribute, keep or roll-over)",
+            input
+        ),
+    }
+}
+
+fn policy_name(policy: UnclaimedBonusPolicy) -> &'static str {
+    match policy {
+        UnclaimedBonusPolicy::Sweep => "sweep to fee recipient",
+        UnclaimedBonusPolicy::Redistribute => "redistribute to on-time claimants",
+        UnclaimedBonusPolicy::KeepClaimable => "keep claimable",
+        UnclaimedBonusPolicy::RollOver => "roll over into the next round",
+    }
+}
+
+fn phase_name(phase: StakeWindowPhase) -> &'static str {
+    match phase {
+        StakeWindowPhase::NotStarted => "not started",
+        StakeWindowPhase::Open => "open",
+        StakeWindowPhase::Closed => "closed",
+        StakeWindowPhase::Scheduled => "scheduled",
+    }
+}
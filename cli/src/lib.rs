@@ -0,0 +1,8 @@
+//! `qst-stake`: operator and staker commands for the QST staking pool.
+//!
+//! Commands are generic over [`cluster::Cluster`], so they run against an RPC
+//! endpoint (a local validator included) or any test bank implementing it.
+
+pub mod amount;
+pub mod cluster;
+pub mod commands;
@@ -0,0 +1,158 @@
+use std::io;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use qst_stake::cluster::RpcCluster;
+use qst_stake::commands::{self, CommandContext};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::read_keypair_file;
+
+#[derive(Parser)]
+#[command(name = "qst-stake", about = "Operate and use the QST staking pool")]
+struct Cli {
+    /// RPC endpoint
+    #[arg(long, short = 'u', env = "QST_RPC_URL", default_value = "http://localhost:8899")]
+    url: String,
+
+    /// Signer keypair (admin for admin commands, staker otherwise)
+    #[arg(long, short = 'k', env = "QST_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// Staking program id
+    #[arg(long, default_value_t = qst_staking_client::PROGRAM_ID)]
+    program_id: Pubkey,
+
+    /// Signer's QST token account (default: associated token account)
+    #[arg(long)]
+    token_account: Option<Pubkey>,
+
+    /// Pool's QST token account (default: associated token account of the pool PDA)
+    #[arg(long)]
+    pool_token_account: Option<Pubkey>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Initialize the pool and create its token account (program upgrade authority only)
+    Initialize {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Wallet that receives collected dust (default: the signer)
+        #[arg(long)]
+        fee_recipient: Option<Pubkey>,
+    },
+    /// Open the stake window (admin only, once per round)
+    StartWindow,
+    /// Schedule the stake window to open at a unix timestamp; rerun to reschedule (admin only)
+    ScheduleWindow { start: i64 },
+    /// Cancel a scheduled stake window before it opens (admin only)
+    CancelWindowSchedule,
+    /// Move the open window's end later, up to the maximum window period (admin only)
+    ExtendWindow { end: i64 },
+    /// Move the open window's end earlier, leaving at least the notice period (admin only)
+    CloseWindowEarly { end: i64 },
+    /// Reset a fully withdrawn pool for another window, rolling the penalty vault over (admin only, roll-over policy)
+    StartNextRound,
+    /// Stake QST, e.g. `stake 200000`; --enroll also joins the bonus program in the same transaction
+    Stake {
+        amount: String,
+        #[arg(long)]
+        enroll: bool,
+    },
+    /// Enroll the signer's position in the bonus program
+    Enroll,
+    /// Show the early-unstake quote; pass --yes to unstake at that penalty
+    Unstake {
+        amount: String,
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Withdraw unlocked principal
+    Withdraw,
+    /// Withdraw bonus rewards after the bonus withdrawal time
+    WithdrawBonus,
+    /// Withdraw a share of unclaimed bonus after the claim period (redistribute policy, on-time claimants)
+    WithdrawRedistributedBonus,
+    /// Sweep unclaimed funds to the fee recipient after the claim period (admin only)
+    CollectDust {
+        /// Destination (default: fee recipient's associated token account)
+        #[arg(long)]
+        fee_recipient_token_account: Option<Pubkey>,
+    },
+    /// Choose what happens to unclaimed bonus: sweep, redistribute, keep or roll-over (admin only, before the window)
+    ConfigureUnclaimedBonus {
+        policy: String,
+        /// Seconds after the bonus withdrawal time before the policy applies
+        #[arg(long, default_value_t = qst_staking_client::math::BONUS_CLAIM_PERIOD)]
+        claim_period: i64,
+    },
+    /// Stream QST to enrolled stake between two unix timestamps, e.g. `fund-rewards 50000 <start> <end>` (admin only)
+    FundRewards { amount: String, start: i64, end: i64 },
+    /// Take back emissions released while nothing was enrolled, once the stream has ended (admin only)
+    ReclaimRewards,
+    /// Claim the reward emissions accrued by the signer's position
+    ClaimRewards,
+    /// Rotate the wallet that receives collected dust (admin only)
+    SetFeeRecipient { wallet: Pubkey },
+    /// Show pool state, phases and deadlines
+    Pool,
+    /// Show a position (default: the signer's)
+    User { wallet: Option<Pubkey> },
+}
+
+fn default_keypair_path() -> Result<PathBuf> {
+    let home = std::env::var("HOME").map_err(|_| anyhow!("HOME not set, pass --keypair"))?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let keypair_path = match cli.keypair {
+        Some(path) => path,
+        None => default_keypair_path()?,
+    };
+    let signer = read_keypair_file(&keypair_path)
+        .map_err(|err| anyhow!("failed to read keypair {}: {}", keypair_path.display(), err))?;
+    let cluster = RpcCluster::new(&cli.url);
+    let ctx = CommandContext {
+        cluster: &cluster,
+        program_id: cli.program_id,
+        signer: &signer,
+        user_token_account: cli.token_account,
+        pool_token_account: cli.pool_token_account,
+    };
+    let out = &mut io::stdout();
+
+    match cli.command {
+        Command::Initialize { mint, fee_recipient } => commands::initialize(&ctx, &mint, fee_recipient, out),
+        Command::StartWindow => commands::start_window(&ctx, out),
+        Command::ScheduleWindow { start } => commands::schedule_window(&ctx, start, out),
+        Command::CancelWindowSchedule => commands::cancel_window_schedule(&ctx, out),
+        Command::ExtendWindow { end } => commands::extend_window(&ctx, end, out),
+        Command::CloseWindowEarly { end } => commands::close_window_early(&ctx, end, out),
+        Command::StartNextRound => commands::start_next_round(&ctx, out),
+        Command::Stake { amount, enroll } => commands::stake(&ctx, &amount, enroll, out),
+        Command::Enroll => commands::enroll(&ctx, out),
+        Command::Unstake { amount, yes } => commands::unstake(&ctx, &amount, yes, out),
+        Command::Withdraw => commands::withdraw(&ctx, out),
+        Command::WithdrawBonus => commands::withdraw_bonus(&ctx, out),
+        Command::WithdrawRedistributedBonus => commands::withdraw_redistributed_bonus(&ctx, out),
+        Command::CollectDust { fee_recipient_token_account } => {
+            commands::collect_dust(&ctx, fee_recipient_token_account, out)
+        }
+        Command::ConfigureUnclaimedBonus { policy, claim_period } => {
+            commands::configure_unclaimed_bonus(&ctx, &policy, claim_period, out)
+        }
+        Command::FundRewards { amount, start, end } => commands::fund_rewards(&ctx, &amount, start, end, out),
+        Command::ReclaimRewards => commands::reclaim_rewards(&ctx, out),
+        Command::ClaimRewards => commands::claim_rewards(&ctx, out),
+        Command::SetFeeRecipient { wallet } => commands::set_fee_recipient(&ctx, &wallet, out),
+        Command::Pool => commands::pool_status(&ctx, out),
+        Command::User { wallet } => commands::user_status(&ctx, wallet, out),
+    }
+}
@@ -0,0 +1,37 @@
+use qst_stake::amount::{format_amount, format_duration, parse_amount};
+
+#[test]
+fn parses_whole_and_fractional_amounts() {
+    assert_eq!(parse_amount("200000", 6).unwrap(), 200_000_000_000);
+    assert_eq!(parse_amount("200000.5", 6).unwrap(), 200_000_500_000);
+    assert_eq!(parse_amount("0.000001", 6).unwrap(), 1);
+    assert_eq!(parse_amount(".25", 6).unwrap(), 250_000);
+    assert_eq!(parse_amount("3", 0).unwrap(), 3);
+}
+
+#[test]
+fn rejects_malformed_amounts() {
+    assert!(parse_amount("", 6).is_err());
+    assert!(parse_amount(".", 6).is_err());
+    assert!(parse_amount("-1", 6).is_err());
+    assert!(parse_amount("1e6", 6).is_err());
+    assert!(parse_amount("1.0000001", 6).is_err());
+    assert!(parse_amount("18446744073709551616", 0).is_err());
+    assert!(parse_amount("18446744073709.551616", 6).is_err());
+}
+
+#[test]
+fn formats_amounts_with_mint_decimals() {
+    assert_eq!(format_amount(200_000_000_000, 6), "200000");
+    assert_eq!(format_amount(200_000_500_000, 6), "200000.5");
+    assert_eq!(format_amount(1, 6), "0.000001");
+    assert_eq!(format_amount(1_500, 3), "1.5");
+}
+
+#[test]
+fn formats_durations() {
+    assert_eq!(format_duration(0), "now");
+    assert_eq!(format_duration(90), "1m 30s");
+    assert_eq!(format_duration(3 * 3_600 + 5 * 60), "3h 5m");
+    assert_eq!(format_duration(8 * 86_400 + 7_200), "8d 2h");
+}
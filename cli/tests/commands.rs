@@ -0,0 +1,407 @@
+// Commands against two clusters. FakeCluster is in memory and checks what gets sent;
+// ProgramTestCluster runs the commands end to end against the program, as a host-compiled
+// builtin in a solana-program-test bank with the SPL Token and Associated Token programs.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::{AccountSerialize, AnchorSerialize, InstructionData};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use anchor_spl::token::spl_token::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token::state::Mint;
+use anyhow::{anyhow, Result};
+use qst_stake::cluster::Cluster;
+use qst_stake::commands::{self, CommandContext};
+use qst_staking_client::pda::{find_program_data_address, find_staking_pool_address};
+use qst_staking_client::program::{instruction, PenaltyTier, StakingPool, UnclaimedBonusPolicy, UnstakeQuote};
+use qst_staking_client::state::decode_staking_pool;
+use qst_staking_client::PROGRAM_ID;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use tokio::runtime::Runtime;
+
+const QST: u64 = 1_000_000;
+const DAY: i64 = 24 * 60 * 60;
+const START_TIME: i64 = 1_700_000_000;
+
+struct FakeCluster {
+    accounts: HashMap<Pubkey, Vec<u8>>,
+    view_result: Vec<u8>,
+    sent: RefCell<Vec<Instruction>>,
+}
+
+impl Cluster for FakeCluster {
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+        Ok(self.accounts.get(address).cloned())
+    }
+
+    fn simulate_view(&self, _ix: Instruction, _fee_payer: &Pubkey) -> Result<Vec<u8>> {
+        Ok(self.view_result.clone())
+    }
+
+    fn send(&self, ixs: &[Instruction], _signer: &Keypair) -> Result<Signature> {
+        self.sent.borrow_mut().extend_from_slice(ixs);
+        Ok(Signature::default())
+    }
+}
+
+// Anchor's entry ties the account slice to the AccountInfo lifetime, which the builtin
+// processor signature can't express
+fn builtin_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    qst_staking_mainnet::entry(program_id, accounts, data)
+}
+
+struct ProgramTestCluster {
+    runtime: Runtime,
+    context: ProgramTestContext,
+}
+
+impl ProgramTestCluster {
+    fn new() -> Self {
+        let mut program_test = ProgramTest::default();
+        program_test.add_program("qst_staking_mainnet", PROGRAM_ID, processor!(builtin_entry));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let context = runtime.block_on(program_test.start_with_context());
+        let mut cluster = Self { runtime, context };
+        cluster.warp_to(START_TIME);
+        cluster
+    }
+
+    fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut banks_client = self.context.banks_client.clone();
+        let clock = self.runtime.block_on(banks_client.get_sysvar::<Clock>()).unwrap();
+        self.context.set_sysvar(&Clock { unix_timestamp, ..clock });
+    }
+
+    fn set_account(&mut self, address: Pubkey, owner: Pubkey, data: Vec<u8>) {
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()).max(10_000_000_000),
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(&address, &AccountSharedData::from(account));
+    }
+
+    fn add_wallet(&mut self) -> Keypair {
+        let wallet = Keypair::new();
+        self.set_account(wallet.pubkey(), system_program::ID, Vec::new());
+        wallet
+    }
+
+    // The upgradeable loader's ProgramData, so `initialize` accepts `authority` as the deployer
+    fn set_upgrade_authority(&mut self, authority: &Pubkey) {
+        let state = UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: Some(*authority),
+        };
+        let data = bincode::serialize(&state).unwrap();
+        self.set_account(find_program_data_address(&PROGRAM_ID).0, bpf_loader_upgradeable::ID, data);
+    }
+
+    fn add_mint(&mut self) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let mut data = vec![0u8; Mint::LEN];
+        Mint::pack(
+            Mint {
+                supply: u64::MAX / 2,
+                decimals: 6,
+                is_initialized: true,
+                ..Mint::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        self.set_account(mint, spl_token::ID, data);
+        mint
+    }
+
+    // Funds the owner's associated token account, the CLI's default
+    fn fund_associated_token_account(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) {
+        let account = spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..spl_token::state::Account::default()
+        };
+        let mut data = vec![0u8; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        self.set_account(get_associated_token_address(owner, mint), spl_token::ID, data);
+    }
+
+    fn token_balance(&self, owner: &Pubkey, mint: &Pubkey) -> u64 {
+        let data = self.account_data(&get_associated_token_address(owner, mint)).unwrap().unwrap();
+        spl_token::state::Account::unpack(&data).unwrap().amount
+    }
+
+    fn pool(&self) -> StakingPool {
+        let data = self.account_data(&find_staking_pool_address(&PROGRAM_ID).0).unwrap().unwrap();
+        decode_staking_pool(&data).unwrap()
+    }
+}
+
+impl Cluster for ProgramTestCluster {
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let mut banks_client = self.context.banks_client.clone();
+        let account = self.runtime.block_on(banks_client.get_account(*address))?;
+        Ok(account.map(|account| account.data))
+    }
+
+    fn simulate_view(&self, ix: Instruction, fee_payer: &Pubkey) -> Result<Vec<u8>> {
+        let mut banks_client = self.context.banks_client.clone();
+        let blockhash = self.runtime.block_on(banks_client.get_latest_blockhash())?;
+        let tx = Transaction::new_unsigned(Message::new_with_blockhash(&[ix], Some(fee_payer), &blockhash));
+        let simulation = self.runtime.block_on(banks_client.simulate_transaction(tx))?;
+        if let Some(Err(err)) = simulation.result {
+            return Err(anyhow!("simulation failed: {}", err));
+        }
+        simulation
+            .simulation_details
+            .and_then(|details| details.return_data)
+            .map(|return_data| return_data.data)
+            .ok_or_else(|| anyhow!("view instruction returned no data"))
+    }
+
+    fn send(&self, ixs: &[Instruction], signer: &Keypair) -> Result<Signature> {
+        let mut banks_client = self.context.banks_client.clone();
+        let blockhash = self.runtime.block_on(banks_client.get_latest_blockhash())?;
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&signer.pubkey()), &[signer], blockhash);
+        let signature = tx.signatures[0];
+        self.runtime.block_on(banks_client.process_transaction(tx))?;
+        Ok(signature)
+    }
+}
+
+fn pool_with_mint(mint: Pubkey) -> StakingPool {
+    StakingPool {
+        authority: Pubkey::new_unique(),
+        total_staked: 0,
+        total_enrolled_stake: 0,
+        penalty_vault_amount: 0,
+        first_stake_timestamp: 1_700_000_000,
+        bonus_enrollment_deadline: 1_700_172_800,
+        stake_window_end: 1_700_777_600,
+        latest_bonus_unlock_time: 0,
+        qst_mint: mint,
+        bump: 255,
+        max_total_staked: u64::MAX,
+        max_user_stake: u64::MAX,
+        allowlist_root: [0u8; 32],
+        allowlist_public_time: 0,
+        enrolled_exit_penalty_rate: 40,
+        last_admin_action: 1_700_000_000,
+        admin_inactivity_period: 90 * 86_400,
+        settlement_active: false,
+        settlement_penalty_vault: 0,
+        settlement_total_stake: 0,
+        event_seq: 2,
+        fee_recipient: Pubkey::new_unique(),
+        bonus_claim_period: qst_staking_client::program::BONUS_CLAIM_PERIOD,
+        unclaimed_bonus_policy: UnclaimedBonusPolicy::Sweep,
+        claimed_enrolled_stake: 0,
+        bonus_snapshot_taken: false,
+        bonus_snapshot_vault: 0,
+        bonus_snapshot_weight: 0,
+        redistribution_snapshot_vault: 0,
+        redistribution_snapshot_weight: 0,
+        rounding_dust: 0,
+        reward_vault_amount: 0,
+        reward_stream_amount: 0,
+        reward_start: 0,
+        reward_end: 0,
+        reward_released: 0,
+        reward_per_weight: 0,
+        reward_unallocated: 0,
+        unclaimed_bonus_swept: false,
+        post_snapshot_penalties: 0,
+        total_reward_weight: 0,
+        round: 0,
+    }
+}
+
+fn fake_cluster(view_result: Vec<u8>) -> (FakeCluster, Pubkey) {
+    let mint = Pubkey::new_unique();
+    let mut mint_data = vec![0u8; Mint::LEN];
+    Mint::pack(
+        Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Mint::default()
+        },
+        &mut mint_data,
+    )
+    .unwrap();
+    let mut pool_data = Vec::new();
+    pool_with_mint(mint).try_serialize(&mut pool_data).unwrap();
+
+    let mut accounts = HashMap::new();
+    accounts.insert(mint, mint_data);
+    accounts.insert(find_staking_pool_address(&PROGRAM_ID).0, pool_data);
+    let cluster = FakeCluster {
+        accounts,
+        view_result,
+        sent: RefCell::new(Vec::new()),
+    };
+    (cluster, mint)
+}
+
+fn late_tier_quote(amount: u64) -> Vec<u8> {
+    UnstakeQuote {
+        amount,
+        penalty: amount / 5,
+        net_amount: amount - amount / 5,
+        tier: PenaltyTier::Late,
+        penalty_rate: 20,
+        time_until_next_tier: 3 * 86_400,
+        unstake_allowed: true,
+    }
+    .try_to_vec()
+    .unwrap()
+}
+
+#[test]
+fn unstake_only_previews_without_confirmation() {
+    let (cluster, _) = fake_cluster(late_tier_quote(200_000_000_000));
+    let signer = Keypair::new();
+    let ctx = CommandContext {
+        cluster: &cluster,
+        program_id: PROGRAM_ID,
+        signer: &signer,
+        user_token_account: None,
+        pool_token_account: None,
+    };
+
+    let mut out = Vec::new();
+    commands::unstake(&ctx, "200000", false, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert!(out.contains("Penalty:            40000 QST (20%)"));
+    assert!(out.contains("You receive:        160000 QST"));
+    assert!(out.contains("Next tier in:       3d 0h"));
+    assert!(cluster.sent.borrow().is_empty());
+}
+
+#[test]
+fn unstake_sends_quoted_penalty_as_max_penalty() {
+    let (cluster, mint) = fake_cluster(late_tier_quote(200_000_000_000));
+    let signer = Keypair::new();
+    let ctx = CommandContext {
+        cluster: &cluster,
+        program_id: PROGRAM_ID,
+        signer: &signer,
+        user_token_account: None,
+        pool_token_account: None,
+    };
+
+    commands::unstake(&ctx, "200000", true, &mut Vec::new()).unwrap();
+
+    let sent = cluster.sent.borrow();
+    assert_eq!(sent.len(), 1);
+    let expected = instruction::UnstakeTokens {
+        amount: 200_000_000_000,
+        max_penalty: 40_000_000_000,
+    };
+    assert_eq!(sent[0].data, expected.data());
+    // Default token accounts are the associated token accounts
+    let user_ata = get_associated_token_address(&signer.pubkey(), &mint);
+    assert!(sent[0].accounts.iter().any(|meta| meta.pubkey == user_ata));
+}
+
+#[test]
+fn stake_rejects_amounts_the_program_would_reject() {
+    let (cluster, _) = fake_cluster(Vec::new());
+    let signer = Keypair::new();
+    let ctx = CommandContext {
+        cluster: &cluster,
+        program_id: PROGRAM_ID,
+        signer: &signer,
+        user_token_account: None,
+        pool_token_account: None,
+    };
+
+    assert!(commands::stake(&ctx, "250000", false, &mut Vec::new()).is_err());
+    assert!(cluster.sent.borrow().is_empty());
+
+    commands::stake(&ctx, "400000", false, &mut Vec::new()).unwrap();
+    assert_eq!(
+        cluster.sent.borrow()[0].data,
+        instruction::StakeTokens { amount: 400_000_000_000 }.data()
+    );
+
+    commands::stake(&ctx, "200000", true, &mut Vec::new()).unwrap();
+    assert_eq!(
+        cluster.sent.borrow()[1].data,
+        instruction::StakeAndEnroll { amount: 200_000_000_000 }.data()
+    );
+}
+
+#[test]
+fn stake_quote_and_unstake_against_the_program() {
+    let mut cluster = ProgramTestCluster::new();
+    let admin = cluster.add_wallet();
+    cluster.set_upgrade_authority(&admin.pubkey());
+    let mint = cluster.add_mint();
+    let staker = cluster.add_wallet();
+    cluster.fund_associated_token_account(&staker.pubkey(), &mint, 400_000 * QST);
+
+    let admin_ctx = CommandContext {
+        cluster: &cluster,
+        program_id: PROGRAM_ID,
+        signer: &admin,
+        user_token_account: None,
+        pool_token_account: None,
+    };
+    commands::initialize(&admin_ctx, &mint, None, &mut Vec::new()).unwrap();
+    commands::start_window(&admin_ctx, &mut Vec::new()).unwrap();
+
+    let ctx = CommandContext {
+        cluster: &cluster,
+        program_id: PROGRAM_ID,
+        signer: &staker,
+        user_token_account: None,
+        pool_token_account: None,
+    };
+    commands::stake(&ctx, "400000", false, &mut Vec::new()).unwrap();
+    assert_eq!(cluster.pool().total_staked, 400_000 * QST);
+    assert_eq!(cluster.token_balance(&staker.pubkey(), &mint), 0);
+
+    // A week before the principal unlock: the late tier's 20%
+    cluster.warp_to(START_TIME + 18 * DAY);
+    let ctx = CommandContext {
+        cluster: &cluster,
+        program_id: PROGRAM_ID,
+        signer: &staker,
+        user_token_account: None,
+        pool_token_account: None,
+    };
+    let mut out = Vec::new();
+    commands::unstake(&ctx, "200000", false, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("Penalty:            40000 QST (20%)"));
+    assert!(out.contains("You receive:        160000 QST"));
+    assert_eq!(cluster.pool().total_staked, 400_000 * QST);
+
+    commands::unstake(&ctx, "200000", true, &mut Vec::new()).unwrap();
+    assert_eq!(cluster.token_balance(&staker.pubkey(), &mint), 160_000 * QST);
+    let pool = cluster.pool();
+    assert_eq!(pool.total_staked, 200_000 * QST);
+    assert_eq!(pool.penalty_vault_amount, 40_000 * QST);
+}
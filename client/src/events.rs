@@ -0,0 +1,130 @@
+//! Event decoding.
+//!
+//! The program emits through `emit_cpi!`: each event is the data of a self-invoked
+//! inner instruction, `EVENT_IX_TAG_LE || discriminator || borsh`. Transactions from
+//! before the switch used `emit!`, which logs the same `discriminator || borsh`
+//! payload base64-encoded on a `Program data:` line.
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+pub use qst_staking_mainnet::{
+    AdminHeartbeatRecorded, AdminInactivityPeriodUpdated, AllowlistConfigured, AuditEvent,
+    AuthorityChanged, BonusEnrollment, BonusRedistributed, BonusUnenrollment, BonusWithdrawEvent,
+    DustCollected, EnrolledExitEvent, EnrolledExitPenaltyUpdated, FeeRecipientChanged, NextRoundStarted,
+    PoolInitialized, PositionSettled, RewardsClaimed, RewardsFunded, SettlementTriggered, StakeCapsUpdated,
+    StakeEvent,
+    StakeWindowClosedEarly, StakeWindowExtended, StakeWindowScheduleCancelled, StakeWindowScheduled,
+    StakeWindowStarted, UnallocatedRewardsReclaimed, UnclaimedBonusPolicyUpdated, UnstakeEvent,
+    WithdrawAllEvent,
+};
+
+macro_rules! qst_events {
+    ($($name:ident),* $(,)?) => {
+        pub enum QstEvent {
+            $($name($name),)*
+        }
+
+        impl QstEvent {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(QstEvent::$name(_) => stringify!($name),)*
+                }
+            }
+
+            // Pool-wide sequence number; consecutive events differ by exactly 1
+            pub fn seq(&self) -> u64 {
+                match self {
+                    $(QstEvent::$name(event) => event.seq,)*
+                }
+            }
+        }
+
+        // `discriminator || borsh`, as logged by emit! or carried by emit_cpi!
+        pub fn decode_event(data: &[u8]) -> Option<QstEvent> {
+            if data.len() < 8 {
+                return None;
+            }
+            let (discriminator, mut payload) = data.split_at(8);
+            $(
+                if discriminator == $name::DISCRIMINATOR {
+                    return $name::deserialize(&mut payload).ok().map(QstEvent::$name);
+                }
+            )*
+            None
+        }
+    };
+}
+
+qst_events!(
+    PoolInitialized,
+    AuthorityChanged,
+    FeeRecipientChanged,
+    AdminHeartbeatRecorded,
+    EnrolledExitPenaltyUpdated,
+    AdminInactivityPeriodUpdated,
+    UnclaimedBonusPolicyUpdated,
+    RewardsFunded,
+    UnallocatedRewardsReclaimed,
+    StakeWindowStarted,
+    StakeWindowScheduled,
+    StakeWindowScheduleCancelled,
+    StakeWindowExtended,
+    StakeWindowClosedEarly,
+    NextRoundStarted,
+    StakeCapsUpdated,
+    AllowlistConfigured,
+    BonusEnrollment,
+    BonusUnenrollment,
+    StakeEvent,
+    UnstakeEvent,
+    EnrolledExitEvent,
+    WithdrawAllEvent,
+    SettlementTriggered,
+    PositionSettled,
+    BonusWithdrawEvent,
+    BonusRedistributed,
+    RewardsClaimed,
+    DustCollected,
+    AuditEvent,
+);
+
+// Data of an inner instruction the program invoked on itself through emit_cpi!.
+// Returns None for anything that is not an event of this program.
+pub fn decode_cpi_event(ix_data: &[u8]) -> Option<QstEvent> {
+    ix_data
+        .strip_prefix(&EVENT_IX_TAG_LE[..])
+        .and_then(decode_event)
+}
+
+// Events from `Program data:` lines logged while `program_id` is the executing
+// program. Lines logged by other programs (including CPIs made from ours) are skipped.
+pub fn parse_logs(program_id: &Pubkey, logs: &[String]) -> Vec<QstEvent> {
+    let program_id = program_id.to_string();
+    let mut invoke_stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let Some(rest) = log.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(encoded) = rest.strip_prefix("data: ") {
+            if invoke_stack.last() == Some(&program_id.as_str()) {
+                if let Some(event) = STANDARD.decode(encoded).ok().as_deref().and_then(decode_event) {
+                    events.push(event);
+                }
+            }
+        } else if let Some((id, tail)) = rest.split_once(' ') {
+            if tail.starts_with("invoke [") {
+                invoke_stack.push(id);
+            } else if tail == "success" || tail.starts_with("failed") {
+                invoke_stack.pop();
+            }
+        }
+    }
+
+    events
+}
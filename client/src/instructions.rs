@@ -0,0 +1,688 @@
+//! One builder per program instruction. Account order and argument encoding come
+//! from the program's anchor-generated `accounts` and `instruction` modules, so
+//! a builder stops compiling when the program's interface changes.
+
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, InstructionData, ToAccountMetas};
+use qst_staking_mainnet::{accounts, instruction, UnclaimedBonusPolicy};
+
+use crate::pda::{
+    find_event_authority_address, find_program_data_address, find_reward_vault_address,
+    find_stake_account_address, find_staking_pool_address,
+};
+
+fn build(program_id: &Pubkey, accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn staking_pool(program_id: &Pubkey) -> Pubkey {
+    find_staking_pool_address(program_id).0
+}
+
+fn stake_account(program_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    find_stake_account_address(program_id, user).0
+}
+
+fn event_authority(program_id: &Pubkey) -> Pubkey {
+    find_event_authority_address(program_id).0
+}
+
+// === ADMIN ===
+
+// `payer` must be the program's upgrade authority
+pub fn initialize(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    qst_mint: &Pubkey,
+    admin_wallet: &Pubkey,
+    fee_recipient: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::Initialize {
+            staking_pool: staking_pool(program_id),
+            qst_mint: *qst_mint,
+            payer: *payer,
+            program_data: find_program_data_address(program_id).0,
+            system_program: system_program::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::Initialize {
+            admin_wallet: *admin_wallet,
+            fee_recipient: *fee_recipient,
+        },
+    )
+}
+
+pub fn start_stake_window(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::StartStakeWindow {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::StartStakeWindow {},
+    )
+}
+
+pub fn schedule_stake_window(program_id: &Pubkey, admin: &Pubkey, start_ts: i64) -> Instruction {
+    build(
+        program_id,
+        accounts::ScheduleStakeWindow {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ScheduleStakeWindow { start_ts },
+    )
+}
+
+pub fn cancel_stake_window_schedule(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::CancelStakeWindowSchedule {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::CancelStakeWindowSchedule {},
+    )
+}
+
+pub fn extend_stake_window(program_id: &Pubkey, admin: &Pubkey, new_window_end: i64) -> Instruction {
+    build(
+        program_id,
+        accounts::ExtendStakeWindow {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ExtendStakeWindow { new_window_end },
+    )
+}
+
+pub fn close_stake_window_early(program_id: &Pubkey, admin: &Pubkey, new_window_end: i64) -> Instruction {
+    build(
+        program_id,
+        accounts::CloseStakeWindowEarly {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::CloseStakeWindowEarly { new_window_end },
+    )
+}
+
+pub fn configure_stake_caps(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    max_total_staked: u64,
+    max_user_stake: u64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ConfigureStakeCaps {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ConfigureStakeCaps { max_total_staked, max_user_stake },
+    )
+}
+
+pub fn configure_allowlist(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    merkle_root: [u8; 32],
+    public_time: i64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ConfigureAllowlist {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ConfigureAllowlist { merkle_root, public_time },
+    )
+}
+
+pub fn configure_enrolled_exit_penalty(program_id: &Pubkey, admin: &Pubkey, penalty_rate: u64) -> Instruction {
+    build(
+        program_id,
+        accounts::ConfigureEnrolledExitPenalty {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ConfigureEnrolledExitPenalty { penalty_rate },
+    )
+}
+
+pub fn configure_admin_inactivity_period(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    inactivity_period: i64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ConfigureAdminInactivityPeriod {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ConfigureAdminInactivityPeriod { inactivity_period },
+    )
+}
+
+pub fn configure_unclaimed_bonus(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    policy: UnclaimedBonusPolicy,
+    claim_period: i64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ConfigureUnclaimedBonus {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ConfigureUnclaimedBonus { policy, claim_period },
+    )
+}
+
+// `admin` pays rent for the reward vault on the first funding
+pub fn fund_rewards(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    admin_token_account: &Pubkey,
+    qst_mint: &Pubkey,
+    amount: u64,
+    start: i64,
+    end: i64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::FundRewards {
+            staking_pool: staking_pool(program_id),
+            reward_vault: find_reward_vault_address(program_id).0,
+            qst_mint: *qst_mint,
+            admin: *admin,
+            admin_token_account: *admin_token_account,
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::FundRewards { amount, start, end },
+    )
+}
+
+pub fn reclaim_unallocated_rewards(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    admin_token_account: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ReclaimUnallocatedRewards {
+            staking_pool: staking_pool(program_id),
+            reward_vault: find_reward_vault_address(program_id).0,
+            admin: *admin,
+            admin_token_account: *admin_token_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ReclaimUnallocatedRewards {},
+    )
+}
+
+pub fn start_next_round(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::StartNextRound {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::StartNextRound {},
+    )
+}
+
+pub fn admin_heartbeat(program_id: &Pubkey, admin: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::AdminHeartbeat {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::AdminHeartbeat {},
+    )
+}
+
+pub fn set_authority(program_id: &Pubkey, admin: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::SetAuthority {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::SetAuthority { new_authority: *new_authority },
+    )
+}
+
+pub fn set_fee_recipient(program_id: &Pubkey, admin: &Pubkey, new_fee_recipient: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::SetFeeRecipient {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::SetFeeRecipient { new_fee_recipient: *new_fee_recipient },
+    )
+}
+
+pub fn collect_dust(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    pool_token_account: &Pubkey,
+    fee_recipient_token_account: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::CollectDust {
+            staking_pool: staking_pool(program_id),
+            admin: *admin,
+            pool_token_account: *pool_token_account,
+            fee_recipient_token_account: *fee_recipient_token_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::CollectDust {},
+    )
+}
+
+// === USER ===
+
+pub fn enroll_in_bonus(program_id: &Pubkey, user: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::EnrollInBonus {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::EnrollInBonus {},
+    )
+}
+
+pub fn unenroll_from_bonus(program_id: &Pubkey, user: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::UnenrollFromBonus {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::UnenrollFromBonus {},
+    )
+}
+
+fn stake_tokens_accounts(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+) -> accounts::StakeTokens {
+    accounts::StakeTokens {
+        staking_pool: staking_pool(program_id),
+        stake_account: stake_account(program_id, user),
+        user: *user,
+        user_token_account: *user_token_account,
+        pool_token_account: *pool_token_account,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        event_authority: event_authority(program_id),
+        program: *program_id,
+    }
+}
+
+pub fn stake_tokens(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        program_id,
+        stake_tokens_accounts(program_id, user, user_token_account, pool_token_account),
+        instruction::StakeTokens { amount },
+    )
+}
+
+pub fn stake_and_enroll(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        program_id,
+        stake_tokens_accounts(program_id, user, user_token_account, pool_token_account),
+        instruction::StakeAndEnroll { amount },
+    )
+}
+
+pub fn stake_tokens_allowlisted(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+    amount: u64,
+    max_allocation: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    build(
+        program_id,
+        stake_tokens_accounts(program_id, user, user_token_account, pool_token_account),
+        instruction::StakeTokensAllowlisted { amount, max_allocation, proof },
+    )
+}
+
+pub fn stake_allowlisted_and_enroll(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+    amount: u64,
+    max_allocation: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    build(
+        program_id,
+        stake_tokens_accounts(program_id, user, user_token_account, pool_token_account),
+        instruction::StakeAllowlistedAndEnroll { amount, max_allocation, proof },
+    )
+}
+
+pub fn unstake_tokens(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+    amount: u64,
+    max_penalty: u64,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::UnstakeTokens {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            user_token_account: *user_token_account,
+            pool_token_account: *pool_token_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::UnstakeTokens { amount, max_penalty },
+    )
+}
+
+pub fn exit_enrolled(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::ExitEnrolled {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            user_token_account: *user_token_account,
+            pool_token_account: *pool_token_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ExitEnrolled {},
+    )
+}
+
+pub fn withdraw_all(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::WithdrawAll {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            user_token_account: *user_token_account,
+            pool_token_account: *pool_token_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::WithdrawAll {},
+    )
+}
+
+pub fn withdraw_bonus(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::WithdrawBonus {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            user_token_account: *user_token_account,
+            pool_token_account: *pool_token_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::WithdrawBonus {},
+    )
+}
+
+pub fn withdraw_redistributed_bonus(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::WithdrawRedistributedBonus {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            user_token_account: *user_token_account,
+            pool_token_account: *pool_token_account,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::WithdrawRedistributedBonus {},
+    )
+}
+
+pub fn claim_rewards(program_id: &Pubkey, user: &Pubkey, user_token_account: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::ClaimRewards {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+            user_token_account: *user_token_account,
+            reward_vault: find_reward_vault_address(program_id).0,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::ClaimRewards {},
+    )
+}
+
+// === SETTLEMENT (permissionless) ===
+
+pub fn trigger_settlement(program_id: &Pubkey, caller: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::TriggerSettlement {
+            staking_pool: staking_pool(program_id),
+            caller: *caller,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::TriggerSettlement {},
+    )
+}
+
+// `owner_token_account` must belong to `owner`; the cranker only signs and pays fees
+pub fn settle_position(
+    program_id: &Pubkey,
+    cranker: &Pubkey,
+    owner: &Pubkey,
+    owner_token_account: &Pubkey,
+    pool_token_account: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        accounts::SettlePosition {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, owner),
+            user_token_account: *owner_token_account,
+            pool_token_account: *pool_token_account,
+            cranker: *cranker,
+            token_program: anchor_spl::token::ID,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::SettlePosition {},
+    )
+}
+
+// === AUDIT (permissionless) ===
+
+// Checks pool solvency plus the positions of `users`; fails with the violated invariant
+pub fn audit_pool(
+    program_id: &Pubkey,
+    auditor: &Pubkey,
+    pool_token_account: &Pubkey,
+    users: &[Pubkey],
+) -> Instruction {
+    let mut ix = build(
+        program_id,
+        accounts::AuditPool {
+            staking_pool: staking_pool(program_id),
+            pool_token_account: *pool_token_account,
+            auditor: *auditor,
+            event_authority: event_authority(program_id),
+            program: *program_id,
+        },
+        instruction::AuditPool {},
+    );
+    ix.accounts.extend(
+        users
+            .iter()
+            .map(|user| AccountMeta::new_readonly(stake_account(program_id, user), false)),
+    );
+    ix
+}
+
+// === VIEWS (simulate and read the return data, see state::decode_return_data) ===
+
+pub fn get_stake_info(program_id: &Pubkey, user: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::GetStakeInfo {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+        },
+        instruction::GetStakeInfo {},
+    )
+}
+
+// At most MAX_STAKE_INFO_BATCH users per call
+pub fn get_stake_infos(program_id: &Pubkey, users: &[Pubkey]) -> Instruction {
+    let mut ix = build(
+        program_id,
+        accounts::GetStakeInfos {
+            staking_pool: staking_pool(program_id),
+        },
+        instruction::GetStakeInfos {},
+    );
+    ix.accounts.extend(
+        users
+            .iter()
+            .map(|user| AccountMeta::new_readonly(stake_account(program_id, user), false)),
+    );
+    ix
+}
+
+pub fn quote_unstake(program_id: &Pubkey, user: &Pubkey, amount: u64) -> Instruction {
+    build(
+        program_id,
+        accounts::QuoteUnstake {
+            staking_pool: staking_pool(program_id),
+            stake_account: stake_account(program_id, user),
+            user: *user,
+        },
+        instruction::QuoteUnstake { amount },
+    )
+}
+
+pub fn get_pool_info(program_id: &Pubkey) -> Instruction {
+    build(
+        program_id,
+        accounts::GetPoolInfo {
+            staking_pool: staking_pool(program_id),
+        },
+        instruction::GetPoolInfo {},
+    )
+}
@@ -0,0 +1,19 @@
+//! Rust client for the QST staking program.
+//!
+//! - [`pda`]: `staking_pool`, `stake_account`, reward vault, event authority and program data addresses
+//! - [`instructions`]: typed builders for every program instruction
+//! - [`state`]: `StakingPool` / `StakeAccount` decoders and view return data
+//! - [`events`]: event decoding from transaction logs and `emit_cpi!` inner instructions
+//! - [`math`]: penalty and bonus math mirrored from the program
+//!
+//! Builders and PDA helpers take the program id explicitly so the same client
+//! works against mainnet and devnet deployments.
+
+pub mod events;
+pub mod instructions;
+pub mod math;
+pub mod pda;
+pub mod state;
+
+pub use qst_staking_mainnet as program;
+pub use qst_staking_mainnet::ID as PROGRAM_ID;
@@ -0,0 +1,80 @@
+//! Penalty, key-issuance, bonus and timing math for previewing positions without an
+//! RPC round trip. Constants come from the program crate and the economics delegate to
+//! `qst-staking-math`, the crate the program itself calls; `tests/math_sync.rs` pins every
+//! function here to fixed values from the penalty schedule and the program's timing.
+
+use qst_staking_mainnet::PenaltyTier;
+
+// The program's own constants and rules, re-exported so previews can't drift from the chain
+pub use qst_staking_mainnet::{
+    BONUS_CLAIM_PERIOD, BONUS_ENROLLMENT_PERIOD, BONUS_LOCK_PERIOD, BONUS_WITHDRAWAL_DELAY,
+    EARLY_UNSTAKE_THRESHOLD_1, EARLY_UNSTAKE_THRESHOLD_2, ENROLLED_EXIT_PENALTY_RATE, KEYS_PER_STAKE,
+    MAXIMUM_STAKE_AMOUNT, MAX_STAKE_WINDOW_PERIOD, MINIMUM_STAKE_AMOUNT, MIN_BONUS_CLAIM_PERIOD,
+    PENALTY_RATE_EARLY, PENALTY_RATE_LATE, PENALTY_SCHEDULE, PRINCIPAL_LOCK_PERIOD, REWARD_PRECISION,
+    STAKE_RULES, STAKE_WINDOW_PERIOD,
+};
+pub use qst_staking_math::StakeError;
+
+// stake_tokens: amount must be a whole multiple of MINIMUM_STAKE_AMOUNT
+pub fn is_valid_stake_amount(amount: u64) -> bool {
+    node_keys_for(amount).is_ok()
+}
+
+// stake_tokens: node keys issued for one stake, or why the program rejects the amount
+pub fn node_keys_for(amount: u64) -> Result<u32, StakeError> {
+    STAKE_RULES.node_keys_for(amount)
+}
+
+// Total stake behind a position's node keys
+pub fn original_stake(node_keys_earned: u32) -> u64 {
+    STAKE_RULES.original_stake(node_keys_earned)
+}
+
+pub fn penalty_tier(time_until_unlock: i64) -> PenaltyTier {
+    PENALTY_SCHEDULE.tier(time_until_unlock).into()
+}
+
+pub fn penalty_rate(tier: PenaltyTier) -> u64 {
+    PENALTY_SCHEDULE.rate(tier.into())
+}
+
+pub use qst_staking_math::penalty_amount;
+
+// unstake_tokens: (penalty, net to user); None while the tier is Blocked
+pub fn unstake_outcome(amount: u64, time_until_unlock: i64) -> Option<(u64, u64)> {
+    PENALTY_SCHEDULE.unstake_outcome(amount, time_until_unlock)
+}
+
+// Seconds until a position moves to the next (cheaper) tier; 0 once unlocked
+pub fn seconds_until_next_tier(time_until_unlock: i64) -> i64 {
+    PENALTY_SCHEDULE.seconds_until_next(PENALTY_SCHEDULE.tier(time_until_unlock), time_until_unlock)
+}
+
+// withdraw_bonus / get_stake_info / settle_position pro-rata split
+pub use qst_staking_math::pro_rata_share;
+
+// fund_rewards emissions released by a given time
+pub use qst_staking_math::stream_released;
+
+pub fn principal_unlock_time(stake_time: i64) -> i64 {
+    stake_time + PRINCIPAL_LOCK_PERIOD
+}
+
+pub fn bonus_unlock_time(principal_unlock_time: i64) -> i64 {
+    principal_unlock_time + BONUS_LOCK_PERIOD
+}
+
+// Last principal or bonus unlock in the pool; settlement waits for it
+pub fn final_unlock_time(stake_window_end: i64, latest_bonus_unlock_time: i64) -> i64 {
+    (stake_window_end + PRINCIPAL_LOCK_PERIOD).max(latest_bonus_unlock_time)
+}
+
+// Fixed once enrollment closes; window extensions don't move it
+pub fn bonus_withdrawal_time(latest_bonus_unlock_time: i64, bonus_enrollment_deadline: i64) -> i64 {
+    latest_bonus_unlock_time.max(bonus_enrollment_deadline) + BONUS_WITHDRAWAL_DELAY
+}
+
+// `claim_period` is the pool's configured `bonus_claim_period`
+pub fn bonus_claim_deadline(latest_bonus_unlock_time: i64, bonus_enrollment_deadline: i64, claim_period: i64) -> i64 {
+    bonus_withdrawal_time(latest_bonus_unlock_time, bonus_enrollment_deadline) + claim_period
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+pub const STAKING_POOL_SEED: &[u8] = b"staking_pool";
+pub const STAKE_ACCOUNT_SEED: &[u8] = b"stake_account";
+pub const REWARD_VAULT_SEED: &[u8] = b"reward_vault";
+// Fixed by anchor's #[event_cpi]
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub fn find_staking_pool_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKING_POOL_SEED], program_id)
+}
+
+pub fn find_stake_account_address(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_ACCOUNT_SEED, user.as_ref()], program_id)
+}
+
+// Token account holding fund_rewards emissions, owned by the staking pool
+pub fn find_reward_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[REWARD_VAULT_SEED], program_id)
+}
+
+pub fn find_event_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], program_id)
+}
+
+// ProgramData account of an upgradeable program; `initialize` checks its upgrade authority
+pub fn find_program_data_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID)
+}
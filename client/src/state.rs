@@ -0,0 +1,22 @@
+use anchor_lang::error::ErrorCode as AnchorErrorCode;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Result};
+
+pub use qst_staking_mainnet::{
+    PenaltyTier, PoolInfo, StakeAccount, StakeInfo, StakeWindowPhase, StakingPool, UnclaimedBonusPolicy,
+    UnstakeQuote,
+};
+
+// Raw account data, discriminator included (as returned by getAccountInfo)
+pub fn decode_staking_pool(data: &[u8]) -> Result<StakingPool> {
+    StakingPool::try_deserialize(&mut &data[..])
+}
+
+pub fn decode_stake_account(data: &[u8]) -> Result<StakeAccount> {
+    StakeAccount::try_deserialize(&mut &data[..])
+}
+
+// Return data of a simulated view instruction: StakeInfo, Vec<StakeInfo>,
+// UnstakeQuote or PoolInfo. Anchor writes the borsh value with no discriminator.
+pub fn decode_return_data<T: AnchorDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_from_slice(data).map_err(|_| AnchorErrorCode::AccountDidNotDeserialize.into())
+}
@@ -0,0 +1,71 @@
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Event;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use qst_staking_client::events::{self, QstEvent, StakeEvent, UnstakeEvent};
+
+fn stake_event(seq: u64) -> StakeEvent {
+    StakeEvent {
+        user: Pubkey::new_unique(),
+        amount: 200_000_000_000,
+        total_staked: 400_000_000_000,
+        node_keys_earned: 2,
+        principal_unlock_time: 1_702_160_000,
+        bonus_unlock_time: 0,
+        enrolled_in_bonus: false,
+        timestamp: 1_700_000_000,
+        seq,
+    }
+}
+
+#[test]
+fn decodes_emit_cpi_instruction_data() {
+    let event = stake_event(7);
+    let mut ix_data = EVENT_IX_TAG_LE.to_vec();
+    ix_data.extend(event.data());
+
+    match events::decode_cpi_event(&ix_data) {
+        Some(QstEvent::StakeEvent(decoded)) => {
+            assert_eq!(decoded.user, event.user);
+            assert_eq!(decoded.amount, event.amount);
+            assert_eq!(decoded.seq, 7);
+        }
+        _ => panic!("expected a StakeEvent"),
+    }
+
+    // Without the event tag this is an ordinary instruction, not an event
+    assert!(events::decode_cpi_event(&event.data()).is_none());
+}
+
+#[test]
+fn parses_program_data_logs_of_the_program_only() {
+    let program_id = qst_staking_client::PROGRAM_ID;
+    let token_program = anchor_spl::token::ID;
+    let unstake = UnstakeEvent {
+        user: Pubkey::new_unique(),
+        amount: 200_000_000_000,
+        remaining_staked: 0,
+        penalty_applied: 40_000_000_000,
+        net_to_user: 160_000_000_000,
+        penalty_vault_total: 40_000_000_000,
+        timestamp: 1_700_000_000,
+        seq: 9,
+    };
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Instruction: UnstakeTokens".to_string(),
+        format!("Program {} invoke [2]", token_program),
+        // Logged by the token program, not ours
+        format!("Program data: {}", STANDARD.encode(stake_event(1).data())),
+        format!("Program {} success", token_program),
+        format!("Program data: {}", STANDARD.encode(unstake.data())),
+        format!("Program {} consumed 21000 of 200000 compute units", program_id),
+        format!("Program {} success", program_id),
+    ];
+
+    let decoded = events::parse_logs(&program_id, &logs);
+    assert_eq!(decoded.len(), 1);
+    assert_eq!(decoded[0].name(), "UnstakeEvent");
+    assert_eq!(decoded[0].seq(), 9);
+}
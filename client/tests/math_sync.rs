@@ -0,0 +1,184 @@
+// The client's math against fixed values from the penalty schedule and the program's
+// constants, so a change on either side that moves a number fails here. The timing helpers
+// are checked against the program's own StakingPool methods.
+
+use anchor_lang::prelude::Pubkey;
+use qst_staking_client::math;
+use qst_staking_mainnet as program;
+use qst_staking_mainnet::{PenaltyTier, StakingPool};
+
+const QST: u64 = 1_000_000;
+const DAY: i64 = 24 * 60 * 60;
+
+#[test]
+fn penalty_schedule_table() {
+    // (seconds until unlock, tier, rate, penalty on 1M QST, seconds until the next tier)
+    let table = [
+        (i64::MIN, PenaltyTier::Unlocked, 0, 0, 0),
+        (-DAY, PenaltyTier::Unlocked, 0, 0, 0),
+        (0, PenaltyTier::Unlocked, 0, 0, 0),
+        (1, PenaltyTier::Late, 20, 200_000 * QST, 1),
+        (7 * DAY, PenaltyTier::Late, 20, 200_000 * QST, 7 * DAY),
+        (7 * DAY + 1, PenaltyTier::Early, 30, 300_000 * QST, 1),
+        (15 * DAY, PenaltyTier::Early, 30, 300_000 * QST, 8 * DAY),
+        (15 * DAY + 1, PenaltyTier::Blocked, 0, 0, 1),
+        (25 * DAY, PenaltyTier::Blocked, 0, 0, 10 * DAY),
+    ];
+    for (time_until_unlock, tier, rate, penalty, next_tier) in table {
+        assert_eq!(math::penalty_tier(time_until_unlock), tier, "tier at {}", time_until_unlock);
+        assert_eq!(math::penalty_rate(tier), rate);
+        assert_eq!(math::seconds_until_next_tier(time_until_unlock), next_tier);
+        let outcome = math::unstake_outcome(1_000_000 * QST, time_until_unlock);
+        if tier == PenaltyTier::Blocked {
+            assert_eq!(outcome, None);
+        } else {
+            assert_eq!(outcome, Some((penalty, 1_000_000 * QST - penalty)));
+        }
+    }
+}
+
+#[test]
+fn penalty_amounts_round_down() {
+    let table = [
+        (1, 20, 0),
+        (5, 20, 1),
+        (200_000 * QST, 40, 80_000 * QST),
+        (u64::MAX, 20, 3_689_348_814_741_910_323),
+        (u64::MAX, 30, 5_534_023_222_112_865_484),
+    ];
+    for (amount, rate, penalty) in table {
+        assert_eq!(math::penalty_amount(amount, rate), penalty, "{}% of {}", rate, amount);
+    }
+}
+
+#[test]
+fn bonus_split_table() {
+    // (amount, position weight, total weight, share)
+    let table = [
+        (0, 0, 0, 0),
+        (1_000, 1, 0, 0),
+        (1_000, 1, 3, 333),
+        (7_000_000, 200_000 * QST, 600_000 * QST, 2_333_333),
+        (40_000 * QST, 400_000 * QST, 1_400_000 * QST, 11_428_571_428),
+        (u64::MAX, u64::MAX, u64::MAX, u64::MAX),
+        (u64::MAX, 1, 2, 9_223_372_036_854_775_807),
+    ];
+    for (amount, user_amount, total, share) in table {
+        assert_eq!(math::pro_rata_share(amount, user_amount, total), share);
+    }
+}
+
+#[test]
+fn reward_stream_table() {
+    let (start, end) = (1_700_000_000, 1_700_000_000 + 30 * DAY);
+    let amount = 50_000 * QST;
+    let table = [
+        (0, 0),
+        (start - 1, 0),
+        (start, 0),
+        (start + 1, 19_290),
+        (start + 7 * DAY, 11_666_666_666),
+        (start + 15 * DAY, 25_000 * QST),
+        (end - 1, 49_999_980_709),
+        (end, amount),
+        (end + DAY, amount),
+    ];
+    for (current_time, released) in table {
+        assert_eq!(math::stream_released(amount, start, end, current_time), released, "at {}", current_time);
+    }
+    assert_eq!(math::stream_released(u64::MAX, start, end, end), u64::MAX);
+}
+
+#[test]
+fn key_issuance_table() {
+    let table = [
+        (200_000 * QST, 2),
+        (400_000 * QST, 4),
+        (1_400_000 * QST, 14),
+        (10_000_000 * QST, 100),
+    ];
+    for (amount, node_keys) in table {
+        assert!(math::is_valid_stake_amount(amount));
+        assert_eq!(math::node_keys_for(amount), Ok(node_keys));
+        assert_eq!(math::original_stake(node_keys), amount);
+    }
+    // Odd key counts can't come from a stake; the weight rounds down to whole stakes
+    assert_eq!(math::original_stake(3), 200_000 * QST);
+
+    // Rejections carry the program's reason
+    assert_eq!(math::node_keys_for(0), Err(math::StakeError::Zero));
+    assert_eq!(math::node_keys_for(200_000 * QST - 1), Err(math::StakeError::BelowMinimum));
+    assert_eq!(math::node_keys_for(200_000 * QST + 1), Err(math::StakeError::NotMultiple));
+    assert_eq!(math::node_keys_for(250_000 * QST), Err(math::StakeError::NotMultiple));
+    assert_eq!(math::node_keys_for(10_200_000 * QST), Err(math::StakeError::AboveMaximum));
+}
+
+#[test]
+fn bonus_timing_matches_program() {
+    let stake_time = 1_700_000_000;
+    let principal_unlock = math::principal_unlock_time(stake_time);
+    let latest_bonus_unlock_time = math::bonus_unlock_time(principal_unlock);
+
+    let pool = StakingPool {
+        authority: Pubkey::new_unique(),
+        total_staked: 0,
+        total_enrolled_stake: 0,
+        penalty_vault_amount: 0,
+        first_stake_timestamp: stake_time,
+        bonus_enrollment_deadline: stake_time + program::BONUS_ENROLLMENT_PERIOD,
+        stake_window_end: stake_time + program::STAKE_WINDOW_PERIOD,
+        latest_bonus_unlock_time,
+        qst_mint: Pubkey::new_unique(),
+        bump: 255,
+        max_total_staked: u64::MAX,
+        max_user_stake: u64::MAX,
+        allowlist_root: [0u8; 32],
+        allowlist_public_time: 0,
+        enrolled_exit_penalty_rate: program::ENROLLED_EXIT_PENALTY_RATE,
+        last_admin_action: stake_time,
+        admin_inactivity_period: program::ADMIN_INACTIVITY_PERIOD,
+        settlement_active: false,
+        settlement_penalty_vault: 0,
+        settlement_total_stake: 0,
+        event_seq: 0,
+        fee_recipient: Pubkey::new_unique(),
+        bonus_claim_period: 90 * DAY,
+        unclaimed_bonus_policy: program::UnclaimedBonusPolicy::Sweep,
+        claimed_enrolled_stake: 0,
+        bonus_snapshot_taken: false,
+        bonus_snapshot_vault: 0,
+        bonus_snapshot_weight: 0,
+        redistribution_snapshot_vault: 0,
+        redistribution_snapshot_weight: 0,
+        rounding_dust: 0,
+        reward_vault_amount: 0,
+        reward_stream_amount: 0,
+        reward_start: 0,
+        reward_end: 0,
+        reward_released: 0,
+        reward_per_weight: 0,
+        reward_unallocated: 0,
+        unclaimed_bonus_swept: false,
+        post_snapshot_penalties: 0,
+        total_reward_weight: 0,
+        round: 0,
+    };
+
+    assert_eq!(latest_bonus_unlock_time, stake_time + 35 * DAY);
+    let final_unlock_time = math::final_unlock_time(pool.stake_window_end, latest_bonus_unlock_time);
+    assert_eq!(final_unlock_time, pool.final_unlock_time());
+    let deadline = pool.bonus_enrollment_deadline;
+    assert_eq!(math::bonus_withdrawal_time(latest_bonus_unlock_time, deadline), pool.bonus_withdrawal_time());
+    assert_eq!(
+        math::bonus_claim_deadline(latest_bonus_unlock_time, deadline, 90 * DAY),
+        pool.bonus_claim_deadline()
+    );
+
+    // An extended window can push the last principal unlock past every bonus unlock; only
+    // settlement waits for it, the bonus claim date stays put
+    let extended = StakingPool { stake_window_end: stake_time + 21 * DAY, ..pool };
+    let final_unlock_time = math::final_unlock_time(extended.stake_window_end, latest_bonus_unlock_time);
+    assert_eq!(final_unlock_time, stake_time + 46 * DAY);
+    assert_eq!(final_unlock_time, extended.final_unlock_time());
+    assert_eq!(extended.bonus_withdrawal_time(), pool.bonus_withdrawal_time());
+}
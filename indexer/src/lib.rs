@@ -0,0 +1,9 @@
+//! Materialises QST staking events into SQLite.
+//!
+//! - [`source`]: transactions from an RPC endpoint or a JSON fixture
+//! - [`store`]: event log, per-user ledgers, positions and pool aggregates
+//! - [`verify`]: replayed aggregates against the on-chain `StakingPool`
+
+pub mod source;
+pub mod store;
+pub mod verify;
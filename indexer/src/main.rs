@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+use qst_indexer::source::{load_fixture, RpcSource};
+use qst_indexer::store::Indexer;
+use qst_indexer::verify::verify;
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Parser)]
+#[command(name = "qst-indexer", about = "Index QST staking events into SQLite")]
+struct Cli {
+    /// SQLite database (created if missing)
+    #[arg(long, default_value = "qst-staking.sqlite")]
+    db: PathBuf,
+
+    /// Staking program id
+    #[arg(long, default_value_t = qst_staking_client::PROGRAM_ID)]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Ingest new program transactions from an RPC endpoint
+    Rpc {
+        #[arg(long, short = 'u', env = "QST_RPC_URL", default_value = "http://localhost:8899")]
+        url: String,
+    },
+    /// Ingest a JSON fixture (array of transactions, oldest first)
+    Fixture { path: PathBuf },
+    /// Compare replayed totals with the on-chain StakingPool
+    Verify {
+        #[arg(long, short = 'u', env = "QST_RPC_URL", default_value = "http://localhost:8899")]
+        url: String,
+    },
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let mut indexer = Indexer::open(&cli.db, cli.program_id)?;
+
+    match cli.command {
+        Command::Rpc { url } => {
+            let source = RpcSource::new(&url, cli.program_id);
+            let transactions = source.fetch_since(indexer.last_signature()?.as_deref())?;
+            let mut events = 0;
+            for tx in &transactions {
+                events += indexer.ingest(tx)?;
+            }
+            println!("Indexed {} events from {} transactions", events, transactions.len());
+        }
+        Command::Fixture { path } => {
+            let transactions = load_fixture(&path)?;
+            let mut events = 0;
+            for tx in &transactions {
+                events += indexer.ingest(tx)?;
+            }
+            println!("Indexed {} events from {} transactions", events, transactions.len());
+        }
+        Command::Verify { url } => {
+            let pool = RpcSource::new(&url, cli.program_id).staking_pool()?;
+            let totals = indexer.pool_totals()?;
+            for (expected, found) in indexer.gaps()? {
+                println!("gap: expected seq {}, found {}", expected, found);
+            }
+            let mismatches = verify(&totals, &pool);
+            for mismatch in &mismatches {
+                println!(
+                    "{}: indexed {}, on-chain {}",
+                    mismatch.field, mismatch.indexed, mismatch.on_chain
+                );
+            }
+            if !mismatches.is_empty() {
+                bail!("indexed state does not match the on-chain pool");
+            }
+            println!("Indexed state matches the on-chain pool at seq {}", totals.last_seq);
+        }
+    }
+    Ok(())
+}
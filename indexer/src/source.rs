@@ -0,0 +1,173 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use qst_staking_client::events::{self, QstEvent};
+use qst_staking_client::pda::find_staking_pool_address;
+use qst_staking_client::state::{decode_staking_pool, StakingPool};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction, UiLoadedAddresses,
+    UiMessage, UiTransactionEncoding,
+};
+
+// One confirmed transaction touching the staking program. This is also the JSON
+// fixture format: a file holds an array of these, oldest first.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IndexedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    #[serde(default)]
+    pub block_time: Option<i64>,
+    #[serde(default)]
+    pub logs: Vec<String>,
+    // base64 data of inner instructions the staking program invoked on itself (emit_cpi!)
+    #[serde(default)]
+    pub inner_instructions: Vec<String>,
+}
+
+impl IndexedTransaction {
+    // emit_cpi! events plus any `Program data:` events, in sequence order
+    pub fn events(&self, program_id: &Pubkey) -> Result<Vec<QstEvent>> {
+        let mut decoded = Vec::new();
+        for data in &self.inner_instructions {
+            let data = STANDARD
+                .decode(data)
+                .with_context(|| format!("bad inner instruction data in {}", self.signature))?;
+            if let Some(event) = events::decode_cpi_event(&data) {
+                decoded.push(event);
+            }
+        }
+        decoded.extend(events::parse_logs(program_id, &self.logs));
+        decoded.sort_by_key(QstEvent::seq);
+        decoded.dedup_by_key(|event| event.seq());
+        Ok(decoded)
+    }
+}
+
+pub fn load_fixture(path: &Path) -> Result<Vec<IndexedTransaction>> {
+    let file = std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    serde_json::from_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("failed to parse fixture {}", path.display()))
+}
+
+pub struct RpcSource {
+    client: RpcClient,
+    program_id: Pubkey,
+}
+
+impl RpcSource {
+    pub fn new(url: &str, program_id: Pubkey) -> Self {
+        Self {
+            client: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            program_id,
+        }
+    }
+
+    pub fn staking_pool(&self) -> Result<StakingPool> {
+        let (address, _) = find_staking_pool_address(&self.program_id);
+        let data = self
+            .client
+            .get_account_data(&address)
+            .with_context(|| format!("failed to fetch staking pool {}", address))?;
+        Ok(decode_staking_pool(&data)?)
+    }
+
+    // Successful program transactions after `after` (exclusive), oldest first
+    pub fn fetch_since(&self, after: Option<&str>) -> Result<Vec<IndexedTransaction>> {
+        let until = after.map(str::parse::<Signature>).transpose()?;
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.client.get_signatures_for_address_with_config(
+                &self.program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: None,
+                    commitment: Some(self.client.commitment()),
+                },
+            )?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(last.signature.parse()?);
+            signatures.extend(
+                page.into_iter()
+                    .filter(|status| status.err.is_none())
+                    .map(|status| status.signature),
+            );
+        }
+
+        // RPC returns newest first
+        signatures
+            .iter()
+            .rev()
+            .map(|signature| self.fetch_transaction(signature))
+            .collect()
+    }
+
+    fn fetch_transaction(&self, signature: &str) -> Result<IndexedTransaction> {
+        let tx = self.client.get_transaction_with_config(
+            &signature.parse()?,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(self.client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        to_indexed(signature, tx, &self.program_id)
+    }
+}
+
+fn to_indexed(
+    signature: &str,
+    tx: EncodedConfirmedTransactionWithStatusMeta,
+    program_id: &Pubkey,
+) -> Result<IndexedTransaction> {
+    let meta = tx
+        .transaction
+        .meta
+        .ok_or_else(|| anyhow!("transaction {} has no status meta", signature))?;
+
+    // Static keys followed by lookup-table keys, as indexed by program_id_index
+    let mut account_keys = match tx.transaction.transaction {
+        EncodedTransaction::Json(ui_tx) => match ui_tx.message {
+            UiMessage::Raw(message) => message.account_keys,
+            UiMessage::Parsed(_) => return Err(anyhow!("unexpected parsed message for {}", signature)),
+        },
+        _ => return Err(anyhow!("unexpected transaction encoding for {}", signature)),
+    };
+    if let Some(loaded) = Option::<UiLoadedAddresses>::from(meta.loaded_addresses) {
+        account_keys.extend(loaded.writable);
+        account_keys.extend(loaded.readonly);
+    }
+
+    let program_id = program_id.to_string();
+    let mut inner_instructions = Vec::new();
+    for inner in Option::<Vec<_>>::from(meta.inner_instructions).unwrap_or_default() {
+        for ix in inner.instructions {
+            let UiInstruction::Compiled(ix) = ix else {
+                continue;
+            };
+            if account_keys.get(ix.program_id_index as usize) == Some(&program_id) {
+                let data = bs58::decode(&ix.data).into_vec()?;
+                inner_instructions.push(STANDARD.encode(data));
+            }
+        }
+    }
+
+    Ok(IndexedTransaction {
+        signature: signature.to_string(),
+        slot: tx.slot,
+        block_time: tx.block_time,
+        logs: Option::from(meta.log_messages).unwrap_or_default(),
+        inner_instructions,
+    })
+}
@@ -0,0 +1,495 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use qst_staking_client::events::QstEvent;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::source::IndexedTransaction;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    seq INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    user TEXT,
+    timestamp INTEGER NOT NULL
+);
+-- One row per event that moved a user's funds or enrollment
+CREATE TABLE IF NOT EXISTS ledger (
+    seq INTEGER PRIMARY KEY REFERENCES events(seq),
+    user TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    amount INTEGER NOT NULL,  -- principal staked, unstaked, withdrawn or (un)enrolled
+    penalty INTEGER NOT NULL, -- paid into the penalty vault
+    reward INTEGER NOT NULL,  -- bonus, settlement share or emissions received
+    timestamp INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS ledger_user ON ledger(user, seq);
+CREATE TABLE IF NOT EXISTS positions (
+    user TEXT PRIMARY KEY,
+    staked INTEGER NOT NULL,
+    node_keys INTEGER NOT NULL,
+    enrolled INTEGER NOT NULL,
+    total_penalties INTEGER NOT NULL,
+    total_rewards INTEGER NOT NULL,
+    enrolled_stake INTEGER NOT NULL, -- bonus weight, frozen at the enrollment deadline
+    bonus_claimed INTEGER NOT NULL   -- bonus paid; enrolled keeps the on-chain flag
+);
+CREATE TABLE IF NOT EXISTS pool (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    total_staked INTEGER NOT NULL,
+    total_enrolled_stake INTEGER NOT NULL,
+    penalty_vault_amount INTEGER NOT NULL,
+    total_penalties INTEGER NOT NULL,
+    total_rewards_paid INTEGER NOT NULL,
+    total_dust_collected INTEGER NOT NULL,
+    stake_window_start INTEGER NOT NULL,
+    stake_window_end INTEGER NOT NULL,
+    bonus_enrollment_deadline INTEGER NOT NULL,
+    last_seq INTEGER NOT NULL,
+    last_signature TEXT
+);
+INSERT OR IGNORE INTO pool VALUES (1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, NULL);
+-- Sequence numbers missing from the ingested history
+CREATE TABLE IF NOT EXISTS gaps (
+    expected_seq INTEGER NOT NULL,
+    found_seq INTEGER NOT NULL,
+    signature TEXT NOT NULL
+);
+";
+
+// Pool aggregates rebuilt from events; compared against StakingPool by verify
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolTotals {
+    pub total_staked: u64,
+    pub total_enrolled_stake: u64,
+    pub penalty_vault_amount: u64,
+    pub total_penalties: u64,
+    pub total_rewards_paid: u64,
+    pub total_dust_collected: u64,
+    pub stake_window_start: i64,
+    pub stake_window_end: i64,
+    pub bonus_enrollment_deadline: i64,
+    pub last_seq: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    pub staked: u64,
+    pub node_keys: u64,
+    pub enrolled: bool,
+    pub total_penalties: u64,
+    pub total_rewards: u64,
+    pub enrolled_stake: u64,
+    pub bonus_claimed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerEntry {
+    pub seq: u64,
+    pub kind: String,
+    pub amount: u64,
+    pub penalty: u64,
+    pub reward: u64,
+    pub timestamp: i64,
+}
+
+pub struct Indexer {
+    conn: Connection,
+    program_id: Pubkey,
+}
+
+impl Indexer {
+    pub fn open(path: &Path, program_id: Pubkey) -> Result<Self> {
+        let conn = Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        Self::with_connection(conn, program_id)
+    }
+
+    pub fn open_in_memory(program_id: Pubkey) -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?, program_id)
+    }
+
+    fn with_connection(conn: Connection, program_id: Pubkey) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn, program_id })
+    }
+
+    // Applies the transaction's events; already-indexed sequence numbers are skipped,
+    // so re-ingesting overlapping history is harmless. Returns the number of new events.
+    pub fn ingest(&mut self, tx: &IndexedTransaction) -> Result<usize> {
+        let events = tx.events(&self.program_id)?;
+        let db = self.conn.transaction()?;
+        let mut totals = load_totals(&db)?;
+        let mut applied = 0;
+
+        for event in &events {
+            let inserted = db.execute(
+                "INSERT OR IGNORE INTO events (seq, name, signature, slot, user, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    int(event.seq())?,
+                    event.name(),
+                    tx.signature,
+                    int(tx.slot)?,
+                    event_user(event).map(|user| user.to_string()),
+                    event_timestamp(event),
+                ],
+            )?;
+            if inserted == 0 {
+                continue;
+            }
+            if event.seq() != totals.last_seq + 1 {
+                db.execute(
+                    "INSERT INTO gaps (expected_seq, found_seq, signature) VALUES (?1, ?2, ?3)",
+                    params![int(totals.last_seq + 1)?, int(event.seq())?, tx.signature],
+                )?;
+            }
+            apply(&db, &mut totals, event)?;
+            totals.last_seq = totals.last_seq.max(event.seq());
+            applied += 1;
+        }
+
+        store_totals(&db, &totals)?;
+        db.execute("UPDATE pool SET last_signature = ?1 WHERE id = 1", params![tx.signature])?;
+        db.commit()?;
+        Ok(applied)
+    }
+
+    pub fn last_signature(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row("SELECT last_signature FROM pool WHERE id = 1", [], |row| row.get(0))?)
+    }
+
+    pub fn pool_totals(&self) -> Result<PoolTotals> {
+        load_totals(&self.conn)
+    }
+
+    pub fn position(&self, user: &Pubkey) -> Result<Option<Position>> {
+        load_position(&self.conn, user)
+    }
+
+    pub fn ledger(&self, user: &Pubkey) -> Result<Vec<LedgerEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT seq, kind, amount, penalty, reward, timestamp FROM ledger WHERE user = ?1 ORDER BY seq",
+        )?;
+        let rows = stmt.query_map(params![user.to_string()], |row| {
+            Ok(LedgerEntry {
+                seq: row.get::<_, i64>(0)? as u64,
+                kind: row.get(1)?,
+                amount: row.get::<_, i64>(2)? as u64,
+                penalty: row.get::<_, i64>(3)? as u64,
+                reward: row.get::<_, i64>(4)? as u64,
+                timestamp: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    // (expected, found) pairs where the history skipped sequence numbers
+    pub fn gaps(&self) -> Result<Vec<(u64, u64)>> {
+        let mut stmt = self.conn.prepare("SELECT expected_seq, found_seq FROM gaps ORDER BY rowid")?;
+        let rows = stmt.query_map([], |row| Ok((row.get::<_, i64>(0)? as u64, row.get::<_, i64>(1)? as u64)))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+// Mirrors the program's bookkeeping for each event
+fn apply(db: &Transaction, totals: &mut PoolTotals, event: &QstEvent) -> Result<()> {
+    match event {
+        QstEvent::StakeWindowStarted(e) => {
+            totals.stake_window_start = e.start_time;
+            totals.stake_window_end = e.stake_window_end;
+            totals.bonus_enrollment_deadline = e.bonus_enrollment_deadline;
+        }
+        QstEvent::StakeWindowScheduled(e) => {
+            totals.stake_window_start = e.start_time;
+            totals.stake_window_end = e.stake_window_end;
+            totals.bonus_enrollment_deadline = e.bonus_enrollment_deadline;
+        }
+        QstEvent::StakeWindowScheduleCancelled(_) => {
+            totals.stake_window_start = 0;
+            totals.stake_window_end = 0;
+            totals.bonus_enrollment_deadline = 0;
+        }
+        QstEvent::StakeWindowExtended(e) => {
+            totals.stake_window_end = e.stake_window_end;
+        }
+        QstEvent::StakeWindowClosedEarly(e) => {
+            totals.stake_window_end = e.stake_window_end;
+            totals.bonus_enrollment_deadline = e.bonus_enrollment_deadline;
+        }
+        QstEvent::NextRoundStarted(_) => {
+            // Nothing is staked, and every position's enrollment belonged to the last round
+            totals.stake_window_start = 0;
+            totals.stake_window_end = 0;
+            totals.bonus_enrollment_deadline = 0;
+            totals.total_enrolled_stake = 0;
+            db.execute("UPDATE positions SET enrolled = 0, enrolled_stake = 0, bonus_claimed = 0", [])?;
+        }
+        QstEvent::StakeEvent(e) => {
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            position.staked += e.amount;
+            position.node_keys += e.node_keys_earned as u64;
+            totals.total_staked += e.amount;
+            // Enrolled weight is frozen once enrollment closes
+            if position.enrolled && e.timestamp <= totals.bonus_enrollment_deadline {
+                position.enrolled_stake += e.amount;
+                totals.total_enrolled_stake += e.amount;
+            }
+            record(db, &e.user, &position, e.seq, "stake", e.amount, 0, 0, e.timestamp)?;
+        }
+        QstEvent::BonusEnrollment(e) => {
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            position.enrolled = true;
+            position.enrolled_stake = e.enrolled_stake;
+            totals.total_enrolled_stake += e.enrolled_stake;
+            record(db, &e.user, &position, e.seq, "enroll", e.enrolled_stake, 0, 0, e.timestamp)?;
+        }
+        QstEvent::BonusUnenrollment(e) => {
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            position.enrolled = false;
+            position.enrolled_stake = 0;
+            totals.total_enrolled_stake = totals.total_enrolled_stake.saturating_sub(e.unenrolled_stake);
+            record(db, &e.user, &position, e.seq, "unenroll", e.unenrolled_stake, 0, 0, e.timestamp)?;
+        }
+        QstEvent::UnstakeEvent(e) => {
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            position.staked = position.staked.saturating_sub(e.amount);
+            position.total_penalties += e.penalty_applied;
+            // Enrolled positions can only unstake late stake, which was never enrolled weight
+            totals.total_staked = totals.total_staked.saturating_sub(e.amount);
+            totals.penalty_vault_amount += e.penalty_applied;
+            totals.total_penalties += e.penalty_applied;
+            record(db, &e.user, &position, e.seq, "unstake", e.amount, e.penalty_applied, 0, e.timestamp)?;
+        }
+        QstEvent::EnrolledExitEvent(e) => {
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            totals.total_enrolled_stake = totals.total_enrolled_stake.saturating_sub(position.enrolled_stake);
+            position.staked = position.staked.saturating_sub(e.amount);
+            position.enrolled = false;
+            position.enrolled_stake = 0;
+            position.total_penalties += e.penalty_applied;
+            totals.total_staked = totals.total_staked.saturating_sub(e.amount);
+            totals.penalty_vault_amount += e.penalty_applied;
+            totals.total_penalties += e.penalty_applied;
+            record(db, &e.user, &position, e.seq, "exit_enrolled", e.amount, e.penalty_applied, 0, e.timestamp)?;
+        }
+        QstEvent::WithdrawAllEvent(e) => {
+            // Enrollment is kept, as on chain, so the bonus can still be claimed
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            position.staked = position.staked.saturating_sub(e.principal_amount);
+            totals.total_staked = totals.total_staked.saturating_sub(e.principal_amount);
+            record(db, &e.user, &position, e.seq, "withdraw", e.principal_amount, 0, 0, e.timestamp)?;
+        }
+        QstEvent::BonusWithdrawEvent(e) => {
+            // withdraw_bonus releases the position's enrolled weight; like the stake account,
+            // the position stays enrolled and only the claim is recorded
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            totals.total_enrolled_stake = totals.total_enrolled_stake.saturating_sub(position.enrolled_stake);
+            position.bonus_claimed = true;
+            position.total_rewards += e.bonus_amount;
+            totals.penalty_vault_amount = totals.penalty_vault_amount.saturating_sub(e.bonus_amount);
+            totals.total_rewards_paid += e.bonus_amount;
+            record(db, &e.user, &position, e.seq, "bonus", 0, 0, e.bonus_amount, e.timestamp)?;
+        }
+        QstEvent::BonusRedistributed(e) => {
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            position.total_rewards += e.amount;
+            totals.penalty_vault_amount = totals.penalty_vault_amount.saturating_sub(e.amount);
+            totals.total_rewards_paid += e.amount;
+            record(db, &e.user, &position, e.seq, "redistribution", 0, 0, e.amount, e.timestamp)?;
+        }
+        QstEvent::RewardsClaimed(e) => {
+            // Emissions come from the reward vault, not the penalty vault
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            position.total_rewards += e.amount;
+            totals.total_rewards_paid += e.amount;
+            record(db, &e.user, &position, e.seq, "rewards", 0, 0, e.amount, e.timestamp)?;
+        }
+        QstEvent::PositionSettled(e) => {
+            let mut position = load_position(db, &e.user)?.unwrap_or_default();
+            if position.enrolled && !position.bonus_claimed {
+                totals.total_enrolled_stake = totals.total_enrolled_stake.saturating_sub(position.enrolled_stake);
+            }
+            position.staked = 0;
+            position.enrolled = false;
+            position.enrolled_stake = 0;
+            position.bonus_claimed |= e.bonus_amount > 0;
+            let paid = e.penalty_share + e.bonus_amount;
+            position.total_rewards += paid;
+            totals.total_staked = totals.total_staked.saturating_sub(e.principal_amount);
+            totals.penalty_vault_amount = totals.penalty_vault_amount.saturating_sub(paid);
+            totals.total_rewards_paid += paid;
+            record(db, &e.user, &position, e.seq, "settle", e.principal_amount, 0, paid, e.timestamp)?;
+        }
+        QstEvent::DustCollected(e) => {
+            totals.penalty_vault_amount = totals.penalty_vault_amount.saturating_sub(e.amount);
+            totals.total_dust_collected += e.amount;
+        }
+        // Configuration, admin and audit events only go to the events table
+        _ => {}
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record(
+    db: &Transaction,
+    user: &Pubkey,
+    position: &Position,
+    seq: u64,
+    kind: &str,
+    amount: u64,
+    penalty: u64,
+    reward: u64,
+    timestamp: i64,
+) -> Result<()> {
+    db.execute(
+        "INSERT INTO ledger (seq, user, kind, amount, penalty, reward, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![int(seq)?, user.to_string(), kind, int(amount)?, int(penalty)?, int(reward)?, timestamp],
+    )?;
+    db.execute(
+        "INSERT OR REPLACE INTO positions
+             (user, staked, node_keys, enrolled, total_penalties, total_rewards, enrolled_stake, bonus_claimed)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            user.to_string(),
+            int(position.staked)?,
+            int(position.node_keys)?,
+            position.enrolled,
+            int(position.total_penalties)?,
+            int(position.total_rewards)?,
+            int(position.enrolled_stake)?,
+            position.bonus_claimed,
+        ],
+    )?;
+    Ok(())
+}
+
+fn load_position(conn: &Connection, user: &Pubkey) -> Result<Option<Position>> {
+    Ok(conn
+        .query_row(
+            "SELECT staked, node_keys, enrolled, total_penalties, total_rewards, enrolled_stake, bonus_claimed
+             FROM positions WHERE user = ?1",
+            params![user.to_string()],
+            |row| {
+                Ok(Position {
+                    staked: row.get::<_, i64>(0)? as u64,
+                    node_keys: row.get::<_, i64>(1)? as u64,
+                    enrolled: row.get(2)?,
+                    total_penalties: row.get::<_, i64>(3)? as u64,
+                    total_rewards: row.get::<_, i64>(4)? as u64,
+                    enrolled_stake: row.get::<_, i64>(5)? as u64,
+                    bonus_claimed: row.get(6)?,
+                })
+            },
+        )
+        .optional()?)
+}
+
+fn load_totals(conn: &Connection) -> Result<PoolTotals> {
+    Ok(conn.query_row(
+        "SELECT total_staked, total_enrolled_stake, penalty_vault_amount, total_penalties,
+                total_rewards_paid, total_dust_collected, stake_window_start, stake_window_end,
+                bonus_enrollment_deadline, last_seq
+         FROM pool WHERE id = 1",
+        [],
+        |row| {
+            Ok(PoolTotals {
+                total_staked: row.get::<_, i64>(0)? as u64,
+                total_enrolled_stake: row.get::<_, i64>(1)? as u64,
+                penalty_vault_amount: row.get::<_, i64>(2)? as u64,
+                total_penalties: row.get::<_, i64>(3)? as u64,
+                total_rewards_paid: row.get::<_, i64>(4)? as u64,
+                total_dust_collected: row.get::<_, i64>(5)? as u64,
+                stake_window_start: row.get(6)?,
+                stake_window_end: row.get(7)?,
+                bonus_enrollment_deadline: row.get(8)?,
+                last_seq: row.get::<_, i64>(9)? as u64,
+            })
+        },
+    )?)
+}
+
+fn store_totals(db: &Transaction, totals: &PoolTotals) -> Result<()> {
+    db.execute(
+        "UPDATE pool SET total_staked = ?1, total_enrolled_stake = ?2, penalty_vault_amount = ?3,
+                total_penalties = ?4, total_rewards_paid = ?5, total_dust_collected = ?6,
+                stake_window_start = ?7, stake_window_end = ?8, bonus_enrollment_deadline = ?9,
+                last_seq = ?10
+         WHERE id = 1",
+        params![
+            int(totals.total_staked)?,
+            int(totals.total_enrolled_stake)?,
+            int(totals.penalty_vault_amount)?,
+            int(totals.total_penalties)?,
+            int(totals.total_rewards_paid)?,
+            int(totals.total_dust_collected)?,
+            totals.stake_window_start,
+            totals.stake_window_end,
+            totals.bonus_enrollment_deadline,
+            int(totals.last_seq)?,
+        ],
+    )?;
+    Ok(())
+}
+
+// SQLite integers are signed; QST amounts stay far below i64::MAX
+fn int(value: u64) -> Result<i64> {
+    i64::try_from(value).with_context(|| format!("{} does not fit in an SQLite integer", value))
+}
+
+fn event_user(event: &QstEvent) -> Option<Pubkey> {
+    match event {
+        QstEvent::StakeEvent(e) => Some(e.user),
+        QstEvent::BonusEnrollment(e) => Some(e.user),
+        QstEvent::BonusUnenrollment(e) => Some(e.user),
+        QstEvent::UnstakeEvent(e) => Some(e.user),
+        QstEvent::EnrolledExitEvent(e) => Some(e.user),
+        QstEvent::WithdrawAllEvent(e) => Some(e.user),
+        QstEvent::BonusWithdrawEvent(e) => Some(e.user),
+        QstEvent::BonusRedistributed(e) => Some(e.user),
+        QstEvent::RewardsClaimed(e) => Some(e.user),
+        QstEvent::PositionSettled(e) => Some(e.user),
+        _ => None,
+    }
+}
+
+fn event_timestamp(event: &QstEvent) -> i64 {
+    match event {
+        QstEvent::PoolInitialized(e) => e.timestamp,
+        QstEvent::AuthorityChanged(e) => e.timestamp,
+        QstEvent::FeeRecipientChanged(e) => e.timestamp,
+        QstEvent::AdminHeartbeatRecorded(e) => e.timestamp,
+        QstEvent::EnrolledExitPenaltyUpdated(e) => e.timestamp,
+        QstEvent::AdminInactivityPeriodUpdated(e) => e.timestamp,
+        QstEvent::UnclaimedBonusPolicyUpdated(e) => e.timestamp,
+        QstEvent::RewardsFunded(e) => e.timestamp,
+        QstEvent::UnallocatedRewardsReclaimed(e) => e.timestamp,
+        QstEvent::StakeWindowStarted(e) => e.start_time,
+        QstEvent::StakeWindowScheduled(e) => e.timestamp,
+        QstEvent::StakeWindowScheduleCancelled(e) => e.timestamp,
+        QstEvent::StakeWindowExtended(e) => e.timestamp,
+        QstEvent::StakeWindowClosedEarly(e) => e.timestamp,
+        QstEvent::NextRoundStarted(e) => e.timestamp,
+        QstEvent::StakeCapsUpdated(e) => e.timestamp,
+        QstEvent::AllowlistConfigured(e) => e.timestamp,
+        QstEvent::BonusEnrollment(e) => e.timestamp,
+        QstEvent::BonusUnenrollment(e) => e.timestamp,
+        QstEvent::StakeEvent(e) => e.timestamp,
+        QstEvent::UnstakeEvent(e) => e.timestamp,
+        QstEvent::EnrolledExitEvent(e) => e.timestamp,
+        QstEvent::WithdrawAllEvent(e) => e.timestamp,
+        QstEvent::SettlementTriggered(e) => e.timestamp,
+        QstEvent::PositionSettled(e) => e.timestamp,
+        QstEvent::BonusWithdrawEvent(e) => e.timestamp,
+        QstEvent::BonusRedistributed(e) => e.timestamp,
+        QstEvent::RewardsClaimed(e) => e.timestamp,
+        QstEvent::DustCollected(e) => e.timestamp,
+        QstEvent::AuditEvent(e) => e.timestamp,
+    }
+}
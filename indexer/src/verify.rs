@@ -0,0 +1,24 @@
+use qst_staking_client::state::StakingPool;
+
+use crate::store::PoolTotals;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub field: &'static str,
+    pub indexed: u64,
+    pub on_chain: u64,
+}
+
+// Replayed totals against the live StakingPool; empty when the history is complete
+pub fn verify(totals: &PoolTotals, pool: &StakingPool) -> Vec<Mismatch> {
+    [
+        ("total_staked", totals.total_staked, pool.total_staked),
+        ("total_enrolled_stake", totals.total_enrolled_stake, pool.total_enrolled_stake),
+        ("penalty_vault_amount", totals.penalty_vault_amount, pool.penalty_vault_amount),
+        ("event_seq", totals.last_seq, pool.event_seq),
+    ]
+    .into_iter()
+    .filter(|(_, indexed, on_chain)| indexed != on_chain)
+    .map(|(field, indexed, on_chain)| Mismatch { field, indexed, on_chain })
+    .collect()
+}
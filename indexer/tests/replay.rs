@@ -0,0 +1,257 @@
+// Replays tests/fixtures/pool_history.json: alice stakes 400k and enrolls, bob stakes 200k,
+// carol stakes 200k and unstakes in the late tier (40k penalty), alice withdraws her
+// principal and the whole 40k bonus. Bob is still staked at the end.
+
+use std::path::Path;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::Event;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use qst_indexer::source::{load_fixture, IndexedTransaction};
+use qst_indexer::store::{Indexer, PoolTotals, Position};
+use qst_indexer::verify::verify;
+use qst_staking_client::events::{BonusEnrollment, StakeEvent, StakeWindowStarted, UnstakeEvent};
+use qst_staking_client::state::{StakingPool, UnclaimedBonusPolicy};
+use qst_staking_client::PROGRAM_ID;
+
+const QST: u64 = 1_000_000;
+
+fn alice() -> Pubkey {
+    Pubkey::new_from_array([1; 32])
+}
+
+fn bob() -> Pubkey {
+    Pubkey::new_from_array([2; 32])
+}
+
+fn carol() -> Pubkey {
+    Pubkey::new_from_array([3; 32])
+}
+
+fn indexed_fixture() -> Indexer {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pool_history.json");
+    let mut indexer = Indexer::open_in_memory(PROGRAM_ID).unwrap();
+    for tx in load_fixture(&path).unwrap() {
+        indexer.ingest(&tx).unwrap();
+    }
+    indexer
+}
+
+// A transaction carrying one event the way emit_cpi! does, as a self-invoked inner instruction
+fn event_tx(seq: u64, event: &impl Event) -> IndexedTransaction {
+    IndexedTransaction {
+        signature: format!("event-sig-{seq}"),
+        slot: 100 + seq,
+        block_time: None,
+        logs: Vec::new(),
+        inner_instructions: vec![STANDARD.encode([&EVENT_IX_TAG_LE[..], &event.data()].concat())],
+    }
+}
+
+fn on_chain_pool(totals: &PoolTotals) -> StakingPool {
+    StakingPool {
+        authority: Pubkey::new_from_array([9; 32]),
+        total_staked: totals.total_staked,
+        total_enrolled_stake: totals.total_enrolled_stake,
+        penalty_vault_amount: totals.penalty_vault_amount,
+        first_stake_timestamp: totals.stake_window_start,
+        bonus_enrollment_deadline: totals.bonus_enrollment_deadline,
+        stake_window_end: totals.stake_window_end,
+        latest_bonus_unlock_time: 0,
+        qst_mint: Pubkey::new_from_array([8; 32]),
+        bump: 255,
+        max_total_staked: u64::MAX,
+        max_user_stake: u64::MAX,
+        allowlist_root: [0u8; 32],
+        allowlist_public_time: 0,
+        enrolled_exit_penalty_rate: 40,
+        last_admin_action: 0,
+        admin_inactivity_period: 0,
+        settlement_active: false,
+        settlement_penalty_vault: 0,
+        settlement_total_stake: 0,
+        event_seq: totals.last_seq,
+        fee_recipient: Pubkey::new_from_array([7; 32]),
+        bonus_claim_period: 0,
+        unclaimed_bonus_policy: UnclaimedBonusPolicy::Sweep,
+        claimed_enrolled_stake: 0,
+        bonus_snapshot_taken: false,
+        bonus_snapshot_vault: 0,
+        bonus_snapshot_weight: 0,
+        redistribution_snapshot_vault: 0,
+        redistribution_snapshot_weight: 0,
+        rounding_dust: 0,
+        reward_vault_amount: 0,
+        reward_stream_amount: 0,
+        reward_start: 0,
+        reward_end: 0,
+        reward_released: 0,
+        reward_per_weight: 0,
+        reward_unallocated: 0,
+        unclaimed_bonus_swept: false,
+        post_snapshot_penalties: 0,
+        total_reward_weight: 0,
+        round: 0,
+    }
+}
+
+#[test]
+fn replays_pool_aggregates() {
+    let indexer = indexed_fixture();
+    let totals = indexer.pool_totals().unwrap();
+
+    assert_eq!(totals.total_staked, 200_000 * QST);
+    assert_eq!(totals.total_enrolled_stake, 0);
+    assert_eq!(totals.penalty_vault_amount, 0);
+    assert_eq!(totals.total_penalties, 40_000 * QST);
+    assert_eq!(totals.total_rewards_paid, 40_000 * QST);
+    assert_eq!(totals.stake_window_start, 1_700_000_000);
+    assert_eq!(totals.last_seq, 9);
+    assert!(indexer.gaps().unwrap().is_empty());
+    assert_eq!(indexer.last_signature().unwrap().as_deref(), Some("fixture-sig-8"));
+}
+
+#[test]
+fn builds_per_user_ledgers() {
+    let indexer = indexed_fixture();
+
+    let kinds: Vec<String> = indexer.ledger(&alice()).unwrap().into_iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, ["stake", "enroll", "withdraw", "bonus"]);
+
+    assert_eq!(
+        indexer.position(&alice()).unwrap().unwrap(),
+        Position {
+            staked: 0,
+            node_keys: 4,
+            enrolled: true,
+            total_penalties: 0,
+            total_rewards: 40_000 * QST,
+            enrolled_stake: 400_000 * QST,
+            bonus_claimed: true,
+        }
+    );
+    assert_eq!(indexer.position(&bob()).unwrap().unwrap().staked, 200_000 * QST);
+
+    let carol_ledger = indexer.ledger(&carol()).unwrap();
+    assert_eq!(carol_ledger.len(), 2);
+    assert_eq!(carol_ledger[1].kind, "unstake");
+    assert_eq!(carol_ledger[1].penalty, 40_000 * QST);
+}
+
+#[test]
+fn reingesting_history_is_idempotent() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pool_history.json");
+    let mut indexer = indexed_fixture();
+    let before = indexer.pool_totals().unwrap();
+
+    for tx in load_fixture(&path).unwrap() {
+        assert_eq!(indexer.ingest(&tx).unwrap(), 0);
+    }
+    assert_eq!(indexer.pool_totals().unwrap(), before);
+}
+
+#[test]
+fn records_gaps_and_flags_mismatches() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pool_history.json");
+    let mut indexer = Indexer::open_in_memory(PROGRAM_ID).unwrap();
+    // Drop carol's unstake: seq 7 goes missing and the penalty never reaches the vault
+    for tx in load_fixture(&path).unwrap().iter().filter(|tx| tx.signature != "fixture-sig-6") {
+        indexer.ingest(tx).unwrap();
+    }
+    assert_eq!(indexer.gaps().unwrap(), vec![(7, 8)]);
+
+    let complete = indexed_fixture().pool_totals().unwrap();
+    let pool = on_chain_pool(&complete);
+    assert!(verify(&complete, &pool).is_empty());
+
+    let fields: Vec<&str> = verify(&indexer.pool_totals().unwrap(), &pool)
+        .into_iter()
+        .map(|mismatch| mismatch.field)
+        .collect();
+    assert_eq!(fields, ["total_staked"]);
+}
+
+#[test]
+fn enrolled_late_stake_unstake_keeps_enrolled_weight() {
+    let start = 1_700_000_000;
+    let day = 24 * 60 * 60;
+    let history = [
+        event_tx(
+            1,
+            &StakeWindowStarted {
+                start_time: start,
+                stake_window_end: start + 9 * day,
+                bonus_enrollment_deadline: start + 2 * day,
+                max_total_staked: u64::MAX,
+                max_user_stake: u64::MAX,
+                seq: 1,
+            },
+        ),
+        event_tx(
+            2,
+            &StakeEvent {
+                user: alice(),
+                amount: 400_000 * QST,
+                total_staked: 400_000 * QST,
+                node_keys_earned: 4,
+                principal_unlock_time: start + 25 * day,
+                bonus_unlock_time: 0,
+                enrolled_in_bonus: false,
+                timestamp: start,
+                seq: 2,
+            },
+        ),
+        event_tx(
+            3,
+            &BonusEnrollment {
+                user: alice(),
+                enrolled_stake: 400_000 * QST,
+                timestamp: start,
+                seq: 3,
+            },
+        ),
+        // Staked after enrollment closed, so it is late stake outside the enrolled weight
+        event_tx(
+            4,
+            &StakeEvent {
+                user: alice(),
+                amount: 200_000 * QST,
+                total_staked: 600_000 * QST,
+                node_keys_earned: 2,
+                principal_unlock_time: start + 35 * day,
+                bonus_unlock_time: start + 35 * day,
+                enrolled_in_bonus: true,
+                timestamp: start + 3 * day,
+                seq: 4,
+            },
+        ),
+        event_tx(
+            5,
+            &UnstakeEvent {
+                user: alice(),
+                amount: 200_000 * QST,
+                remaining_staked: 400_000 * QST,
+                penalty_applied: 40_000 * QST,
+                net_to_user: 160_000 * QST,
+                penalty_vault_total: 40_000 * QST,
+                timestamp: start + 21 * day,
+                seq: 5,
+            },
+        ),
+    ];
+    let mut indexer = Indexer::open_in_memory(PROGRAM_ID).unwrap();
+    for tx in &history {
+        indexer.ingest(tx).unwrap();
+    }
+
+    let totals = indexer.pool_totals().unwrap();
+    assert_eq!(totals.total_staked, 400_000 * QST);
+    assert_eq!(totals.total_enrolled_stake, 400_000 * QST);
+    assert_eq!(totals.penalty_vault_amount, 40_000 * QST);
+    let position = indexer.position(&alice()).unwrap().unwrap();
+    assert!(position.enrolled);
+    assert!(!position.bonus_claimed);
+    assert_eq!(position.enrolled_stake, 400_000 * QST);
+}
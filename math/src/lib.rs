@@ -0,0 +1,142 @@
+//! Staking economics as pure functions: stake validation and node-key issuance,
+//! early-unstake penalty tiers and the pro-rata bonus split.
+//!
+//! The on-chain program, the Rust client and the `qst-sim` simulator all call this
+//! crate, so off-chain previews and models run the same code as the chain. It is
+//! `no_std` with no dependencies, and every rule takes its parameters explicitly
+//! (`StakeRules`, `PenaltySchedule`) so simulations can try other values.
+
+#![no_std]
+
+// Stake size rules: amounts are whole multiples of `minimum`, each earning `keys_per_stake`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct StakeRules {
+    pub minimum: u64,
+    pub maximum: u64,
+    pub keys_per_stake: u32,
+}
+
+// Why a stake amount is rejected, in the order the program checks
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StakeError {
+    Zero,
+    BelowMinimum,
+    AboveMaximum,
+    NotMultiple,
+    KeyOverflow,
+}
+
+impl StakeRules {
+    // Node keys issued for one stake of `amount`
+    pub fn node_keys_for(&self, amount: u64) -> Result<u32, StakeError> {
+        if amount == 0 {
+            return Err(StakeError::Zero);
+        }
+        if amount < self.minimum {
+            return Err(StakeError::BelowMinimum);
+        }
+        if amount > self.maximum {
+            return Err(StakeError::AboveMaximum);
+        }
+        if amount / self.minimum * self.minimum != amount {
+            return Err(StakeError::NotMultiple);
+        }
+        (amount / self.minimum)
+            .checked_mul(self.keys_per_stake as u64)
+            .and_then(|keys| u32::try_from(keys).ok())
+            .ok_or(StakeError::KeyOverflow)
+    }
+
+    // Stake recovered from a position's node keys (its bonus weight)
+    pub fn original_stake(&self, node_keys_earned: u32) -> u64 {
+        (node_keys_earned as u64 / self.keys_per_stake as u64) * self.minimum
+    }
+}
+
+// Early unstake tiers, relative to the position's principal unlock time
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Tier {
+    Unlocked,
+    Late,
+    Early,
+    Blocked,
+}
+
+// Thresholds are seconds until unlock: at most `late_threshold` pays `late_rate`, at most
+// `early_threshold` pays `early_rate`, anything further out is blocked. Rates are percent.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PenaltySchedule {
+    pub late_threshold: i64,
+    pub early_threshold: i64,
+    pub late_rate: u64,
+    pub early_rate: u64,
+}
+
+impl PenaltySchedule {
+    pub fn tier(&self, time_until_unlock: i64) -> Tier {
+        if time_until_unlock <= 0 {
+            Tier::Unlocked
+        } else if time_until_unlock <= self.late_threshold {
+            Tier::Late
+        } else if time_until_unlock <= self.early_threshold {
+            Tier::Early
+        } else {
+            Tier::Blocked
+        }
+    }
+
+    // Penalty percentage; Blocked has no rate because the unstake is rejected
+    pub fn rate(&self, tier: Tier) -> u64 {
+        match tier {
+            Tier::Unlocked | Tier::Blocked => 0,
+            Tier::Late => self.late_rate,
+            Tier::Early => self.early_rate,
+        }
+    }
+
+    // Seconds until the position moves to the next (cheaper) tier; 0 once unlocked
+    pub fn seconds_until_next(&self, tier: Tier, time_until_unlock: i64) -> i64 {
+        match tier {
+            Tier::Blocked => time_until_unlock - self.early_threshold,
+            Tier::Early => time_until_unlock - self.late_threshold,
+            Tier::Late => time_until_unlock,
+            Tier::Unlocked => 0,
+        }
+    }
+
+    // (penalty, net to user) for unstaking `amount`; None while blocked
+    pub fn unstake_outcome(&self, amount: u64, time_until_unlock: i64) -> Option<(u64, u64)> {
+        let tier = self.tier(time_until_unlock);
+        if tier == Tier::Blocked {
+            return None;
+        }
+        let penalty = penalty_amount(amount, self.rate(tier));
+        Some((penalty, amount - penalty))
+    }
+}
+
+// `rate` percent of `amount`, rounded down
+pub fn penalty_amount(amount: u64, rate: u64) -> u64 {
+    ((amount as u128) * (rate as u128) / 100u128) as u64
+}
+
+// share of `amount` owed to a position of `user_amount` out of `total` (0 when total is 0).
+// Used for bonus payouts, bonus previews and settlement penalty shares.
+pub fn pro_rata_share(amount: u64, user_amount: u64, total: u64) -> u64 {
+    if total == 0 {
+        return 0;
+    }
+    ((amount as u128) * (user_amount as u128) / (total as u128)) as u64
+}
+
+// part of an `amount` streamed linearly from `start` to `end` that is released by `current_time`.
+// Used for reward emissions.
+pub fn stream_released(amount: u64, start: i64, end: i64, current_time: i64) -> u64 {
+    if current_time >= end {
+        return amount;
+    }
+    if current_time <= start {
+        return 0;
+    }
+    ((amount as u128) * ((current_time - start) as u128) / ((end - start) as u128)) as u64
+}
@@ -1,38 +1,90 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer, Mint};
+use qst_staking_math::{penalty_amount, PenaltySchedule, StakeError, StakeRules, Tier};
 
-declare_id!("MAINNET_PROGRAM_ID_TO_BE_GENERATED");
+pub use qst_staking_math::{pro_rata_share, stream_released};
 
-// === ECONOMICS (QST mainnet: 6 decimals) ===
-const MINIMUM_STAKE_AMOUNT: u64 = 200_000 * 1_000_000; // 200,000 QST (6 decimals)
-const MAXIMUM_STAKE_AMOUNT: u64 = 10_000_000 * 1_000_000; // 10,000,000 QST (6 decimals)
-const KEYS_PER_STAKE: u32 = 2;
+declare_id!("HYJL4HMgnP2NaUjHPWGRBZEtUbaXyH6JiqVqZF7YVYk5");
 
-// === DEV WALLET FOR DEPLOYMENT ===
-const DEV_WALLET: &str = "oejJbosh9dQKKVNNPEkDZxkiTNMkMjAKjYftMGQA2ww";
+// === ECONOMICS (QST mainnet: 6 decimals) ===
+pub const MINIMUM_STAKE_AMOUNT: u64 = 200_000 * 1_000_000; // 200,000 QST (6 decimals)
+pub const MAXIMUM_STAKE_AMOUNT: u64 = 10_000_000 * 1_000_000; // 10,000,000 QST (6 decimals)
+pub const KEYS_PER_STAKE: u32 = 2;
 
 // === TIME CONSTANTS ===
-const PRINCIPAL_LOCK_PERIOD: i64 = 25 * 24 * 60 * 60; // 25 days
-const BONUS_LOCK_PERIOD: i64 = 10 * 24 * 60 * 60;     // +10 days bonus
-const STAKE_WINDOW_PERIOD: i64 = 9 * 24 * 60 * 60;    // 9 days stake window (HAL-01 fix)
-const BONUS_ENROLLMENT_PERIOD: i64 = 48 * 60 * 60;    // 48 hours enrollment window
-const BONUS_WITHDRAWAL_DELAY: i64 = 1 * 24 * 60 * 60;     // 1 day after last user unlock (HAL-02 fix)
-const EARLY_UNSTAKE_THRESHOLD_1: i64 = 7 * 24 * 60 * 60; // 7 days
-const EARLY_UNSTAKE_THRESHOLD_2: i64 = 15 * 24 * 60 * 60; // 15 days
+pub use durations::*;
+
+#[cfg(not(feature = "devnet"))]
+mod durations {
+    pub const PRINCIPAL_LOCK_PERIOD: i64 = 25 * 24 * 60 * 60; // 25 days
+    pub const BONUS_LOCK_PERIOD: i64 = 10 * 24 * 60 * 60;     // +10 days bonus
+    pub const STAKE_WINDOW_PERIOD: i64 = 9 * 24 * 60 * 60;    // 9 days stake window (HAL-01 fix)
+    pub const BONUS_ENROLLMENT_PERIOD: i64 = 48 * 60 * 60;    // 48 hours enrollment window
+    pub const MAX_STAKE_WINDOW_PERIOD: i64 = 21 * 24 * 60 * 60; // 21 days: longest an extended window may run
+    pub const WINDOW_CLOSE_NOTICE_PERIOD: i64 = 24 * 60 * 60;  // 24 hours notice before an early close
+    pub const MAX_SCHEDULE_HORIZON: i64 = 365 * 24 * 60 * 60; // furthest ahead a window start may be scheduled
+    pub const BONUS_WITHDRAWAL_DELAY: i64 = 24 * 60 * 60;     // 1 day after last user unlock (HAL-02 fix)
+    pub const EARLY_UNSTAKE_THRESHOLD_1: i64 = 7 * 24 * 60 * 60; // 7 days
+    pub const EARLY_UNSTAKE_THRESHOLD_2: i64 = 15 * 24 * 60 * 60; // 15 days
+    pub const BONUS_CLAIM_PERIOD: i64 = 60 * 24 * 60 * 60;     // default: 60 days (2 months) to claim bonus before dust collection
+    pub const MIN_BONUS_CLAIM_PERIOD: i64 = 30 * 24 * 60 * 60; // lower bound for the configured claim period
+    pub const ADMIN_INACTIVITY_PERIOD: i64 = 90 * 24 * 60 * 60;     // default: 90 days of admin silence before settlement
+    pub const MIN_ADMIN_INACTIVITY_PERIOD: i64 = 30 * 24 * 60 * 60; // lower bound for the configured inactivity period
+}
+
+// Devnet build (`anchor build -- --features devnet`): the same program with every duration cut
+// to seconds or minutes, so a whole round can be tried out on devnet
+#[cfg(feature = "devnet")]
+mod durations {
+    pub const PRINCIPAL_LOCK_PERIOD: i64 = 60;        // 1 minute (25 days on mainnet)
+    pub const BONUS_LOCK_PERIOD: i64 = 30;            // +30 seconds bonus
+    pub const STAKE_WINDOW_PERIOD: i64 = 300;         // 5 minutes stake window
+    pub const BONUS_ENROLLMENT_PERIOD: i64 = 120;     // 2 minutes enrollment window
+    pub const MAX_STAKE_WINDOW_PERIOD: i64 = 700;     // longest an extended window may run
+    pub const WINDOW_CLOSE_NOTICE_PERIOD: i64 = 60;   // notice before an early close
+    pub const MAX_SCHEDULE_HORIZON: i64 = 24 * 60 * 60; // furthest ahead a window start may be scheduled
+    pub const BONUS_WITHDRAWAL_DELAY: i64 = 10;       // 10 seconds after last user unlock
+    pub const EARLY_UNSTAKE_THRESHOLD_1: i64 = 14;    // 14 seconds (7 days on mainnet)
+    pub const EARLY_UNSTAKE_THRESHOLD_2: i64 = 30;    // 30 seconds (15 days on mainnet)
+    pub const BONUS_CLAIM_PERIOD: i64 = 600;          // default claim period before dust collection
+    pub const MIN_BONUS_CLAIM_PERIOD: i64 = 300;      // lower bound for the configured claim period
+    pub const ADMIN_INACTIVITY_PERIOD: i64 = 900;     // default admin silence before settlement
+    pub const MIN_ADMIN_INACTIVITY_PERIOD: i64 = 300; // lower bound for the configured inactivity period
+}
 
 // === PENALTIES ===
-const PENALTY_RATE_EARLY: u64 = 30; // 30% penalty for 15—8 days remaining
-const PENALTY_RATE_LATE: u64 = 20;  // 20% penalty for 7—1 days remaining
+pub const PENALTY_RATE_EARLY: u64 = 30; // 30% penalty for 15—8 days remaining
+pub const PENALTY_RATE_LATE: u64 = 20;  // 20% penalty for 7—1 days remaining
+pub const ENROLLED_EXIT_PENALTY_RATE: u64 = 40; // default penalty for bonus enrollees exiting early
+
+// === VIEWS ===
+pub const MAX_STAKE_INFO_BATCH: usize = 10; // 10 StakeInfos fit in the 1024-byte return data limit
+
+// === REWARD EMISSIONS ===
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000; // scale of reward_per_weight
+
+// === SHARED MATH (qst-staking-math, also used by the client and qst-sim) ===
+pub const STAKE_RULES: StakeRules = StakeRules {
+    minimum: MINIMUM_STAKE_AMOUNT,
+    maximum: MAXIMUM_STAKE_AMOUNT,
+    keys_per_stake: KEYS_PER_STAKE,
+};
+pub const PENALTY_SCHEDULE: PenaltySchedule = PenaltySchedule {
+    late_threshold: EARLY_UNSTAKE_THRESHOLD_1,
+    early_threshold: EARLY_UNSTAKE_THRESHOLD_2,
+    late_rate: PENALTY_RATE_LATE,
+    early_rate: PENALTY_RATE_EARLY,
+};
 
 #[program]
 pub mod qst_staking_mainnet {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, admin_wallet: Pubkey) -> Result<()> {
-        // SECURITY: Only allow dev wallet to initialize
-        let dev_wallet_key = DEV_WALLET.parse::<Pubkey>().map_err(|_| ErrorCode::InvalidDevWallet)?;
-        require!(ctx.accounts.payer.key() == dev_wallet_key, ErrorCode::Unauthorized);
+    pub fn initialize(ctx: Context<Initialize>, admin_wallet: Pubkey, fee_recipient: Pubkey) -> Result<()> {
+        // SECURITY: Only the program's upgrade authority may initialize (checked on program_data)
         require!(admin_wallet == ctx.accounts.payer.key(), ErrorCode::Unauthorized);
+        require!(fee_recipient != Pubkey::default(), ErrorCode::InvalidFeeRecipient);
 
         // Enforce correct mint precision for QST mainnet
         require!(ctx.accounts.qst_mint.decimals == 6, ErrorCode::InvalidMintDecimals);
@@ -41,6 +93,7 @@ pub mod qst_staking_mainnet {
         staking_pool.authority = admin_wallet;
         staking_pool.total_staked = 0;
         staking_pool.total_enrolled_stake = 0;
+        staking_pool.total_reward_weight = 0;
         staking_pool.penalty_vault_amount = 0;
         staking_pool.first_stake_timestamp = 0;
         staking_pool.bonus_enrollment_deadline = 0;
@@ -48,6 +101,52 @@ pub mod qst_staking_mainnet {
         staking_pool.latest_bonus_unlock_time = 0; // HAL-02 fix
         staking_pool.qst_mint = ctx.accounts.qst_mint.key();
         staking_pool.bump = ctx.bumps.staking_pool;
+        // No caps until the admin configures them
+        staking_pool.max_total_staked = u64::MAX;
+        staking_pool.max_user_stake = u64::MAX;
+        // No allowlist until the admin configures one
+        staking_pool.allowlist_root = [0u8; 32];
+        staking_pool.allowlist_public_time = 0;
+        staking_pool.enrolled_exit_penalty_rate = ENROLLED_EXIT_PENALTY_RATE;
+        let current_time = Clock::get()?.unix_timestamp;
+        // Abandoned-pool escape hatch
+        staking_pool.last_admin_action = current_time;
+        staking_pool.admin_inactivity_period = ADMIN_INACTIVITY_PERIOD;
+        staking_pool.settlement_active = false;
+        staking_pool.settlement_penalty_vault = 0;
+        staking_pool.settlement_total_stake = 0;
+        staking_pool.event_seq = 0;
+        staking_pool.fee_recipient = fee_recipient;
+        // Unclaimed bonus is swept to the fee recipient after 60 days unless configured otherwise
+        staking_pool.bonus_claim_period = BONUS_CLAIM_PERIOD;
+        staking_pool.unclaimed_bonus_policy = UnclaimedBonusPolicy::Sweep;
+        staking_pool.claimed_enrolled_stake = 0;
+        // Bonus split is frozen on the first payout
+        staking_pool.bonus_snapshot_taken = false;
+        staking_pool.bonus_snapshot_vault = 0;
+        staking_pool.bonus_snapshot_weight = 0;
+        staking_pool.redistribution_snapshot_vault = 0;
+        staking_pool.redistribution_snapshot_weight = 0;
+        staking_pool.rounding_dust = 0;
+        staking_pool.post_snapshot_penalties = 0;
+        staking_pool.unclaimed_bonus_swept = false;
+        staking_pool.round = 0;
+        // No emissions until the admin funds a reward stream
+        staking_pool.reward_vault_amount = 0;
+        staking_pool.reward_stream_amount = 0;
+        staking_pool.reward_start = 0;
+        staking_pool.reward_end = 0;
+        staking_pool.reward_released = 0;
+        staking_pool.reward_per_weight = 0;
+        staking_pool.reward_unallocated = 0;
+
+        emit_cpi!(PoolInitialized {
+            authority: admin_wallet,
+            qst_mint: staking_pool.qst_mint,
+            fee_recipient,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
 
         msg!("QST Staking Pool initialized with admin: {:?}", admin_wallet);
         Ok(())
@@ -62,21 +161,26 @@ pub mod qst_staking_mainnet {
             ErrorCode::Unauthorized
         );
 
-        // HAL-03 fix: Prevent restarting entirely - can only start once
+        // HAL-03 fix: Prevent restarting - can only start once per round (see start_next_round)
         require!(
             staking_pool.first_stake_timestamp == 0,
             ErrorCode::StakeWindowAlreadyActive
         );
 
         let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.require_allowlist_phase_at(current_time)?;
+        staking_pool.last_admin_action = current_time;
         staking_pool.first_stake_timestamp = current_time;
         staking_pool.stake_window_end = current_time + STAKE_WINDOW_PERIOD;
         staking_pool.bonus_enrollment_deadline = current_time + BONUS_ENROLLMENT_PERIOD;
 
-        emit!(StakeWindowStarted {
+        emit_cpi!(StakeWindowStarted {
             start_time: current_time,
             stake_window_end: staking_pool.stake_window_end,
             bonus_enrollment_deadline: staking_pool.bonus_enrollment_deadline,
+            max_total_staked: staking_pool.max_total_staked,
+            max_user_stake: staking_pool.max_user_stake,
+            seq: staking_pool.next_event_seq()?,
         });
 
         msg!(
@@ -87,572 +191,2571 @@ pub mod qst_staking_mainnet {
         Ok(())
     }
 
-    pub fn enroll_in_bonus(ctx: Context<EnrollInBonus>) -> Result<()> {
+    // RollOver policy: once the claim period is over and every position has been withdrawn,
+    // resets the pool for another stake window. The penalty vault, unclaimed bonus included,
+    // becomes the next round's; settings, node keys and emissions carry over unchanged.
+    pub fn start_next_round(ctx: Context<StartNextRound>) -> Result<()> {
         let staking_pool = &mut ctx.accounts.staking_pool;
-        let stake_account = &mut ctx.accounts.stake_account;
 
-        let current_time = Clock::get()?.unix_timestamp;
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            staking_pool.unclaimed_bonus_policy == UnclaimedBonusPolicy::RollOver,
+            ErrorCode::RollOverNotEnabled
+        );
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
 
-        // Check if bonus enrollment is still open
+        let current_time = Clock::get()?.unix_timestamp;
         require!(
-            current_time <= staking_pool.bonus_enrollment_deadline,
-            ErrorCode::BonusEnrollmentClosed
+            staking_pool.window_opened(current_time),
+            ErrorCode::StakeWindowNotStarted
         );
-        // HAL-05 fix: Removed redundant validation (bonus_enrollment_deadline > 0 is implied by the above check)
+        require!(
+            current_time >= staking_pool.bonus_claim_deadline(),
+            ErrorCode::BonusClaimPeriodNotExpired
+        );
+        // Stake accounts are only reset when next touched (StakeAccount::enter_round), which
+        // is safe because none of them holds principal
+        require!(staking_pool.total_staked == 0, ErrorCode::RoundStillStaked);
+
+        // No principal left means no reward weight either, so emissions need no settling
+        staking_pool.round = staking_pool
+            .round
+            .checked_add(1)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.first_stake_timestamp = 0;
+        staking_pool.stake_window_end = 0;
+        staking_pool.bonus_enrollment_deadline = 0;
+        staking_pool.latest_bonus_unlock_time = 0;
+        staking_pool.total_enrolled_stake = 0;
+        staking_pool.claimed_enrolled_stake = 0;
+        staking_pool.bonus_snapshot_taken = false;
+        staking_pool.bonus_snapshot_vault = 0;
+        staking_pool.bonus_snapshot_weight = 0;
+        staking_pool.redistribution_snapshot_vault = 0;
+        staking_pool.redistribution_snapshot_weight = 0;
+        staking_pool.rounding_dust = 0;
+        staking_pool.post_snapshot_penalties = 0;
+        staking_pool.unclaimed_bonus_swept = false;
+        staking_pool.last_admin_action = current_time;
+
+        emit_cpi!(NextRoundStarted {
+            round: staking_pool.round,
+            rolled_over: staking_pool.penalty_vault_amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
 
-        // User must have existing stake to enroll
-        require!(stake_account.amount > 0, ErrorCode::NoStakeToEnroll);
-        require!(!stake_account.enrolled_in_bonus, ErrorCode::AlreadyEnrolledInBonus);
+        msg!(
+            "Round {} ready, {} rolled over into its penalty vault",
+            staking_pool.round,
+            staking_pool.penalty_vault_amount
+        );
+        Ok(())
+    }
 
-        // Enroll
-        stake_account.enrolled_in_bonus = true;
+    // Sets a future window start so the opening doesn't depend on when the admin transaction
+    // lands. Can be called again to reschedule, or cancelled, until the window opens.
+    pub fn schedule_stake_window(ctx: Context<ScheduleStakeWindow>, start_ts: i64) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
 
-        // On enroll, add the user's current principal to total_enrolled_stake
-        staking_pool.total_enrolled_stake = staking_pool
-            .total_enrolled_stake
-            .checked_add(stake_account.amount)
-            .ok_or(ErrorCode::NumericOverflow)?;
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
-        // Set bonus unlock to principal + 10d
-        stake_account.bonus_unlock_time = stake_account.principal_unlock_time + BONUS_LOCK_PERIOD;
+        let current_time = Clock::get()?.unix_timestamp;
+        // HAL-03: an opened window can never be moved
+        require!(
+            !staking_pool.window_opened(current_time),
+            ErrorCode::StakeWindowAlreadyActive
+        );
+        let horizon = current_time
+            .checked_add(MAX_SCHEDULE_HORIZON)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        require!(
+            start_ts > current_time && start_ts <= horizon,
+            ErrorCode::InvalidWindowStart
+        );
+        staking_pool.require_allowlist_phase_at(start_ts)?;
 
-        // HAL-02 fix: Track latest unlock time for bonus withdrawal delay
-        if stake_account.bonus_unlock_time > staking_pool.latest_bonus_unlock_time {
-            staking_pool.latest_bonus_unlock_time = stake_account.bonus_unlock_time;
-        }
+        staking_pool.last_admin_action = current_time;
+        staking_pool.first_stake_timestamp = start_ts;
+        staking_pool.stake_window_end = start_ts
+            .checked_add(STAKE_WINDOW_PERIOD)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.bonus_enrollment_deadline = start_ts
+            .checked_add(BONUS_ENROLLMENT_PERIOD)
+            .ok_or(ErrorCode::NumericOverflow)?;
 
-        emit!(BonusEnrollment {
-            user: ctx.accounts.user.key(),
-            enrolled_stake: stake_account.amount,
+        emit_cpi!(StakeWindowScheduled {
+            start_time: start_ts,
+            stake_window_end: staking_pool.stake_window_end,
+            bonus_enrollment_deadline: staking_pool.bonus_enrollment_deadline,
             timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
         });
 
         msg!(
-            "User {} enrolled in bonus with stake: {}",
-            ctx.accounts.user.key(),
-            stake_account.amount
+            "Stake window scheduled for {}. Window ends: {}, Bonus enrollment deadline: {}",
+            start_ts,
+            staking_pool.stake_window_end,
+            staking_pool.bonus_enrollment_deadline
         );
         Ok(())
     }
 
-    pub fn stake_tokens(ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
-        msg!("=== STARTING STAKE_TOKENS ===");
-        msg!("Amount to stake: {}", amount);
-
+    pub fn cancel_stake_window_schedule(ctx: Context<CancelStakeWindowSchedule>) -> Result<()> {
         let staking_pool = &mut ctx.accounts.staking_pool;
-        let stake_account = &mut ctx.accounts.stake_account;
-        let user_token_account = &ctx.accounts.user_token_account;
-        let pool_token_account = &ctx.accounts.pool_token_account;
 
-        // Validate amount
-        require!(amount > 0, ErrorCode::InvalidAmount);
-        require!(amount >= MINIMUM_STAKE_AMOUNT, ErrorCode::InsufficientStakeAmount);
-        require!(amount <= MAXIMUM_STAKE_AMOUNT, ErrorCode::StakeAmountTooLarge);
-        require!(amount % MINIMUM_STAKE_AMOUNT == 0, ErrorCode::InvalidStakeAmount);
-        msg!("✅ Amount checks passed");
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
         let current_time = Clock::get()?.unix_timestamp;
-        msg!("Current time: {}", current_time);
-        msg!("Stake window end: {}", staking_pool.stake_window_end);
-        msg!("First stake timestamp: {}", staking_pool.first_stake_timestamp);
-
-        // Enforce stake window - no auto-restart
         require!(
-            staking_pool.first_stake_timestamp > 0,
-            ErrorCode::StakeWindowNotStarted
+            staking_pool.first_stake_timestamp != 0,
+            ErrorCode::NoScheduledStakeWindow
         );
         require!(
-            current_time <= staking_pool.stake_window_end,
-            ErrorCode::StakeWindowClosed
+            !staking_pool.window_opened(current_time),
+            ErrorCode::StakeWindowAlreadyActive
         );
-        msg!("✅ Stake window is active");
-
-        // Calculate number of keys based on stake amount
-        let stake_multiplier = amount / MINIMUM_STAKE_AMOUNT;
-        
-        // Use checked math and try_from to prevent overflow/truncation
-        let raw_keys = stake_multiplier
-            .checked_mul(KEYS_PER_STAKE as u64)
-            .ok_or(ErrorCode::NumericOverflow)?;
-        let node_keys_earned = u32::try_from(raw_keys).map_err(|_| ErrorCode::NumericOverflow)?;
-        
-        msg!("Node keys to earn: {}", node_keys_earned);
-
-        // Transfer tokens from user to pool
-        msg!("🔄 Starting token transfer");
-        let cpi_accounts = Transfer {
-            from: user_token_account.to_account_info(),
-            to: pool_token_account.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
-        msg!("✅ Token transfer completed");
 
-        // Initialize or update stake account
-        if stake_account.amount == 0 {
-            msg!("🆕 Initializing new stake account");
-            stake_account.user = ctx.accounts.user.key();
-            stake_account.amount = amount;
-            stake_account.node_keys_earned = node_keys_earned;
-
-            // Reset lock model - unlock time based on current stake time
-            stake_account.principal_unlock_time = current_time + PRINCIPAL_LOCK_PERIOD;
+        let cancelled_start_time = staking_pool.first_stake_timestamp;
+        staking_pool.last_admin_action = current_time;
+        staking_pool.first_stake_timestamp = 0;
+        staking_pool.stake_window_end = 0;
+        staking_pool.bonus_enrollment_deadline = 0;
 
-            // Not enrolled by default; bonus unlock remains 0 until enroll
-            stake_account.bonus_unlock_time = 0;
-            stake_account.enrolled_in_bonus = false;
-            stake_account.bump = ctx.bumps.stake_account;
-        } else {
-            msg!("📈 Updating existing stake account");
-            stake_account.amount = stake_account
-                .amount
-                .checked_add(amount)
-                .ok_or(ErrorCode::NumericOverflow)?;
-            stake_account.node_keys_earned = stake_account
-                .node_keys_earned
-                .checked_add(node_keys_earned)
-                .ok_or(ErrorCode::NumericOverflow)?;
+        emit_cpi!(StakeWindowScheduleCancelled {
+            cancelled_start_time,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
 
-            // Reset lock model - set unlock to current_time + 25 days
-            stake_account.principal_unlock_time = current_time + PRINCIPAL_LOCK_PERIOD;
+        msg!("Scheduled stake window start {} cancelled", cancelled_start_time);
+        Ok(())
+    }
 
-            // If already enrolled, bonus unlock follows principal (principal + 10d)
-            if stake_account.enrolled_in_bonus {
-                stake_account.bonus_unlock_time = stake_account.principal_unlock_time + BONUS_LOCK_PERIOD;
+    // Moves the window end later, up to MAX_STAKE_WINDOW_PERIOD after the start. Unstaking is
+    // gated on stake_window_end, so HAL-01 holds until the new end.
+    pub fn extend_stake_window(ctx: Context<ExtendStakeWindow>, new_window_end: i64) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
 
-                // HAL-02 fix: Track latest unlock time for bonus withdrawal delay
-                if stake_account.bonus_unlock_time > staking_pool.latest_bonus_unlock_time {
-                    staking_pool.latest_bonus_unlock_time = stake_account.bonus_unlock_time;
-                }
-            }
-        }
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
-        // Update pool totals
-        staking_pool.total_staked = staking_pool
-            .total_staked
-            .checked_add(amount)
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.require_window_open(current_time)?;
+        require!(
+            new_window_end > staking_pool.stake_window_end,
+            ErrorCode::InvalidWindowEnd
+        );
+        let latest_window_end = staking_pool
+            .first_stake_timestamp
+            .checked_add(MAX_STAKE_WINDOW_PERIOD)
             .ok_or(ErrorCode::NumericOverflow)?;
+        require!(
+            new_window_end <= latest_window_end,
+            ErrorCode::StakeWindowTooLong
+        );
 
-        // Track enrolled weight properly: only enrolled users increase total_enrolled_stake
-        if stake_account.enrolled_in_bonus {
-            staking_pool.total_enrolled_stake = staking_pool
-                .total_enrolled_stake
-                .checked_add(amount)
-                .ok_or(ErrorCode::NumericOverflow)?;
-        }
-
-        msg!("✅ STAKE_TOKENS COMPLETED SUCCESSFULLY");
-        msg!("Final stake amount: {}", stake_account.amount);
-        msg!("Total pool staked: {}", staking_pool.total_staked);
+        let previous_window_end = staking_pool.stake_window_end;
+        staking_pool.last_admin_action = current_time;
+        staking_pool.stake_window_end = new_window_end;
 
-        // Emit event
-        emit!(StakeEvent {
-            user: ctx.accounts.user.key(),
-            amount,
-            total_staked: staking_pool.total_staked,
-            node_keys_earned,
-            principal_unlock_time: stake_account.principal_unlock_time,
-            bonus_unlock_time: stake_account.bonus_unlock_time,
-            enrolled_in_bonus: stake_account.enrolled_in_bonus,
+        emit_cpi!(StakeWindowExtended {
+            previous_window_end,
+            stake_window_end: new_window_end,
             timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
         });
 
+        msg!("Stake window extended from {} to {}", previous_window_end, new_window_end);
         Ok(())
     }
 
-    pub fn unstake_tokens(ctx: Context<UnstakeTokens>, amount: u64) -> Result<()> {
-        let stake_account = &mut ctx.accounts.stake_account;
+    // Moves the window end earlier, but never sooner than WINDOW_CLOSE_NOTICE_PERIOD from now
+    // so stakers see the change coming. Bonus enrollment closes no later than the window.
+    pub fn close_stake_window_early(ctx: Context<CloseStakeWindowEarly>, new_window_end: i64) -> Result<()> {
         let staking_pool = &mut ctx.accounts.staking_pool;
 
-        // HAL-04 fix: Prevent zero amount unstaking
-        require!(amount > 0, ErrorCode::InvalidAmount);
-        require!(stake_account.amount >= amount, ErrorCode::InsufficientStakeBalance);
-
-        // Block unstaking completely for bonus enrolled users
-        require!(!stake_account.enrolled_in_bonus, ErrorCode::BonusEnrolledCannotUnstake);
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
         let current_time = Clock::get()?.unix_timestamp;
-
-        // HAL-01 fix: Ensure unstaking only happens after stake window ends
+        staking_pool.require_window_open(current_time)?;
         require!(
-            current_time > staking_pool.stake_window_end,
-            ErrorCode::StakeWindowStillActive
+            new_window_end < staking_pool.stake_window_end,
+            ErrorCode::InvalidWindowEnd
+        );
+        let earliest_window_end = current_time
+            .checked_add(WINDOW_CLOSE_NOTICE_PERIOD)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        require!(
+            new_window_end >= earliest_window_end,
+            ErrorCode::InsufficientCloseNotice
         );
-        let time_until_unlock = stake_account.principal_unlock_time - current_time;
 
-        // Early exit options (relative to current principal unlock date)
-        let (penalty_amount, net_amount) = if time_until_unlock <= 0 {
-            // Unlocked: No penalty
-            (0u64, amount)
-        } else if time_until_unlock <= EARLY_UNSTAKE_THRESHOLD_1 {
-            // 10—0 days remaining: 20% penalty
-            let penalty = ((amount as u128) * (PENALTY_RATE_LATE as u128) / 100u128) as u64;
-            (penalty, amount - penalty)
-        } else if time_until_unlock <= EARLY_UNSTAKE_THRESHOLD_2 {
-            // 20—10 days remaining: 30% penalty
-            let penalty = ((amount as u128) * (PENALTY_RATE_EARLY as u128) / 100u128) as u64;
-            (penalty, amount - penalty)
-        } else {
-            // More than 20 days remaining: Unstake blocked
-            return Err(ErrorCode::UnstakeBlocked.into());
-        };
+        let previous_window_end = staking_pool.stake_window_end;
+        staking_pool.last_admin_action = current_time;
+        staking_pool.stake_window_end = new_window_end;
+        staking_pool.bonus_enrollment_deadline =
+            staking_pool.bonus_enrollment_deadline.min(new_window_end);
 
-        let user_token_account = &ctx.accounts.user_token_account;
-        let pool_token_account = &ctx.accounts.pool_token_account;
+        emit_cpi!(StakeWindowClosedEarly {
+            previous_window_end,
+            stake_window_end: new_window_end,
+            bonus_enrollment_deadline: staking_pool.bonus_enrollment_deadline,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
 
-        // Setup PDA signer
-        let authority_seed = b"staking_pool";
-        let bump_bytes = [staking_pool.bump];
-        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
-        let signer = &[signer_seeds.as_ref()];
+        msg!("Stake window now closes at {} (was {})", new_window_end, previous_window_end);
+        Ok(())
+    }
 
-        // Transfer net amount to user
-        if net_amount > 0 {
-            let cpi_accounts = Transfer {
-                from: pool_token_account.to_account_info(),
-                to: user_token_account.to_account_info(),
-                authority: staking_pool.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-            token::transfer(cpi_ctx, net_amount)?;
-        }
+    pub fn configure_stake_caps(
+        ctx: Context<ConfigureStakeCaps>,
+        max_total_staked: u64,
+        max_user_stake: u64,
+    ) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
 
-        // Add penalty to the penalty vault (stays in pool for enrolled users)
-        if penalty_amount > 0 {
-            staking_pool.penalty_vault_amount = staking_pool
-                .penalty_vault_amount
-                .checked_add(penalty_amount)
-                .ok_or(ErrorCode::NumericOverflow)?;
-        }
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
-        // If enrolled, reduce the enrolled pool weight by the amount being unstaked
-        if stake_account.enrolled_in_bonus {
-            staking_pool.total_enrolled_stake = staking_pool
-                .total_enrolled_stake
-                .saturating_sub(amount);
-        }
+        // Caps are fixed once the window opens so stakers know the rules up front
+        require!(
+            !staking_pool.window_opened(Clock::get()?.unix_timestamp),
+            ErrorCode::StakeWindowAlreadyActive
+        );
 
-        // Update balances
-        stake_account.amount = stake_account
-            .amount
-            .checked_sub(amount)
-            .ok_or(ErrorCode::NumericOverflow)?;
-        staking_pool.total_staked = staking_pool
-            .total_staked
-            .checked_sub(amount)
-            .ok_or(ErrorCode::NumericOverflow)?;
+        // A wallet cap below the minimum stake would block everyone
+        require!(max_user_stake >= MINIMUM_STAKE_AMOUNT, ErrorCode::InvalidStakeCaps);
+        require!(max_user_stake <= max_total_staked, ErrorCode::InvalidStakeCaps);
 
-        emit!(UnstakeEvent {
-            user: ctx.accounts.user.key(),
-            amount,
-            remaining_staked: stake_account.amount,
-            penalty_applied: penalty_amount,
-            net_to_user: net_amount,
-            penalty_vault_total: staking_pool.penalty_vault_amount,
+        staking_pool.max_total_staked = max_total_staked;
+        staking_pool.max_user_stake = max_user_stake;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.last_admin_action = current_time;
+
+        emit_cpi!(StakeCapsUpdated {
+            max_total_staked,
+            max_user_stake,
             timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
         });
 
         msg!(
-            "User {} unstaked {} tokens, penalty: {}, net received: {}",
-            ctx.accounts.user.key(),
-            amount,
-            penalty_amount,
-            net_amount
+            "Stake caps updated. Pool max: {}, per-user max: {}",
+            max_total_staked,
+            max_user_stake
         );
-
         Ok(())
     }
 
-    pub fn withdraw_all(ctx: Context<WithdrawAll>) -> Result<()> {
-        let stake_account = &mut ctx.accounts.stake_account;
+    pub fn configure_allowlist(
+        ctx: Context<ConfigureAllowlist>,
+        merkle_root: [u8; 32],
+        public_time: i64,
+    ) -> Result<()> {
         let staking_pool = &mut ctx.accounts.staking_pool;
 
-        let current_time = Clock::get()?.unix_timestamp;
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
-        // HAL-02 fix: Only check principal unlock time for withdrawing principal
-        // Bonus rewards are withdrawn separately via withdraw_bonus
-        let required_unlock_time = if stake_account.enrolled_in_bonus {
-            stake_account.bonus_unlock_time
-        } else {
-            stake_account.principal_unlock_time
-        };
+        // Allowlist rules are fixed once the window opens
+        require!(
+            !staking_pool.window_opened(Clock::get()?.unix_timestamp),
+            ErrorCode::StakeWindowAlreadyActive
+        );
 
-        require!(current_time >= required_unlock_time, ErrorCode::StillLocked);
-        require!(stake_account.amount > 0, ErrorCode::NoStakeToWithdraw);
+        // A zero root disables the allowlist; staking is then public from the start
+        staking_pool.allowlist_root = merkle_root;
+        staking_pool.allowlist_public_time = public_time;
 
-        let user_token_account = &ctx.accounts.user_token_account;
-        let pool_token_account = &ctx.accounts.pool_token_account;
+        // The window opens at its scheduled start, or no earlier than now if none is scheduled
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.require_allowlist_phase_at(staking_pool.first_stake_timestamp.max(current_time))?;
+        staking_pool.last_admin_action = current_time;
 
-        let principal_amount = stake_account.amount;
-        // HAL-02 fix: No bonus rewards calculated here anymore
-        let bonus_rewards = 0u64;
+        emit_cpi!(AllowlistConfigured {
+            merkle_root,
+            public_time,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
 
-        let total_withdrawal = principal_amount;
+        msg!("Allowlist configured. Public staking opens at: {}", public_time);
+        Ok(())
+    }
 
-        // Setup PDA signer
-        let authority_seed = b"staking_pool";
-        let bump_bytes = [staking_pool.bump];
-        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
-        let signer = &[signer_seeds.as_ref()];
+    pub fn configure_enrolled_exit_penalty(
+        ctx: Context<ConfigureEnrolledExitPenalty>,
+        penalty_rate: u64,
+    ) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
 
-        // Transfer total amount to user
-        let cpi_accounts = Transfer {
-            from: pool_token_account.to_account_info(),
-            to: user_token_account.to_account_info(),
-            authority: staking_pool.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, total_withdrawal)?;
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
-        // Update pool state
-        staking_pool.total_staked = staking_pool
-            .total_staked
-            .checked_sub(principal_amount)
-            .ok_or(ErrorCode::NumericOverflow)?;
+        // Exit terms are fixed once the window opens
+        require!(
+            !staking_pool.window_opened(Clock::get()?.unix_timestamp),
+            ErrorCode::StakeWindowAlreadyActive
+        );
+
+        // Leaving the bonus program early must cost more than a regular early unstake
+        require!(
+            penalty_rate > PENALTY_RATE_EARLY && penalty_rate <= 100,
+            ErrorCode::InvalidPenaltyRate
+        );
 
-        // HAL-02 fix: Don't update total_enrolled_stake here - move to withdraw_bonus
+        staking_pool.enrolled_exit_penalty_rate = penalty_rate;
 
-        emit!(WithdrawAllEvent {
-            user: ctx.accounts.user.key(),
-            principal_amount,
-            bonus_rewards,
-            total_withdrawn: total_withdrawal,
-            node_keys_retained: stake_account.node_keys_earned,
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.last_admin_action = current_time;
+
+        emit_cpi!(EnrolledExitPenaltyUpdated {
+            penalty_rate,
             timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
         });
 
-        // Reset stake account but keep node keys
-        let node_keys_to_keep = stake_account.node_keys_earned;
-        stake_account.amount = 0;
-        stake_account.node_keys_earned = node_keys_to_keep; // Node keys are permanent
-        // HAL-02 fix: Don't reset enrolled_in_bonus here - move to withdraw_bonus
-        stake_account.principal_unlock_time = 0;
-        stake_account.bonus_unlock_time = 0;
+        msg!("Enrolled exit penalty set to {}%", penalty_rate);
+        Ok(())
+    }
 
-        msg!(
-            "User {} withdrew {} principal, keeping {} node keys",
-            ctx.accounts.user.key(),
-            principal_amount,
-            node_keys_to_keep
+    pub fn configure_admin_inactivity_period(
+        ctx: Context<ConfigureAdminInactivityPeriod>,
+        inactivity_period: i64,
+    ) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
+
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
+
+        // Settlement terms are fixed once the window opens
+        require!(
+            !staking_pool.window_opened(Clock::get()?.unix_timestamp),
+            ErrorCode::StakeWindowAlreadyActive
         );
 
+        require!(
+            inactivity_period >= MIN_ADMIN_INACTIVITY_PERIOD,
+            ErrorCode::InvalidInactivityPeriod
+        );
+
+        staking_pool.admin_inactivity_period = inactivity_period;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.last_admin_action = current_time;
+
+        emit_cpi!(AdminInactivityPeriodUpdated {
+            inactivity_period,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!("Admin inactivity period set to {} seconds", inactivity_period);
         Ok(())
     }
 
-    // HAL-02 fix: Separate function to withdraw bonus rewards
-    pub fn withdraw_bonus(ctx: Context<WithdrawBonus>) -> Result<()> {
-        let stake_account = &mut ctx.accounts.stake_account;
+    pub fn configure_unclaimed_bonus(
+        ctx: Context<ConfigureUnclaimedBonus>,
+        policy: UnclaimedBonusPolicy,
+        claim_period: i64,
+    ) -> Result<()> {
         let staking_pool = &mut ctx.accounts.staking_pool;
 
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
+
+        // Enrollees commit under these terms, so they are fixed once the window opens
+        require!(
+            !staking_pool.window_opened(Clock::get()?.unix_timestamp),
+            ErrorCode::StakeWindowAlreadyActive
+        );
+
+        require!(
+            claim_period >= MIN_BONUS_CLAIM_PERIOD,
+            ErrorCode::InvalidClaimPeriod
+        );
+
+        staking_pool.unclaimed_bonus_policy = policy;
+        staking_pool.bonus_claim_period = claim_period;
+
         let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.last_admin_action = current_time;
 
-        // Can only withdraw bonus 1 day after the latest unlock time of all users
-        let bonus_withdrawal_time = staking_pool.latest_bonus_unlock_time + BONUS_WITHDRAWAL_DELAY;
-        require!(current_time >= bonus_withdrawal_time, ErrorCode::BonusWithdrawalNotYetAvailable);
+        emit_cpi!(UnclaimedBonusPolicyUpdated {
+            policy,
+            claim_period,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
 
-        // User must have been enrolled in bonus and completed withdrawal of principal
-        require!(stake_account.enrolled_in_bonus, ErrorCode::NotEnrolledInBonus);
-        require!(stake_account.amount == 0, ErrorCode::MustWithdrawPrincipalFirst);
+        msg!("Unclaimed bonus policy set to {:?}, claim period {} seconds", policy, claim_period);
+        Ok(())
+    }
 
-        let user_token_account = &ctx.accounts.user_token_account;
-        let pool_token_account = &ctx.accounts.pool_token_account;
+    // Streams `amount` linearly to the enrolled weight from `start` to `end`. The tokens sit in
+    // the reward vault, apart from principal and the penalty vault.
+    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64, start: i64, end: i64) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
 
-        let mut bonus_rewards = 0u64;
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::InvalidAmount);
 
-        // Calculate bonus rewards proportionally
-        if staking_pool.total_enrolled_stake > 0 && staking_pool.penalty_vault_amount > 0 {
-            // Use the user's last staked amount (stored in node_keys to track their share)
-            // We need to track original stake amount - let me add a field for this
-            let pv = staking_pool.penalty_vault_amount as u128;
-            let user_original_stake = (stake_account.node_keys_earned as u64 / KEYS_PER_STAKE as u64) * MINIMUM_STAKE_AMOUNT;
-            let user_amt = user_original_stake as u128;
-            let total = staking_pool.total_enrolled_stake as u128;
-            bonus_rewards = if total > 0 { ((pv * user_amt) / total) as u64 } else { 0 };
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(start >= current_time && end > start, ErrorCode::InvalidRewardPeriod);
 
-            staking_pool.penalty_vault_amount =
-                staking_pool.penalty_vault_amount.saturating_sub(bonus_rewards);
-        }
+        // One stream at a time, so a position's accrual never mixes two rates
+        require!(current_time >= staking_pool.reward_end, ErrorCode::RewardStreamActive);
 
-        if bonus_rewards > 0 {
-            // Setup PDA signer
-            let authority_seed = b"staking_pool";
-            let bump_bytes = [staking_pool.bump];
-            let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
-            let signer = &[signer_seeds.as_ref()];
+        // Credit the finished stream first; what it released while nothing was enrolled
+        // is still in the vault and rolls into the new one
+        staking_pool.accrue_rewards(current_time)?;
+        let stream_amount = amount
+            .checked_add(staking_pool.reward_unallocated)
+            .ok_or(ErrorCode::NumericOverflow)?;
 
-            // Transfer bonus amount to user
-            let cpi_accounts = Transfer {
-                from: pool_token_account.to_account_info(),
-                to: user_token_account.to_account_info(),
-                authority: staking_pool.to_account_info(),
-            };
-            let cpi_program = ctx.accounts.token_program.to_account_info();
-            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-            token::transfer(cpi_ctx, bonus_rewards)?;
-        }
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.admin_token_account.to_account_info(),
+            to: ctx.accounts.reward_vault.to_account_info(),
+            authority: ctx.accounts.admin.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        token::transfer(CpiContext::new(cpi_program, cpi_accounts), amount)?;
 
-        emit!(BonusWithdrawEvent {
-            user: ctx.accounts.user.key(),
-            bonus_amount: bonus_rewards,
+        staking_pool.reward_vault_amount = staking_pool
+            .reward_vault_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.reward_stream_amount = stream_amount;
+        staking_pool.reward_start = start;
+        staking_pool.reward_end = end;
+        staking_pool.reward_released = 0;
+        staking_pool.reward_unallocated = 0;
+        staking_pool.last_admin_action = current_time;
+
+        emit_cpi!(RewardsFunded {
+            funder: ctx.accounts.admin.key(),
+            amount,
+            stream_amount,
+            start,
+            end,
             timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
         });
 
-        // HAL-02 fix: Update total_enrolled_stake and reset enrolled_in_bonus here
-        // Get the user's original stake amount for total_enrolled_stake calculation
-        let user_original_stake = (stake_account.node_keys_earned as u64 / KEYS_PER_STAKE as u64) * MINIMUM_STAKE_AMOUNT;
-
-        // Reduce total_enrolled_stake by user's original stake amount
-        staking_pool.total_enrolled_stake = staking_pool
-            .total_enrolled_stake
-            .saturating_sub(user_original_stake);
+        msg!("Funded {} rewards, streaming {} from {} to {}", amount, stream_amount, start, end);
+        Ok(())
+    }
 
-        // Reset enrolled_in_bonus to prevent repeated withdrawals
-        stake_account.enrolled_in_bonus = false;
+    // Emissions released while nothing earned only roll into a later stream. Once the stream
+    // has ended, the admin may take them back instead of funding another one.
+    pub fn reclaim_unallocated_rewards(ctx: Context<ReclaimUnallocatedRewards>) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
 
-        msg!(
-            "User {} withdrew {} bonus rewards, original stake {}, total_enrolled_stake now {}",
-            ctx.accounts.user.key(),
-            bonus_rewards,
-            user_original_stake,
-            staking_pool.total_enrolled_stake
+        // Admin only
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
         );
 
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time >= staking_pool.reward_end, ErrorCode::RewardStreamActive);
+
+        staking_pool.accrue_rewards(current_time)?;
+        let amount = staking_pool.reward_unallocated;
+        require!(amount > 0, ErrorCode::NoUnallocatedRewards);
+
+        staking_pool.reward_unallocated = 0;
+        staking_pool.reward_vault_amount = staking_pool
+            .reward_vault_amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.last_admin_action = current_time;
+
+        let authority_seed = b"staking_pool";
+        let bump_bytes = [staking_pool.bump];
+        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+        let signer = &[signer_seeds.as_ref()];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.reward_vault.to_account_info(),
+            to: ctx.accounts.admin_token_account.to_account_info(),
+            authority: staking_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit_cpi!(UnallocatedRewardsReclaimed {
+            admin: ctx.accounts.admin.key(),
+            amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!("Reclaimed {} unallocated rewards", amount);
         Ok(())
     }
 
-    pub fn get_stake_info(ctx: Context<GetStakeInfo>) -> Result<StakeInfo> {
-        let stake_account = &ctx.accounts.stake_account;
-        let staking_pool = &ctx.accounts.staking_pool;
+    // Lets an active admin prove liveness without changing pool state
+    pub fn admin_heartbeat(ctx: Context<AdminHeartbeat>) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
 
-        // Calculate potential bonus (simple pro-rata preview)
-        let potential_bonus = if stake_account.enrolled_in_bonus && staking_pool.total_enrolled_stake > 0 {
-            let pv = staking_pool.penalty_vault_amount as u128;
-            let user_amt = stake_account.amount as u128;
-            let total = staking_pool.total_enrolled_stake as u128;
-            // HAL-05 fix: Removed redundant total > 0 check (already checked above)
-            ((pv * user_amt) / total) as u64
-        } else {
-            0
-        };
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
 
         let current_time = Clock::get()?.unix_timestamp;
-        
-        // Determine unlock time based on enrollment status
-        let unlock_time = if stake_account.enrolled_in_bonus {
-            stake_account.bonus_unlock_time
-        } else {
-            stake_account.principal_unlock_time
-        };
+        staking_pool.last_admin_action = current_time;
 
-        let stake_info = StakeInfo {
-            amount: stake_account.amount,
-            node_keys_earned: stake_account.node_keys_earned,
-            principal_unlock_time: stake_account.principal_unlock_time,
-            bonus_unlock_time: stake_account.bonus_unlock_time,
-            enrolled_in_bonus: stake_account.enrolled_in_bonus,
-            potential_bonus,
-            unlock_time,
-            is_unlocked: current_time >= unlock_time,
-            time_until_unlock: if current_time >= unlock_time { 0 } else { unlock_time - current_time },
-        };
+        emit_cpi!(AdminHeartbeatRecorded {
+            admin: ctx.accounts.admin.key(),
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
 
-        msg!(
-            "Stake Info - Amount: {}, Keys: {}, Enrolled: {}, Unlock Time: {}, Potential Bonus: {}",
-            stake_info.amount,
-            stake_info.node_keys_earned,
-            stake_info.enrolled_in_bonus,
-            stake_info.unlock_time,
-            stake_info.potential_bonus
+        msg!("Admin heartbeat at {}", current_time);
+        Ok(())
+    }
+
+    pub fn set_authority(ctx: Context<SetAuthority>, new_authority: Pubkey) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
+
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
         );
+        require!(new_authority != Pubkey::default(), ErrorCode::InvalidAuthority);
 
-        Ok(stake_info)
+        let previous_authority = staking_pool.authority;
+        staking_pool.authority = new_authority;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.last_admin_action = current_time;
+
+        emit_cpi!(AuthorityChanged {
+            previous_authority,
+            new_authority,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!("Authority changed from {} to {}", previous_authority, new_authority);
+        Ok(())
     }
 
-    // ADDED: Collect dust from penalty vault to dev wallet
-    pub fn collect_dust(ctx: Context<CollectDust>) -> Result<()> {
+    // Rotates the wallet whose token account receives collect_dust
+    pub fn set_fee_recipient(ctx: Context<SetFeeRecipient>, new_fee_recipient: Pubkey) -> Result<()> {
         let staking_pool = &mut ctx.accounts.staking_pool;
 
         require!(
             ctx.accounts.admin.key() == staking_pool.authority,
             ErrorCode::Unauthorized
         );
+        require!(new_fee_recipient != Pubkey::default(), ErrorCode::InvalidFeeRecipient);
+
+        let previous_fee_recipient = staking_pool.fee_recipient;
+        staking_pool.fee_recipient = new_fee_recipient;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.last_admin_action = current_time;
+
+        emit_cpi!(FeeRecipientChanged {
+            previous_fee_recipient,
+            new_fee_recipient,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!("Fee recipient changed from {} to {}", previous_fee_recipient, new_fee_recipient);
+        Ok(())
+    }
+
+    pub fn enroll_in_bonus(ctx: Context<EnrollInBonus>) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        let stake_account = &mut ctx.accounts.stake_account;
+        stake_account.enter_round(staking_pool.round);
 
         let current_time = Clock::get()?.unix_timestamp;
 
-        // Give users 2 months to claim their bonus rewards after bonus withdrawals become available
-        let bonus_claim_deadline = staking_pool.latest_bonus_unlock_time + BONUS_WITHDRAWAL_DELAY + (60 * 24 * 60 * 60); // +60 days (2 months)
+        // A scheduled window has deadlines set before it opens
         require!(
-            current_time >= bonus_claim_deadline,
-            ErrorCode::BonusClaimPeriodNotExpired
+            current_time >= staking_pool.first_stake_timestamp,
+            ErrorCode::StakeWindowNotOpenYet
         );
 
-        // Only collect when penalty vault has tokens
+        // Check if bonus enrollment is still open
         require!(
-            staking_pool.penalty_vault_amount > 0,
-            ErrorCode::NoDustToCollect
+            current_time <= staking_pool.bonus_enrollment_deadline,
+            ErrorCode::BonusEnrollmentClosed
+        );
+        // HAL-05 fix: Removed redundant validation (bonus_enrollment_deadline > 0 is implied by the above check)
+
+        enroll_position(staking_pool, stake_account, current_time)?;
+
+        emit_cpi!(BonusEnrollment {
+            user: ctx.accounts.user.key(),
+            enrolled_stake: stake_account.amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "User {} enrolled in bonus with stake: {}",
+            ctx.accounts.user.key(),
+            stake_account.amount
         );
+        Ok(())
+    }
 
-        let dust_amount = staking_pool.penalty_vault_amount;
-        let dev_wallet_key = DEV_WALLET.parse::<Pubkey>().map_err(|_| ErrorCode::InvalidDevWallet)?;
+    pub fn unenroll_from_bonus(ctx: Context<UnenrollFromBonus>) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        let stake_account = &mut ctx.accounts.stake_account;
+        stake_account.enter_round(staking_pool.round);
+
+        let current_time = Clock::get()?.unix_timestamp;
 
+        // Opting out is only possible while enrollment is still open
         require!(
-            ctx.accounts.dev_token_account.owner == dev_wallet_key,
-            ErrorCode::InvalidDevWallet
+            current_time <= staking_pool.bonus_enrollment_deadline,
+            ErrorCode::BonusEnrollmentClosed
         );
+        require!(stake_account.enrolled_in_bonus, ErrorCode::NotEnrolledInBonus);
 
-        // Setup PDA signer
-        let authority_seed = b"staking_pool";
-        let bump_bytes = [staking_pool.bump];
-        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
-        let signer = &[signer_seeds.as_ref()];
+        // Emissions accrued while enrolled are kept
+        sync_rewards(staking_pool, stake_account, current_time)?;
+        let reward_weight = stake_account.reward_weight();
 
-        // Transfer dust to dev wallet
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.pool_token_account.to_account_info(),
-            to: ctx.accounts.dev_token_account.to_account_info(),
-            authority: staking_pool.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, dust_amount)?;
+        // Before the deadline every stake joins the enrolled weight, so this is the whole principal
+        staking_pool.total_enrolled_stake = staking_pool
+            .total_enrolled_stake
+            .checked_sub(stake_account.enrolled_stake)
+            .ok_or(ErrorCode::NumericOverflow)?;
 
-        // Reset penalty vault
-        staking_pool.penalty_vault_amount = 0;
+        stake_account.enrolled_in_bonus = false;
+        stake_account.enrolled_stake = 0;
+        stake_account.bonus_unlock_time = 0;
+        staking_pool.update_reward_weight(reward_weight, 0)?;
+
+        // latest_bonus_unlock_time is left as-is: it is an upper bound and can only
+        // delay withdraw_bonus, never bring it forward
+
+        emit_cpi!(BonusUnenrollment {
+            user: ctx.accounts.user.key(),
+            unenrolled_stake: stake_account.amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "User {} unenrolled from bonus with stake: {}",
+            ctx.accounts.user.key(),
+            stake_account.amount
+        );
+        Ok(())
+    }
+
+    pub fn stake_tokens(mut ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
+        process_stake(&mut ctx, amount, None, false)
+    }
+
+    // stake_tokens + enroll_in_bonus in one transaction: the enrollment deadline can't pass
+    // between the two, and wallets prompt once
+    pub fn stake_and_enroll(mut ctx: Context<StakeTokens>, amount: u64) -> Result<()> {
+        process_stake(&mut ctx, amount, None, true)
+    }
+
+    pub fn stake_tokens_allowlisted(
+        mut ctx: Context<StakeTokens>,
+        amount: u64,
+        max_allocation: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require_allowlisted(&ctx, max_allocation, &proof)?;
+        process_stake(&mut ctx, amount, Some(max_allocation), false)
+    }
+
+    // stake_and_enroll for the allowlist phase, which overlaps the enrollment window
+    pub fn stake_allowlisted_and_enroll(
+        mut ctx: Context<StakeTokens>,
+        amount: u64,
+        max_allocation: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require_allowlisted(&ctx, max_allocation, &proof)?;
+        process_stake(&mut ctx, amount, Some(max_allocation), true)
+    }
+
+    // `max_penalty` is the most the user agreed to pay (see quote_unstake); the call fails
+    // instead of silently charging a higher tier
+    pub fn unstake_tokens(ctx: Context<UnstakeTokens>, amount: u64, max_penalty: u64) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        stake_account.enter_round(staking_pool.round);
+
+        // HAL-04 fix: Prevent zero amount unstaking
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(stake_account.amount >= amount, ErrorCode::InsufficientStakeBalance);
+
+        // Block unstaking of enrolled principal; a late stake unstakes on its own unlock
+        let (unstakeable, unlock_time) = stake_account.unstakeable();
+        require!(amount <= unstakeable, ErrorCode::BonusEnrolledCannotUnstake);
+        // Settled pools pay out through settle_position only
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // HAL-01 fix: Ensure unstaking only happens after stake window ends
+        require!(
+            current_time > staking_pool.stake_window_end,
+            ErrorCode::StakeWindowStillActive
+        );
+        let time_until_unlock = unlock_time - current_time;
+
+        // Early exit options (relative to current principal unlock date)
+        let tier = PenaltyTier::from_time_until_unlock(time_until_unlock);
+        require!(tier != PenaltyTier::Blocked, ErrorCode::UnstakeBlocked);
+        let penalty_amount = tier.penalty_for(amount);
+        let net_amount = amount - penalty_amount;
+
+        // Slippage guard: a threshold crossed since the quote must not raise the penalty
+        require!(penalty_amount <= max_penalty, ErrorCode::PenaltyExceedsMaximum);
+
+        let user_token_account = &ctx.accounts.user_token_account;
+        let pool_token_account = &ctx.accounts.pool_token_account;
+
+        // Setup PDA signer
+        let authority_seed = b"staking_pool";
+        let bump_bytes = [staking_pool.bump];
+        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+        let signer = &[signer_seeds.as_ref()];
+
+        // Transfer net amount to user
+        if net_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: pool_token_account.to_account_info(),
+                to: user_token_account.to_account_info(),
+                authority: staking_pool.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, net_amount)?;
+        }
+
+        // Add penalty to the penalty vault (stays in pool for enrolled users)
+        if penalty_amount > 0 {
+            staking_pool.add_penalty(penalty_amount)?;
+        }
+
+        // A late stake never joined the enrolled weight, so total_enrolled_stake is unchanged
+        if stake_account.enrolled_in_bonus {
+            stake_account.late_stake = stake_account
+                .late_stake
+                .checked_sub(amount)
+                .ok_or(ErrorCode::NumericOverflow)?;
+            if stake_account.late_stake == 0 {
+                stake_account.late_stake_unlock_time = 0;
+            }
+        }
+
+        // Update balances
+        stake_account.amount = stake_account
+            .amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.total_staked = staking_pool
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+
+        emit_cpi!(UnstakeEvent {
+            user: ctx.accounts.user.key(),
+            amount,
+            remaining_staked: stake_account.amount,
+            penalty_applied: penalty_amount,
+            net_to_user: net_amount,
+            penalty_vault_total: staking_pool.penalty_vault_amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "User {} unstaked {} tokens, penalty: {}, net received: {}",
+            ctx.accounts.user.key(),
+            amount,
+            penalty_amount,
+            net_amount
+        );
+
+        Ok(())
+    }
+
+    // Early exit for bonus enrollees: forfeits the bonus share and pays the enrolled exit penalty
+    pub fn exit_enrolled(ctx: Context<ExitEnrolled>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        stake_account.enter_round(staking_pool.round);
+
+        require!(stake_account.enrolled_in_bonus, ErrorCode::NotEnrolledInBonus);
+        require!(stake_account.amount > 0, ErrorCode::NoStakeToWithdraw);
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // HAL-01 fix: Same as unstake_tokens, no exits while the stake window is active
+        require!(
+            current_time > staking_pool.stake_window_end,
+            ErrorCode::StakeWindowStillActive
+        );
+        // Once fully unlocked there is nothing to exit early from
+        require!(
+            current_time < stake_account.bonus_unlock_time,
+            ErrorCode::AlreadyUnlocked
+        );
+
+        // A late stake is not enrolled principal: it stays staked as a plain position
+        let amount = stake_account.amount - stake_account.late_stake;
+        let penalty_amount = penalty_amount(amount, staking_pool.enrolled_exit_penalty_rate);
+        let net_amount = amount - penalty_amount;
+
+        // Setup PDA signer
+        let authority_seed = b"staking_pool";
+        let bump_bytes = [staking_pool.bump];
+        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+        let signer = &[signer_seeds.as_ref()];
+
+        // Transfer net amount to user
+        if net_amount > 0 {
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: staking_pool.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, net_amount)?;
+        }
+
+        // Penalty stays in the pool for the remaining enrollees
+        staking_pool.add_penalty(penalty_amount)?;
+
+        // The whole position leaves, but only its frozen enrolled weight was in the bonus pool.
+        // Emissions accrued until now are kept; only the bonus share is forfeited.
+        sync_rewards(staking_pool, stake_account, current_time)?;
+        staking_pool.update_reward_weight(stake_account.reward_weight(), 0)?;
+        staking_pool.total_enrolled_stake = staking_pool
+            .total_enrolled_stake
+            .checked_sub(stake_account.enrolled_stake)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.total_staked = staking_pool
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+
+        // Forfeit the bonus share; node keys are permanent
+        stake_account.amount = stake_account.late_stake;
+        stake_account.enrolled_in_bonus = false;
+        stake_account.enrolled_stake = 0;
+        stake_account.principal_unlock_time = stake_account.late_stake_unlock_time;
+        stake_account.bonus_unlock_time = 0;
+        stake_account.late_stake = 0;
+        stake_account.late_stake_unlock_time = 0;
+
+        emit_cpi!(EnrolledExitEvent {
+            user: ctx.accounts.user.key(),
+            amount,
+            penalty_applied: penalty_amount,
+            net_to_user: net_amount,
+            penalty_vault_total: staking_pool.penalty_vault_amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "User {} exited bonus early with {} tokens, penalty: {}, net received: {}",
+            ctx.accounts.user.key(),
+            amount,
+            penalty_amount,
+            net_amount
+        );
+
+        Ok(())
+    }
+
+    pub fn withdraw_all(ctx: Context<WithdrawAll>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        stake_account.enter_round(staking_pool.round);
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // HAL-02 fix: Only check principal unlock time for withdrawing principal
+        // Bonus rewards are withdrawn separately via withdraw_bonus
+        let principal_amount = stake_account.withdrawable_principal(current_time);
+
+        require!(
+            principal_amount > 0 || stake_account.amount == 0,
+            ErrorCode::StillLocked
+        );
+        require!(stake_account.amount > 0, ErrorCode::NoStakeToWithdraw);
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
+
+        let user_token_account = &ctx.accounts.user_token_account;
+        let pool_token_account = &ctx.accounts.pool_token_account;
+
+        // HAL-02 fix: No bonus rewards calculated here anymore
+        let bonus_rewards = 0u64;
+
+        let total_withdrawal = principal_amount;
+
+        // Setup PDA signer
+        let authority_seed = b"staking_pool";
+        let bump_bytes = [staking_pool.bump];
+        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+        let signer = &[signer_seeds.as_ref()];
+
+        // Transfer total amount to user
+        let cpi_accounts = Transfer {
+            from: pool_token_account.to_account_info(),
+            to: user_token_account.to_account_info(),
+            authority: staking_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, total_withdrawal)?;
+
+        // Update pool state
+        staking_pool.total_staked = staking_pool
+            .total_staked
+            .checked_sub(principal_amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+
+        // HAL-02 fix: Don't update total_enrolled_stake here - move to withdraw_bonus. The
+        // enrolled weight stops earning emissions once its principal has left, though.
+        sync_rewards(staking_pool, stake_account, current_time)?;
+        let reward_weight = stake_account.reward_weight();
+
+        emit_cpi!(WithdrawAllEvent {
+            user: ctx.accounts.user.key(),
+            principal_amount,
+            bonus_rewards,
+            total_withdrawn: total_withdrawal,
+            node_keys_retained: stake_account.node_keys_earned,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        // Reset stake account but keep node keys
+        let node_keys_to_keep = stake_account.node_keys_earned;
+        stake_account.amount -= principal_amount;
+        stake_account.node_keys_earned = node_keys_to_keep; // Node keys are permanent
+        // HAL-02 fix: Don't reset enrolled_in_bonus here - move to withdraw_bonus
+        if current_time >= stake_account.late_stake_unlock_time {
+            stake_account.late_stake = 0;
+            stake_account.late_stake_unlock_time = 0;
+        }
+        // A late stake still locked stays behind and is released by a later withdraw_all
+        if stake_account.amount == 0 {
+            stake_account.principal_unlock_time = 0;
+            stake_account.bonus_unlock_time = 0;
+        }
+        staking_pool.update_reward_weight(reward_weight, stake_account.reward_weight())?;
+
+        msg!(
+            "User {} withdrew {} principal, keeping {} node keys",
+            ctx.accounts.user.key(),
+            principal_amount,
+            node_keys_to_keep
+        );
+
+        Ok(())
+    }
+
+    // HAL-02 fix: Separate function to withdraw bonus rewards
+    pub fn withdraw_bonus(ctx: Context<WithdrawBonus>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        stake_account.enter_round(staking_pool.round);
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // Can only withdraw bonus 1 day after the latest unlock time of all users
+        let bonus_withdrawal_time = staking_pool.bonus_withdrawal_time();
+        require!(current_time >= bonus_withdrawal_time, ErrorCode::BonusWithdrawalNotYetAvailable);
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
+
+        // Entitlement depends only on the enrolled weight, so principal may still be staked
+        require!(stake_account.enrolled_in_bonus, ErrorCode::NotEnrolledInBonus);
+        require!(!stake_account.bonus_claimed, ErrorCode::BonusAlreadyClaimed);
+
+        // Under Redistribute, shares still unclaimed at the deadline belong to on-time claimants;
+        // under RollOver, to the next round
+        require!(
+            !staking_pool.late_claims_refused() || current_time < staking_pool.bonus_claim_deadline(),
+            ErrorCode::BonusClaimPeriodExpired
+        );
+        // Under Sweep, collect_dust already took the shares left unclaimed at the deadline
+        require!(!staking_pool.unclaimed_bonus_swept, ErrorCode::UnclaimedBonusSwept);
+
+        let user_token_account = &ctx.accounts.user_token_account;
+        let pool_token_account = &ctx.accounts.pool_token_account;
+
+        // Share of the snapshot, so claim order doesn't matter
+        staking_pool.take_bonus_snapshot();
+        let bonus_rewards = staking_pool
+            .bonus_share(stake_account.enrolled_stake)
+            .min(staking_pool.penalty_vault_amount);
+        staking_pool.penalty_vault_amount -= bonus_rewards;
+
+        if bonus_rewards > 0 {
+            // Setup PDA signer
+            let authority_seed = b"staking_pool";
+            let bump_bytes = [staking_pool.bump];
+            let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+            let signer = &[signer_seeds.as_ref()];
+
+            // Transfer bonus amount to user
+            let cpi_accounts = Transfer {
+                from: pool_token_account.to_account_info(),
+                to: user_token_account.to_account_info(),
+                authority: staking_pool.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, bonus_rewards)?;
+        }
+
+        emit_cpi!(BonusWithdrawEvent {
+            user: ctx.accounts.user.key(),
+            bonus_amount: bonus_rewards,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        // HAL-02 fix: The claimed weight leaves the bonus pool here, and stops earning emissions
+        sync_rewards(staking_pool, stake_account, current_time)?;
+        staking_pool.update_reward_weight(stake_account.reward_weight(), 0)?;
+        let enrolled_stake = stake_account.enrolled_stake;
+        staking_pool.total_enrolled_stake = staking_pool
+            .total_enrolled_stake
+            .saturating_sub(enrolled_stake);
+        staking_pool.claimed_enrolled_stake = staking_pool
+            .claimed_enrolled_stake
+            .checked_add(enrolled_stake)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.update_rounding_dust();
+
+        // enrolled_in_bonus keeps the principal on the bonus unlock; the flag stops a second claim
+        stake_account.bonus_claimed = true;
+
+        msg!(
+            "User {} withdrew {} bonus rewards, enrolled stake {}, total_enrolled_stake now {}",
+            ctx.accounts.user.key(),
+            bonus_rewards,
+            enrolled_stake,
+            staking_pool.total_enrolled_stake
+        );
+
+        Ok(())
+    }
+
+    // Redistribute policy: once the claim period ends, each on-time claimant takes a pro-rata
+    // share of what is left in the penalty vault, weighted by the same enrolled stake
+    pub fn withdraw_redistributed_bonus(ctx: Context<WithdrawRedistributedBonus>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        stake_account.enter_round(staking_pool.round);
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(
+            staking_pool.unclaimed_bonus_policy == UnclaimedBonusPolicy::Redistribute,
+            ErrorCode::RedistributionNotEnabled
+        );
+        require!(
+            current_time >= staking_pool.bonus_claim_deadline(),
+            ErrorCode::BonusClaimPeriodNotExpired
+        );
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
+
+        // withdraw_bonus refuses claims after the deadline, so every claimed position was on time
+        require!(stake_account.bonus_claimed, ErrorCode::NotEligibleForRedistribution);
+        require!(
+            !stake_account.redistribution_claimed,
+            ErrorCode::RedistributionAlreadyClaimed
+        );
+
+        // Forfeited shares are frozen on the first call and split like withdraw_bonus
+        staking_pool.take_redistribution_snapshot();
+        let enrolled_stake = stake_account.enrolled_stake;
+        let amount = pro_rata_share(
+            staking_pool.redistribution_snapshot_vault,
+            enrolled_stake,
+            staking_pool.redistribution_snapshot_weight,
+        )
+        .min(staking_pool.penalty_vault_amount);
+        staking_pool.penalty_vault_amount -= amount;
+        staking_pool.claimed_enrolled_stake = staking_pool
+            .claimed_enrolled_stake
+            .saturating_sub(enrolled_stake);
+        staking_pool.update_rounding_dust();
+        stake_account.redistribution_claimed = true;
+
+        if amount > 0 {
+            let authority_seed = b"staking_pool";
+            let bump_bytes = [staking_pool.bump];
+            let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+            let signer = &[signer_seeds.as_ref()];
+
+            let cpi_accounts = Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: staking_pool.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+            token::transfer(cpi_ctx, amount)?;
+        }
+
+        emit_cpi!(BonusRedistributed {
+            user: ctx.accounts.user.key(),
+            amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "User {} received {} redistributed bonus, {} left in the penalty vault",
+            ctx.accounts.user.key(),
+            amount,
+            staking_pool.penalty_vault_amount
+        );
+        Ok(())
+    }
+
+    // Pays a position's accrued emissions. The reward vault backs nothing else, so claims
+    // are open at any time, settlement included.
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        stake_account.enter_round(staking_pool.round);
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        sync_rewards(staking_pool, stake_account, current_time)?;
+        let amount = stake_account.rewards_accrued;
+        require!(amount > 0, ErrorCode::NoRewardsToClaim);
+
+        stake_account.rewards_accrued = 0;
+        staking_pool.reward_vault_amount = staking_pool
+            .reward_vault_amount
+            .checked_sub(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+
+        let authority_seed = b"staking_pool";
+        let bump_bytes = [staking_pool.bump];
+        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+        let signer = &[signer_seeds.as_ref()];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.reward_vault.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: staking_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, amount)?;
+
+        emit_cpi!(RewardsClaimed {
+            user: ctx.accounts.user.key(),
+            amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "User {} claimed {} rewards, {} left in the reward vault",
+            ctx.accounts.user.key(),
+            amount,
+            staking_pool.reward_vault_amount
+        );
+        Ok(())
+    }
+
+    // Permissionless view: any wallet's position can be queried without its signature
+    pub fn get_stake_info(ctx: Context<GetStakeInfo>) -> Result<StakeInfo> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let stake_info = build_stake_info(
+            &ctx.accounts.stake_account,
+            &ctx.accounts.staking_pool,
+            current_time,
+        )?;
+
+        msg!(
+            "Stake Info - Amount: {}, Keys: {}, Enrolled: {}, Unlock Time: {}, Potential Bonus: {}",
+            stake_info.amount,
+            stake_info.node_keys_earned,
+            stake_info.enrolled_in_bonus,
+            stake_info.unlock_time,
+            stake_info.potential_bonus
+        );
+
+        Ok(stake_info)
+    }
+
+    // Batch view: StakeAccounts are passed through remaining_accounts, results keep their order
+    pub fn get_stake_infos<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetStakeInfos<'info>>,
+    ) -> Result<Vec<StakeInfo>> {
+        // Return data is capped at 1024 bytes
+        require!(
+            ctx.remaining_accounts.len() <= MAX_STAKE_INFO_BATCH,
+            ErrorCode::TooManyStakeAccounts
+        );
+
+        let staking_pool = &ctx.accounts.staking_pool;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let mut stake_infos = Vec::with_capacity(ctx.remaining_accounts.len());
+        for account_info in ctx.remaining_accounts.iter() {
+            // Checks owner and discriminator
+            let stake_account: Account<StakeAccount> = Account::try_from(account_info)?;
+
+            // Must be the canonical PDA for its user
+            let expected = Pubkey::create_program_address(
+                &[b"stake_account", stake_account.user.as_ref(), &[stake_account.bump]],
+                ctx.program_id,
+            )
+            .map_err(|_| ErrorCode::InvalidStakeAccount)?;
+            require!(account_info.key() == expected, ErrorCode::InvalidStakeAccount);
+
+            stake_infos.push(build_stake_info(&stake_account, staking_pool, current_time)?);
+        }
+
+        msg!("Stake Infos - Returned {} positions", stake_infos.len());
+
+        Ok(stake_infos)
+    }
+
+    // Preview of unstake_tokens for the given wallet and amount, including when the tier changes
+    pub fn quote_unstake(ctx: Context<QuoteUnstake>, amount: u64) -> Result<UnstakeQuote> {
+        let stake_account = &ctx.accounts.stake_account;
+        let staking_pool = &ctx.accounts.staking_pool;
+
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(stake_account.amount >= amount, ErrorCode::InsufficientStakeBalance);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let (unstakeable, unlock_time) = stake_account.unstakeable();
+        let time_until_unlock = unlock_time - current_time;
+        let natural_tier = PenaltyTier::from_time_until_unlock(time_until_unlock);
+
+        // HAL-01: everything is blocked until the window closes
+        let window_active = current_time <= staking_pool.stake_window_end;
+        let tier = if window_active { PenaltyTier::Blocked } else { natural_tier };
+
+        let time_until_next_tier = if window_active {
+            let first_unstake_time = staking_pool.stake_window_end + 1;
+            let tier_at_window_close = PenaltyTier::from_time_until_unlock(unlock_time - first_unstake_time);
+            if tier_at_window_close == PenaltyTier::Blocked {
+                natural_tier.seconds_until_next(time_until_unlock)
+            } else {
+                first_unstake_time - current_time
+            }
+        } else {
+            natural_tier.seconds_until_next(time_until_unlock)
+        };
+
+        let penalty = tier.penalty_for(amount);
+        let unstake_allowed = tier != PenaltyTier::Blocked
+            && amount <= unstakeable
+            && !staking_pool.settlement_active;
+
+        let quote = UnstakeQuote {
+            amount,
+            penalty,
+            net_amount: amount - penalty,
+            tier,
+            penalty_rate: tier.rate(),
+            time_until_next_tier,
+            unstake_allowed,
+        };
+
+        msg!(
+            "Unstake Quote - Amount: {}, Penalty: {}, Tier: {:?}, Next tier in: {}s",
+            quote.amount,
+            quote.penalty,
+            quote.tier,
+            quote.time_until_next_tier
+        );
+
+        Ok(quote)
+    }
+
+    pub fn get_pool_info(ctx: Context<GetPoolInfo>) -> Result<PoolInfo> {
+        let staking_pool = &ctx.accounts.staking_pool;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let window_phase = staking_pool.window_phase(current_time);
+
+        // Worst case a staker can face: a position staked at window close. HAL-01 keeps
+        // every position blocked while the window is open.
+        let current_penalty_tier = match window_phase {
+            StakeWindowPhase::Closed => {
+                let latest_principal_unlock = staking_pool.stake_window_end + PRINCIPAL_LOCK_PERIOD;
+                PenaltyTier::from_time_until_unlock(latest_principal_unlock - current_time)
+            }
+            _ => PenaltyTier::Blocked,
+        };
+
+        let bonus_withdrawal_time = staking_pool.bonus_withdrawal_time();
+        let dust_claim_deadline = staking_pool.bonus_claim_deadline();
+
+        let pool_info = PoolInfo {
+            authority: staking_pool.authority,
+            fee_recipient: staking_pool.fee_recipient,
+            qst_mint: staking_pool.qst_mint,
+            total_staked: staking_pool.total_staked,
+            total_enrolled_stake: staking_pool.total_enrolled_stake,
+            penalty_vault_amount: staking_pool.penalty_vault_amount,
+            max_total_staked: staking_pool.max_total_staked,
+            max_user_stake: staking_pool.max_user_stake,
+            window_phase,
+            first_stake_timestamp: staking_pool.first_stake_timestamp,
+            stake_window_end: staking_pool.stake_window_end,
+            bonus_enrollment_deadline: staking_pool.bonus_enrollment_deadline,
+            bonus_enrollment_open: staking_pool.window_opened(current_time)
+                && current_time <= staking_pool.bonus_enrollment_deadline,
+            allowlist_gated: staking_pool.is_allowlist_gated(current_time),
+            allowlist_public_time: staking_pool.allowlist_public_time,
+            latest_bonus_unlock_time: staking_pool.latest_bonus_unlock_time,
+            bonus_withdrawal_time,
+            bonus_withdrawal_available: window_phase == StakeWindowPhase::Closed
+                && current_time >= bonus_withdrawal_time,
+            dust_claim_deadline,
+            dust_collectable: window_phase == StakeWindowPhase::Closed
+                && current_time >= dust_claim_deadline
+                && staking_pool.penalty_vault_amount > 0
+                && !staking_pool.settlement_active
+                && (staking_pool.unclaimed_bonus_sweepable() || staking_pool.rounding_dust > 0),
+            current_penalty_tier,
+            current_penalty_rate: current_penalty_tier.rate(),
+            settlement_active: staking_pool.settlement_active,
+            settlement_available_time: staking_pool.settlement_available_time(),
+            event_seq: staking_pool.event_seq,
+            current_time,
+            unclaimed_bonus_policy: staking_pool.unclaimed_bonus_policy,
+            bonus_claim_period: staking_pool.bonus_claim_period,
+            rounding_dust: staking_pool.rounding_dust,
+            reward_vault_amount: staking_pool.reward_vault_amount,
+            reward_stream_amount: staking_pool.reward_stream_amount,
+            reward_start: staking_pool.reward_start,
+            reward_end: staking_pool.reward_end,
+            rewards_released: staking_pool.rewards_released(current_time),
+            round: staking_pool.round,
+        };
+
+        msg!(
+            "Pool Info - Total staked: {}, Enrolled: {}, Penalty vault: {}, Phase: {:?}",
+            pool_info.total_staked,
+            pool_info.total_enrolled_stake,
+            pool_info.penalty_vault_amount,
+            pool_info.window_phase
+        );
+
+        Ok(pool_info)
+    }
+
+    // Permissionless: switch an abandoned pool into settlement once the admin has been silent
+    // for the inactivity period after all unlock times
+    pub fn trigger_settlement(ctx: Context<TriggerSettlement>) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
+
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
+        require!(
+            staking_pool.first_stake_timestamp > 0,
+            ErrorCode::StakeWindowNotStarted
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= staking_pool.settlement_available_time(),
+            ErrorCode::SettlementNotAvailable
+        );
+
+        // Freeze the bonus still claimable at the deadline; settle_position pays it to its
+        // owners, and only the rest of the vault is split by principal
+        staking_pool.take_bonus_snapshot();
+        if staking_pool.unclaimed_bonus_policy == UnclaimedBonusPolicy::Redistribute
+            && staking_pool.claimed_enrolled_stake > 0
+        {
+            staking_pool.take_redistribution_snapshot();
+        }
+        staking_pool.update_rounding_dust();
+        let bonus_reserve = staking_pool.settlement_bonus_reserve();
+
+        // Snapshot so every position's share is independent of settlement order
+        staking_pool.settlement_active = true;
+        staking_pool.settlement_penalty_vault = staking_pool
+            .penalty_vault_amount
+            .checked_sub(bonus_reserve)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.settlement_total_stake = staking_pool.total_staked;
+
+        emit_cpi!(SettlementTriggered {
+            triggered_by: ctx.accounts.caller.key(),
+            penalty_vault: staking_pool.settlement_penalty_vault,
+            total_stake: staking_pool.settlement_total_stake,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "Settlement triggered. Penalty vault: {}, total stake: {}",
+            staking_pool.settlement_penalty_vault,
+            staking_pool.settlement_total_stake
+        );
+        Ok(())
+    }
+
+    // Permissionless crank: pays one position its principal, the bonus it could still claim,
+    // and a pro-rata share of the rest of the penalty vault snapshot. Funds always go to the
+    // position owner's token account.
+    pub fn settle_position(ctx: Context<SettlePosition>) -> Result<()> {
+        let stake_account = &mut ctx.accounts.stake_account;
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        stake_account.enter_round(staking_pool.round);
+
+        require!(staking_pool.settlement_active, ErrorCode::SettlementNotActive);
+        let bonus_amount = staking_pool.settlement_bonus(stake_account);
+        require!(
+            stake_account.amount > 0 || bonus_amount > 0,
+            ErrorCode::NothingToSettle
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let principal_amount = stake_account.amount;
+        let penalty_share = pro_rata_share(
+            staking_pool.settlement_penalty_vault,
+            principal_amount,
+            staking_pool.settlement_total_stake,
+        );
+        let penalty_payout = penalty_share
+            .checked_add(bonus_amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        let total_payout = principal_amount
+            .checked_add(penalty_payout)
+            .ok_or(ErrorCode::NumericOverflow)?;
+
+        // Setup PDA signer
+        let authority_seed = b"staking_pool";
+        let bump_bytes = [staking_pool.bump];
+        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+        let signer = &[signer_seeds.as_ref()];
+
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.pool_token_account.to_account_info(),
+            to: ctx.accounts.user_token_account.to_account_info(),
+            authority: staking_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, total_payout)?;
+
+        // Update pool state
+        staking_pool.total_staked = staking_pool
+            .total_staked
+            .checked_sub(principal_amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        staking_pool.penalty_vault_amount = staking_pool
+            .penalty_vault_amount
+            .checked_sub(penalty_payout)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        // Emissions stay claimable through claim_rewards
+        sync_rewards(staking_pool, stake_account, current_time)?;
+        staking_pool.update_reward_weight(stake_account.reward_weight(), 0)?;
+        if stake_account.in_bonus_pool() {
+            staking_pool.total_enrolled_stake = staking_pool
+                .total_enrolled_stake
+                .saturating_sub(stake_account.enrolled_stake);
+        }
+        // The bonus is paid here, so neither claim can run again for this position
+        if bonus_amount > 0 {
+            if stake_account.bonus_claimed {
+                staking_pool.claimed_enrolled_stake = staking_pool
+                    .claimed_enrolled_stake
+                    .saturating_sub(stake_account.enrolled_stake);
+                stake_account.redistribution_claimed = true;
+            }
+            stake_account.bonus_claimed = true;
+        }
+
+        // Close out the position; node keys are permanent
+        stake_account.amount = 0;
+        stake_account.enrolled_in_bonus = false;
+        stake_account.enrolled_stake = 0;
+        stake_account.principal_unlock_time = 0;
+        stake_account.bonus_unlock_time = 0;
+        stake_account.late_stake = 0;
+        stake_account.late_stake_unlock_time = 0;
+
+        emit_cpi!(PositionSettled {
+            user: stake_account.user,
+            principal_amount,
+            penalty_share,
+            bonus_amount,
+            settled_by: ctx.accounts.cranker.key(),
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "Settled position of {}: principal {}, penalty share {}, bonus {}",
+            stake_account.user,
+            principal_amount,
+            penalty_share,
+            bonus_amount
+        );
+        Ok(())
+    }
+
+    // ADDED: Collect dust from penalty vault to the fee recipient
+    pub fn collect_dust(ctx: Context<CollectDust>) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
+
+        require!(
+            ctx.accounts.admin.key() == staking_pool.authority,
+            ErrorCode::Unauthorized
+        );
+
+        // Settlement snapshot already assigned the penalty vault to positions
+        require!(!staking_pool.settlement_active, ErrorCode::SettlementActive);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        staking_pool.last_admin_action = current_time;
+
+        // A vault rolled over by start_next_round belongs to the round that has yet to run
+        require!(
+            staking_pool.window_opened(current_time),
+            ErrorCode::StakeWindowNotStarted
+        );
+
+        // Give users the claim period to claim their bonus rewards after bonus withdrawals become available
+        let bonus_claim_deadline = staking_pool.bonus_claim_deadline();
+        require!(
+            current_time >= bonus_claim_deadline,
+            ErrorCode::BonusClaimPeriodNotExpired
+        );
+
+        // Only collect when penalty vault has tokens
+        require!(
+            staking_pool.penalty_vault_amount > 0,
+            ErrorCode::NoDustToCollect
+        );
+
+        // Whatever no position can be paid is collected under every policy: rounding dust,
+        // penalties paid after the bonus snapshot, and a snapshot vault with no enrolled weight.
+        // The bonus still owed to unclaimed positions is only forfeited when the policy sweeps it.
+        staking_pool.take_bonus_snapshot();
+        staking_pool.update_rounding_dust();
+        let sweep_unclaimed = staking_pool.unclaimed_bonus_sweepable();
+        let rounding_dust = staking_pool.rounding_dust;
+        let dust_amount = if sweep_unclaimed {
+            staking_pool.penalty_vault_amount
+        } else {
+            staking_pool.penalty_vault_amount.saturating_sub(staking_pool.owed_bonus())
+        };
+        require!(dust_amount > 0, ErrorCode::UnclaimedBonusNotSweepable);
+
+        require!(
+            ctx.accounts.fee_recipient_token_account.owner == staking_pool.fee_recipient,
+            ErrorCode::InvalidFeeRecipient
+        );
+
+        // Setup PDA signer
+        let authority_seed = b"staking_pool";
+        let bump_bytes = [staking_pool.bump];
+        let signer_seeds = &[authority_seed.as_ref(), bump_bytes.as_ref()];
+        let signer = &[signer_seeds.as_ref()];
+
+        // Transfer dust to the fee recipient
+        let cpi_accounts = Transfer {
+            from: ctx.accounts.pool_token_account.to_account_info(),
+            to: ctx.accounts.fee_recipient_token_account.to_account_info(),
+            authority: staking_pool.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+        token::transfer(cpi_ctx, dust_amount)?;
+
+        // Reset penalty vault
+        staking_pool.penalty_vault_amount -= dust_amount;
+        staking_pool.rounding_dust = 0;
+        staking_pool.post_snapshot_penalties = 0;
+        if sweep_unclaimed {
+            staking_pool.unclaimed_bonus_swept = true;
+        }
+
+        emit_cpi!(DustCollected {
+            recipient: ctx.accounts.fee_recipient_token_account.key(),
+            amount: dust_amount,
+            rounding_dust,
+            unclaimed_bonus: dust_amount - rounding_dust,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "Collected {} tokens to fee recipient after the claim period: {} unclaimed bonus, {} rounding dust",
+            dust_amount,
+            dust_amount - rounding_dust,
+            rounding_dust
+        );
+        Ok(())
+    }
+
+    // Permissionless solvency check over the vault and the StakeAccounts passed through
+    // remaining_accounts. Fails with the violated invariant, or emits an AuditEvent.
+    pub fn audit_pool<'info>(ctx: Context<'_, '_, 'info, 'info, AuditPool<'info>>) -> Result<()> {
+        let staking_pool = &mut ctx.accounts.staking_pool;
+        let vault_balance = ctx.accounts.pool_token_account.amount;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // Every principal and every unclaimed penalty must be backed by vault tokens
+        let liabilities = staking_pool
+            .total_staked
+            .checked_add(staking_pool.penalty_vault_amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        require!(vault_balance >= liabilities, ErrorCode::VaultBalanceShortfall);
+
+        // Enrolled weight is principal still in the pool until an enrollee can withdraw it
+        if staking_pool.enrolled_principal_locked(current_time) {
+            require!(
+                staking_pool.total_enrolled_stake <= staking_pool.total_staked,
+                ErrorCode::EnrolledStakeExceedsTotalStaked
+            );
+        }
+
+        let mut audited_keys: Vec<Pubkey> = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut audited_stake = 0u64;
+        let mut audited_enrolled_stake = 0u64;
+        for account_info in ctx.remaining_accounts.iter() {
+            // Checks owner and discriminator
+            let stake_account = Account::<StakeAccount>::try_from(account_info)?.in_round(staking_pool.round);
+
+            // Must be the canonical PDA for its user, and counted once
+            let expected = Pubkey::create_program_address(
+                &[b"stake_account", stake_account.user.as_ref(), &[stake_account.bump]],
+                ctx.program_id,
+            )
+            .map_err(|_| ErrorCode::InvalidStakeAccount)?;
+            require!(account_info.key() == expected, ErrorCode::InvalidStakeAccount);
+            require!(!audited_keys.contains(&expected), ErrorCode::DuplicateStakeAccount);
+            audited_keys.push(expected);
+
+            audited_stake = audited_stake
+                .checked_add(stake_account.amount)
+                .ok_or(ErrorCode::NumericOverflow)?;
+            if stake_account.enrolled_in_bonus {
+                require!(
+                    stake_account.bonus_unlock_time <= staking_pool.latest_bonus_unlock_time,
+                    ErrorCode::BonusUnlockAfterLatest
+                );
+            }
+            if stake_account.in_bonus_pool() {
+                audited_enrolled_stake = audited_enrolled_stake
+                    .checked_add(stake_account.enrolled_stake)
+                    .ok_or(ErrorCode::NumericOverflow)?;
+            }
+        }
+
+        // A subset of positions can never add up to more than the pool totals
+        require!(
+            audited_stake <= staking_pool.total_staked
+                && audited_enrolled_stake <= staking_pool.total_enrolled_stake,
+            ErrorCode::AuditedStakeExceedsTotals
+        );
+
+        emit_cpi!(AuditEvent {
+            auditor: ctx.accounts.auditor.key(),
+            vault_balance,
+            total_staked: staking_pool.total_staked,
+            penalty_vault_amount: staking_pool.penalty_vault_amount,
+            total_enrolled_stake: staking_pool.total_enrolled_stake,
+            accounts_audited: audited_keys.len() as u32,
+            audited_stake,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "Pool audit passed: vault {}, liabilities {}, {} positions audited",
+            vault_balance,
+            liabilities,
+            audited_keys.len()
+        );
+        Ok(())
+    }
+
+}
+
+// Leaf commits to both the wallet and its allocation
+fn require_allowlisted(ctx: &Context<StakeTokens>, max_allocation: u64, proof: &[[u8; 32]]) -> Result<()> {
+    let leaf = allowlist_leaf(&ctx.accounts.user.key(), max_allocation);
+    require!(
+        verify_allowlist_proof(proof, ctx.accounts.staking_pool.allowlist_root, leaf),
+        ErrorCode::InvalidAllowlistProof
+    );
+    Ok(())
+}
+
+// Shared by the stake instructions. `allowlist_allocation` is the proven per-wallet
+// allocation, or None when the caller supplied no proof; `enroll` also enrolls the
+// position in the bonus program before the stake is reported.
+fn process_stake(
+    ctx: &mut Context<StakeTokens>,
+    amount: u64,
+    allowlist_allocation: Option<u64>,
+    enroll: bool,
+) -> Result<()> {
+    msg!("=== STARTING STAKE_TOKENS ===");
+    msg!("Amount to stake: {}", amount);
+
+    let staking_pool = &mut ctx.accounts.staking_pool;
+    let stake_account = &mut ctx.accounts.stake_account;
+    stake_account.enter_round(staking_pool.round);
+    let user_token_account = &ctx.accounts.user_token_account;
+    let pool_token_account = &ctx.accounts.pool_token_account;
+
+    // Validate amount and calculate number of keys based on stake amount
+    let node_keys_earned = STAKE_RULES.node_keys_for(amount).map_err(ErrorCode::from)?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    msg!("Current time: {}", current_time);
+    msg!("Stake window end: {}", staking_pool.stake_window_end);
+    msg!("First stake timestamp: {}", staking_pool.first_stake_timestamp);
+
+    // Enforce stake window - no auto-restart
+    require!(
+        staking_pool.first_stake_timestamp > 0,
+        ErrorCode::StakeWindowNotStarted
+    );
+    require!(
+        current_time >= staking_pool.first_stake_timestamp,
+        ErrorCode::StakeWindowNotOpenYet
+    );
+    require!(
+        current_time <= staking_pool.stake_window_end,
+        ErrorCode::StakeWindowClosed
+    );
+
+    // The deadline is earlier than the window end
+    if enroll {
+        require!(
+            current_time <= staking_pool.bonus_enrollment_deadline,
+            ErrorCode::BonusEnrollmentClosed
+        );
+    }
+
+    // Enforce pool-wide and per-wallet caps
+    let new_total_staked = staking_pool
+        .total_staked
+        .checked_add(amount)
+        .ok_or(ErrorCode::NumericOverflow)?;
+    require!(
+        new_total_staked <= staking_pool.max_total_staked,
+        ErrorCode::PoolStakeCapExceeded
+    );
+    let new_user_stake = stake_account
+        .amount
+        .checked_add(amount)
+        .ok_or(ErrorCode::NumericOverflow)?;
+    require!(
+        new_user_stake <= staking_pool.max_user_stake,
+        ErrorCode::UserStakeCapExceeded
+    );
+
+    // Allowlist phase: only proven wallets may stake, up to their allocation
+    if staking_pool.is_allowlist_gated(current_time) {
+        let max_allocation = allowlist_allocation.ok_or(ErrorCode::AllowlistProofRequired)?;
+        require!(
+            new_user_stake <= max_allocation,
+            ErrorCode::AllowlistAllocationExceeded
+        );
+    }
+
+    msg!("Node keys to earn: {}", node_keys_earned);
+
+    // An enrolled top-up adds weight, so accrue emissions at the old weight first
+    sync_rewards(staking_pool, stake_account, current_time)?;
+    let reward_weight = stake_account.reward_weight();
+
+    // Transfer tokens from user to pool
+    let cpi_accounts = Transfer {
+        from: user_token_account.to_account_info(),
+        to: pool_token_account.to_account_info(),
+        authority: ctx.accounts.user.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    token::transfer(cpi_ctx, amount)?;
+
+    // Initialize or update stake account
+    if stake_account.amount == 0 {
+        stake_account.user = ctx.accounts.user.key();
+        stake_account.amount = amount;
+        stake_account.node_keys_earned = node_keys_earned;
+
+        // Reset lock model - unlock time based on current stake time
+        stake_account.principal_unlock_time = current_time + PRINCIPAL_LOCK_PERIOD;
+
+        // Not enrolled by default; bonus unlock remains 0 until enroll
+        stake_account.bonus_unlock_time = 0;
+        stake_account.enrolled_in_bonus = false;
+        stake_account.enrolled_stake = 0;
+        stake_account.bonus_claimed = false;
+        stake_account.redistribution_claimed = false;
+        stake_account.late_stake = 0;
+        stake_account.late_stake_unlock_time = 0;
+        stake_account.bump = ctx.bumps.stake_account;
+    } else {
+        stake_account.amount = stake_account
+            .amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        stake_account.node_keys_earned = stake_account
+            .node_keys_earned
+            .checked_add(node_keys_earned)
+            .ok_or(ErrorCode::NumericOverflow)?;
+
+        if stake_account.enrolled_in_bonus && current_time > staking_pool.bonus_enrollment_deadline {
+            // Enrolled weight and the bonus claim date are frozen at the deadline: the new stake
+            // is principal-only, tracked apart so the enrolled principal keeps its unlock
+            stake_account.late_stake = stake_account
+                .late_stake
+                .checked_add(amount)
+                .ok_or(ErrorCode::NumericOverflow)?;
+            stake_account.late_stake_unlock_time = current_time + PRINCIPAL_LOCK_PERIOD;
+        } else {
+            // Reset lock model - set unlock to current_time + 25 days
+            stake_account.principal_unlock_time = current_time + PRINCIPAL_LOCK_PERIOD;
+        }
+
+        // If enrolled before the deadline, bonus unlock follows principal (principal + 10d)
+        if stake_account.enrolled_in_bonus && current_time <= staking_pool.bonus_enrollment_deadline {
+            stake_account.bonus_unlock_time = stake_account.principal_unlock_time + BONUS_LOCK_PERIOD;
+            stake_account.enrolled_stake = stake_account
+                .enrolled_stake
+                .checked_add(amount)
+                .ok_or(ErrorCode::NumericOverflow)?;
+            staking_pool.total_enrolled_stake = staking_pool
+                .total_enrolled_stake
+                .checked_add(amount)
+                .ok_or(ErrorCode::NumericOverflow)?;
+
+            // HAL-02 fix: Track latest unlock time for bonus withdrawal delay
+            if stake_account.bonus_unlock_time > staking_pool.latest_bonus_unlock_time {
+                staking_pool.latest_bonus_unlock_time = stake_account.bonus_unlock_time;
+            }
+        }
+    }
+
+    // Update pool totals
+    staking_pool.total_staked = staking_pool
+        .total_staked
+        .checked_add(amount)
+        .ok_or(ErrorCode::NumericOverflow)?;
+    staking_pool.update_reward_weight(reward_weight, stake_account.reward_weight())?;
+
+    // Enroll before emitting so the StakeEvent reports the final position
+    if enroll {
+        enroll_position(staking_pool, stake_account, current_time)?;
+    }
+
+    msg!("Final stake amount: {}", stake_account.amount);
+    msg!("Total pool staked: {}", staking_pool.total_staked);
+
+    // Emit event
+    emit_cpi!(StakeEvent {
+        user: ctx.accounts.user.key(),
+        amount,
+        total_staked: staking_pool.total_staked,
+        node_keys_earned,
+        // Only a stake made after the enrollment deadline adds to late_stake
+        principal_unlock_time: if stake_account.late_stake > 0 {
+            stake_account.late_stake_unlock_time
+        } else {
+            stake_account.principal_unlock_time
+        },
+        bonus_unlock_time: stake_account.bonus_unlock_time,
+        enrolled_in_bonus: stake_account.enrolled_in_bonus,
+        timestamp: current_time,
+        seq: staking_pool.next_event_seq()?,
+    });
+
+    if enroll {
+        emit_cpi!(BonusEnrollment {
+            user: ctx.accounts.user.key(),
+            enrolled_stake: stake_account.amount,
+            timestamp: current_time,
+            seq: staking_pool.next_event_seq()?,
+        });
+
+        msg!(
+            "User {} staked {} and enrolled in bonus with stake: {}",
+            ctx.accounts.user.key(),
+            amount,
+            stake_account.amount
+        );
+    }
+
+    Ok(())
+}
+
+// Shared by enroll_in_bonus and process_stake; callers check the enrollment window
+fn enroll_position(
+    staking_pool: &mut StakingPool,
+    stake_account: &mut StakeAccount,
+    current_time: i64,
+) -> Result<()> {
+    // User must have existing stake to enroll
+    require!(stake_account.amount > 0, ErrorCode::NoStakeToEnroll);
+    require!(!stake_account.enrolled_in_bonus, ErrorCode::AlreadyEnrolledInBonus);
+
+    // Emissions start accruing from here
+    sync_rewards(staking_pool, stake_account, current_time)?;
+
+    // Enroll
+    stake_account.enrolled_in_bonus = true;
+
+    // On enroll, the user's current principal becomes their enrolled weight
+    stake_account.enrolled_stake = stake_account.amount;
+    staking_pool.total_enrolled_stake = staking_pool
+        .total_enrolled_stake
+        .checked_add(stake_account.amount)
+        .ok_or(ErrorCode::NumericOverflow)?;
+    staking_pool.update_reward_weight(0, stake_account.reward_weight())?;
+
+    // Set bonus unlock to principal + 10d
+    stake_account.bonus_unlock_time = stake_account.principal_unlock_time + BONUS_LOCK_PERIOD;
+
+    // HAL-02 fix: Track latest unlock time for bonus withdrawal delay
+    if stake_account.bonus_unlock_time > staking_pool.latest_bonus_unlock_time {
+        staking_pool.latest_bonus_unlock_time = stake_account.bonus_unlock_time;
+    }
+    Ok(())
+}
+
+// Brings a position's emissions up to date. Must run before its reward weight or
+// total_reward_weight changes.
+fn sync_rewards(
+    staking_pool: &mut StakingPool,
+    stake_account: &mut StakeAccount,
+    current_time: i64,
+) -> Result<()> {
+    staking_pool.accrue_rewards(current_time)?;
+    stake_account.rewards_accrued = stake_account.accrued_rewards(staking_pool.reward_per_weight)?;
+    stake_account.reward_per_weight_paid = staking_pool.reward_per_weight;
+    Ok(())
+}
+
+// Shared by get_stake_info and get_stake_infos
+fn build_stake_info(
+    stake_account: &StakeAccount,
+    staking_pool: &StakingPool,
+    current_time: i64,
+) -> Result<StakeInfo> {
+    let stake_account = &stake_account.in_round(staking_pool.round);
+    // Preview on the current vault until the snapshot, the exact share afterwards
+    let potential_bonus = if stake_account.in_bonus_pool() {
+        staking_pool.bonus_share(stake_account.enrolled_stake)
+    } else {
+        0
+    };
+
+    // Determine unlock time based on enrollment status
+    let unlock_time = stake_account.unlock_time();
+
+    Ok(StakeInfo {
+        amount: stake_account.amount,
+        node_keys_earned: stake_account.node_keys_earned,
+        principal_unlock_time: stake_account.principal_unlock_time,
+        bonus_unlock_time: stake_account.bonus_unlock_time,
+        enrolled_in_bonus: stake_account.enrolled_in_bonus,
+        potential_bonus,
+        unlock_time,
+        is_unlocked: current_time >= unlock_time,
+        time_until_unlock: if current_time >= unlock_time { 0 } else { unlock_time - current_time },
+        user: stake_account.user,
+        bonus_claimed: stake_account.bonus_claimed,
+        accrued_rewards: stake_account.accrued_rewards(staking_pool.reward_per_weight_at(current_time))?,
+    })
+}
+
+// Merkle leaf for an allowlisted wallet: keccak(user || max_allocation_le)
+pub fn allowlist_leaf(user: &Pubkey, max_allocation: u64) -> [u8; 32] {
+    keccak::hashv(&[user.as_ref(), &max_allocation.to_le_bytes()]).to_bytes()
+}
+
+// Sorted-pair merkle verification (siblings are hashed in ascending byte order)
+pub fn verify_allowlist_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let mut computed = leaf;
+    for node in proof {
+        computed = if computed <= *node {
+            keccak::hashv(&[&computed, node]).to_bytes()
+        } else {
+            keccak::hashv(&[node, &computed]).to_bytes()
+        };
+    }
+    computed == root
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = payer,
+        // discriminator + authority + total_staked + total_enrolled_stake + penalty_vault_amount
+        // + first_stake + bonus_deadline + stake_window_end + latest_bonus_unlock_time + qst_mint + bump
+        // + max_total_staked + max_user_stake + allowlist_root + allowlist_public_time
+        // + enrolled_exit_penalty_rate + last_admin_action + admin_inactivity_period
+        // + settlement_active + settlement_penalty_vault + settlement_total_stake + event_seq
+        // + fee_recipient + bonus_claim_period + unclaimed_bonus_policy + claimed_enrolled_stake
+        // + bonus snapshot (taken, vault, weight) + redistribution snapshot (vault, weight) + rounding_dust
+        // + reward_vault_amount + reward_stream_amount + reward_start + reward_end + reward_released
+        // + reward_per_weight(u128) + reward_unallocated + unclaimed_bonus_swept + post_snapshot_penalties
+        // + total_reward_weight + round(u32)
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 32 + 1 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 32 + 8 + 1 + 8 + 1 + 8 + 8 + 8 + 8 + 8
+            + 8 + 8 + 8 + 8 + 8 + 16 + 8 + 1 + 8 + 8 + 4,
+        seeds = [b"staking_pool"],
+        bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account()]
+    pub qst_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // The payer must be this program's upgrade authority, so a redeploy needs no source edits
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StartStakeWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: Only admin can start the window (but only once)
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StartNextRound<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ScheduleStakeWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: (re)schedule until the window opens
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelStakeWindowSchedule<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExtendStakeWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: while the window is open
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseStakeWindowEarly<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureStakeCaps<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: caps can only change before the window opens
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureAllowlist<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: allowlist can only change before the window opens
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureEnrolledExitPenalty<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: exit penalty can only change before the window opens
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureAdminInactivityPeriod<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: inactivity period can only change before the window opens
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureUnclaimedBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Admin only: claim terms can only change before the window opens
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Emissions only; created by the first funding
+    #[account(
+        init_if_needed,
+        payer = admin,
+        seeds = [b"reward_vault"],
+        bump,
+        token::mint = qst_mint,
+        token::authority = staking_pool
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    #[account(address = staking_pool.qst_mint)]
+    pub qst_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = admin_token_account.owner == admin.key(),
+        constraint = admin_token_account.mint == staking_pool.qst_mint
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReclaimUnallocatedRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_vault"],
+        bump
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = admin_token_account.owner == admin.key(),
+        constraint = admin_token_account.mint == staking_pool.qst_mint
+    )]
+    pub admin_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AdminHeartbeat<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFeeRecipient<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account()]
+    pub admin: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TriggerSettlement<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    // Permissionless: anyone can trigger once the admin is inactive
+    #[account()]
+    pub caller: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettlePosition<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_account", stake_account.user.as_ref()],
+        bump = stake_account.bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    // Payout always goes to the position owner, whoever cranks
+    #[account(
+        mut,
+        constraint = user_token_account.owner == stake_account.user,
+        constraint = user_token_account.mint == staking_pool.qst_mint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.owner == staking_pool.key(),
+        constraint = pool_token_account.mint == staking_pool.qst_mint
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    #[account()]
+    pub cranker: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectDust<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account()]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.owner == staking_pool.key(),
+        constraint = pool_token_account.mint == staking_pool.qst_mint
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    // Must be owned by staking_pool.fee_recipient (checked in the handler)
+    #[account(
+        mut,
+        constraint = fee_recipient_token_account.mint == staking_pool.qst_mint
+    )]
+    pub fee_recipient_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AuditPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account(
+        constraint = pool_token_account.owner == staking_pool.key(),
+        constraint = pool_token_account.mint == staking_pool.qst_mint
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    // Permissionless: any signer can audit; StakeAccounts are passed as remaining_accounts
+    #[account()]
+    pub auditor: Signer<'info>,
+}
+
+
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EnrollInBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_account", user.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.user == user.key()
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account()]
+    pub user: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnenrollFromBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
 
-        msg!("Collected {} unclaimed bonus tokens to dev wallet after 2-month claim period", dust_amount);
-        Ok(())
-    }
+    #[account(
+        mut,
+        seeds = [b"stake_account", user.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.user == user.key()
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
 
+    #[account()]
+    pub user: Signer<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+pub struct StakeTokens<'info> {
     #[account(
-        init,
-        payer = payer,
-        // discriminator + authority + total_staked + total_enrolled_stake + penalty_vault_amount
-        // + first_stake + bonus_deadline + stake_window_end + latest_bonus_unlock_time + qst_mint + bump
-        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 32 + 1,
+        mut,
         seeds = [b"staking_pool"],
-        bump
+        bump = staking_pool.bump,
+        constraint = staking_pool.qst_mint == user_token_account.mint
     )]
     pub staking_pool: Account<'info, StakingPool>,
 
-    #[account()]
-    pub qst_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        // discriminator + user + amount + node_keys_earned(u32) + principal_unlock + bonus_unlock + enrolled_in_bonus + bump + enrolled_stake + bonus_claimed
+        // + redistribution_claimed + reward_per_weight_paid(u128) + rewards_accrued + late_stake + late_stake_unlock_time
+        // + round(u32)
+        space = 8 + 32 + 8 + 4 + 8 + 8 + 1 + 1 + 8 + 1 + 1 + 16 + 8 + 8 + 8 + 4,
+        seeds = [b"stake_account", user.key().as_ref()],
+        bump
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
 
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == staking_pool.qst_mint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.owner == staking_pool.key(),
+        constraint = pool_token_account.mint == staking_pool.qst_mint
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
 
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct StartStakeWindow<'info> {
+pub struct UnstakeTokens<'info> {
     #[account(
         mut,
         seeds = [b"staking_pool"],
@@ -660,13 +2763,37 @@ pub struct StartStakeWindow<'info> {
     )]
     pub staking_pool: Account<'info, StakingPool>,
 
-    // Admin only: Only admin can start the window (but only once)
-    #[account()]
-    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_account", user.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.user == user.key()
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == staking_pool.qst_mint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.owner == staking_pool.key(),
+        constraint = pool_token_account.mint == staking_pool.qst_mint
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct CollectDust<'info> {
+pub struct ExitEnrolled<'info> {
     #[account(
         mut,
         seeds = [b"staking_pool"],
@@ -674,29 +2801,37 @@ pub struct CollectDust<'info> {
     )]
     pub staking_pool: Account<'info, StakingPool>,
 
-    #[account()]
-    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"stake_account", user.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.user == user.key()
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
 
     #[account(
         mut,
-        constraint = pool_token_account.owner == staking_pool.key(),
-        constraint = pool_token_account.mint == staking_pool.qst_mint
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == staking_pool.qst_mint
     )]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: Account<'info, TokenAccount>,
 
     #[account(
         mut,
-        constraint = dev_token_account.mint == staking_pool.qst_mint
+        constraint = pool_token_account.owner == staking_pool.key(),
+        constraint = pool_token_account.mint == staking_pool.qst_mint
     )]
-    pub dev_token_account: Account<'info, TokenAccount>,
+    pub pool_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 }
 
-
-
+#[event_cpi]
 #[derive(Accounts)]
-pub struct EnrollInBonus<'info> {
+pub struct WithdrawAll<'info> {
     #[account(
         mut,
         seeds = [b"staking_pool"],
@@ -712,27 +2847,42 @@ pub struct EnrollInBonus<'info> {
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
-    #[account()]
+    #[account(mut)]
     pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = user_token_account.owner == user.key(),
+        constraint = user_token_account.mint == staking_pool.qst_mint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        constraint = pool_token_account.owner == staking_pool.key(),
+        constraint = pool_token_account.mint == staking_pool.qst_mint
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
 }
 
+// HAL-02 fix: New accounts struct for bonus withdrawal
+#[event_cpi]
 #[derive(Accounts)]
-pub struct StakeTokens<'info> {
+pub struct WithdrawBonus<'info> {
     #[account(
         mut,
         seeds = [b"staking_pool"],
-        bump = staking_pool.bump,
-        constraint = staking_pool.qst_mint == user_token_account.mint
+        bump = staking_pool.bump
     )]
     pub staking_pool: Account<'info, StakingPool>,
 
     #[account(
-        init_if_needed,
-        payer = user,
-        // discriminator + user + amount + node_keys_earned(u32) + principal_unlock + bonus_unlock + enrolled_in_bonus + bump
-        space = 8 + 32 + 8 + 4 + 8 + 8 + 1 + 1,
+        mut,
         seeds = [b"stake_account", user.key().as_ref()],
-        bump
+        bump = stake_account.bump,
+        constraint = stake_account.user == user.key()
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
@@ -754,11 +2904,11 @@ pub struct StakeTokens<'info> {
     pub pool_token_account: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct UnstakeTokens<'info> {
+pub struct WithdrawRedistributedBonus<'info> {
     #[account(
         mut,
         seeds = [b"staking_pool"],
@@ -794,8 +2944,9 @@ pub struct UnstakeTokens<'info> {
     pub token_program: Program<'info, Token>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct WithdrawAll<'info> {
+pub struct ClaimRewards<'info> {
     #[account(
         mut,
         seeds = [b"staking_pool"],
@@ -823,82 +2974,365 @@ pub struct WithdrawAll<'info> {
 
     #[account(
         mut,
-        constraint = pool_token_account.owner == staking_pool.key(),
-        constraint = pool_token_account.mint == staking_pool.qst_mint
+        seeds = [b"reward_vault"],
+        bump
     )]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub reward_vault: Account<'info, TokenAccount>,
 
     pub token_program: Program<'info, Token>,
 }
 
-// HAL-02 fix: New accounts struct for bonus withdrawal
 #[derive(Accounts)]
-pub struct WithdrawBonus<'info> {
+pub struct GetStakeInfo<'info> {
+    #[account(
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+
+    #[account(
+        seeds = [b"stake_account", user.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.user == user.key()
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+
+    /// CHECK: Read-only view, only used to derive the stake account PDA. No signature required.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteUnstake<'info> {
     #[account(
-        mut,
         seeds = [b"staking_pool"],
         bump = staking_pool.bump
     )]
     pub staking_pool: Account<'info, StakingPool>,
 
     #[account(
-        mut,
         seeds = [b"stake_account", user.key().as_ref()],
         bump = stake_account.bump,
         constraint = stake_account.user == user.key()
     )]
     pub stake_account: Account<'info, StakeAccount>,
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+    /// CHECK: Read-only view, only used to derive the stake account PDA. No signature required.
+    pub user: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetStakeInfos<'info> {
+    #[account(
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+    // StakeAccounts to query are passed as remaining_accounts
+}
+
+#[derive(Accounts)]
+pub struct GetPoolInfo<'info> {
+    #[account(
+        seeds = [b"staking_pool"],
+        bump = staking_pool.bump
+    )]
+    pub staking_pool: Account<'info, StakingPool>,
+}
+
+#[account]
+pub struct StakingPool {
+    pub authority: Pubkey,
+    pub total_staked: u64,
+    pub total_enrolled_stake: u64,
+    pub penalty_vault_amount: u64,
+    pub first_stake_timestamp: i64,
+    pub bonus_enrollment_deadline: i64,
+    pub stake_window_end: i64,
+    pub latest_bonus_unlock_time: i64, // HAL-02 fix: track latest unlock time for bonus withdrawal
+    pub qst_mint: Pubkey,
+    pub bump: u8,
+    pub max_total_staked: u64, // pool-wide cap on total_staked (u64::MAX = uncapped)
+    pub max_user_stake: u64,   // per-wallet cap on StakeAccount.amount (u64::MAX = uncapped)
+    pub allowlist_root: [u8; 32],   // merkle root of (user, max_allocation); zero = no allowlist
+    pub allowlist_public_time: i64, // staking becomes public at this time
+    pub enrolled_exit_penalty_rate: u64, // % charged by exit_enrolled
+    pub last_admin_action: i64,          // updated by every admin instruction
+    pub admin_inactivity_period: i64,    // admin silence required before settlement
+    pub settlement_active: bool,         // abandoned pool: payouts only via settle_position
+    pub settlement_penalty_vault: u64,   // penalty vault snapshot at settlement
+    pub settlement_total_stake: u64,     // total_staked snapshot at settlement
+    pub event_seq: u64,                  // sequence number of the last emitted event
+    pub fee_recipient: Pubkey,           // wallet whose token account receives collect_dust
+    pub bonus_claim_period: i64,         // time after bonus_withdrawal_time to claim before the policy applies
+    pub unclaimed_bonus_policy: UnclaimedBonusPolicy,
+    pub claimed_enrolled_stake: u64,     // weight of claimed positions not yet paid a redistribution share
+    pub bonus_snapshot_taken: bool,      // penalty vault and enrolled weight frozen for withdraw_bonus
+    pub bonus_snapshot_vault: u64,       // penalty_vault_amount at the snapshot
+    pub bonus_snapshot_weight: u64,      // total_enrolled_stake at the snapshot
+    pub redistribution_snapshot_vault: u64,  // forfeited shares at the first redistribution claim
+    pub redistribution_snapshot_weight: u64, // claimed_enrolled_stake at that claim; 0 until then
+    pub rounding_dust: u64,              // precision loss left in the penalty vault
+    pub reward_vault_amount: u64,        // emissions funded and not yet claimed
+    pub reward_stream_amount: u64,       // emissions streamed by the current fund_rewards period
+    pub reward_start: i64,
+    pub reward_end: i64,
+    pub reward_released: u64,            // part of the stream already credited
+    pub reward_per_weight: u128,         // emissions per enrolled weight, scaled by REWARD_PRECISION
+    pub reward_unallocated: u64,         // released while nothing was enrolled; rolls into the next stream
+    pub unclaimed_bonus_swept: bool,     // collect_dust took the unclaimed bonus under Sweep
+    pub post_snapshot_penalties: u64,    // penalties paid after the bonus snapshot; owed to nobody
+    pub total_reward_weight: u64,        // enrolled weight earning emissions; see StakeAccount::reward_weight
+    pub round: u32,                      // stake windows run before the current one; see start_next_round
+}
+
+impl StakingPool {
+    // Every event carries the next pool sequence number so indexers can detect gaps
+    pub fn next_event_seq(&mut self) -> Result<u64> {
+        self.event_seq = self.event_seq.checked_add(1).ok_or(ErrorCode::NumericOverflow)?;
+        Ok(self.event_seq)
+    }
+
+    // With an allowlist set, a window opening at `start` must begin in the allowlist phase
+    pub fn require_allowlist_phase_at(&self, start: i64) -> Result<()> {
+        require!(
+            self.allowlist_root == [0u8; 32] || self.allowlist_public_time > start,
+            ErrorCode::InvalidAllowlistPublicTime
+        );
+        Ok(())
+    }
+
+    // True while only allowlisted wallets may stake
+    pub fn is_allowlist_gated(&self, current_time: i64) -> bool {
+        self.allowlist_root != [0u8; 32] && current_time < self.allowlist_public_time
+    }
+
+    // Stakes only happen inside the window, so no principal unlocks after window end + lock
+    pub fn final_unlock_time(&self) -> i64 {
+        let latest_principal_unlock = self.stake_window_end + PRINCIPAL_LOCK_PERIOD;
+        latest_principal_unlock.max(self.latest_bonus_unlock_time)
+    }
+
+    // Earliest time trigger_settlement may run: inactivity period after both the final
+    // unlock and the last admin action, and never before the bonus claim period ends
+    pub fn settlement_available_time(&self) -> i64 {
+        let inactive_since = self.final_unlock_time().max(self.last_admin_action);
+        (inactive_since + self.admin_inactivity_period).max(self.bonus_claim_deadline())
+    }
+
+    // No enrollee can withdraw principal before the earliest possible bonus unlock, so until
+    // then total_enrolled_stake is a part of total_staked
+    pub fn enrolled_principal_locked(&self, current_time: i64) -> bool {
+        current_time < self.first_stake_timestamp + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD
+    }
+
+    // Can only withdraw bonus 1 day after the latest bonus unlock of all enrollees, and never
+    // before enrollment closes, so a pool nobody has enrolled in yet can't be snapshotted. The
+    // unlocks are frozen once enrollment closes, so extending the window never moves the
+    // claim date; penalties paid in after the snapshot are tracked in post_snapshot_penalties.
+    pub fn bonus_withdrawal_time(&self) -> i64 {
+        self.latest_bonus_unlock_time.max(self.bonus_enrollment_deadline) + BONUS_WITHDRAWAL_DELAY
+    }
+
+    // Freezes the bonus split on the first payout, so shares don't depend on claim order
+    pub fn take_bonus_snapshot(&mut self) {
+        if !self.bonus_snapshot_taken {
+            self.bonus_snapshot_taken = true;
+            self.bonus_snapshot_vault = self.penalty_vault_amount;
+            self.bonus_snapshot_weight = self.total_enrolled_stake;
+        }
+    }
+
+    // A position's bonus: exact once the snapshot exists, a preview on the current vault before
+    pub fn bonus_share(&self, enrolled_stake: u64) -> u64 {
+        if self.bonus_snapshot_taken {
+            pro_rata_share(self.bonus_snapshot_vault, enrolled_stake, self.bonus_snapshot_weight)
+        } else {
+            pro_rata_share(self.penalty_vault_amount, enrolled_stake, self.total_enrolled_stake)
+        }
+    }
+
+    // After the claim deadline the unclaimed positions' shares go to the on-time claimants
+    pub fn take_redistribution_snapshot(&mut self) {
+        if self.redistribution_snapshot_weight == 0 {
+            self.redistribution_snapshot_vault = self.owed_bonus();
+            self.redistribution_snapshot_weight = self.claimed_enrolled_stake;
+        }
+    }
+
+    // Penalties always reach the penalty vault, but only those paid before the bonus snapshot
+    // are split by it. Later ones are owed to nobody and collect_dust takes them under every
+    // unclaimed_bonus_policy.
+    pub fn add_penalty(&mut self, amount: u64) -> Result<()> {
+        self.penalty_vault_amount = self
+            .penalty_vault_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::NumericOverflow)?;
+        if self.bonus_snapshot_taken {
+            self.post_snapshot_penalties = self
+                .post_snapshot_penalties
+                .checked_add(amount)
+                .ok_or(ErrorCode::NumericOverflow)?;
+        }
+        Ok(())
+    }
+
+    // Part of the penalty vault some position can still be paid
+    pub fn owed_bonus(&self) -> u64 {
+        if self.redistribution_snapshot_weight > 0 {
+            pro_rata_share(
+                self.redistribution_snapshot_vault,
+                self.claimed_enrolled_stake,
+                self.redistribution_snapshot_weight,
+            )
+        } else if self.bonus_snapshot_taken {
+            pro_rata_share(self.bonus_snapshot_vault, self.total_enrolled_stake, self.bonus_snapshot_weight)
+        } else {
+            self.penalty_vault_amount
+        }
+    }
+
+    // Flooring every share leaves less than one base unit per position behind. A snapshot
+    // with no enrolled weight floors nothing: its whole vault is owed to nobody.
+    pub fn update_rounding_dust(&mut self) {
+        self.rounding_dust = if self.bonus_snapshot_taken && self.bonus_snapshot_weight == 0 {
+            0
+        } else {
+            self.penalty_vault_amount
+                .saturating_sub(self.owed_bonus())
+                .saturating_sub(self.post_snapshot_penalties)
+        };
+    }
+
+    // Bonus withdraw_bonus or withdraw_redistributed_bonus would still pay the position after
+    // the claim deadline. Settlement pays it ahead of the penalty split.
+    pub fn settlement_bonus(&self, stake_account: &StakeAccount) -> u64 {
+        if self.redistribution_snapshot_weight > 0 {
+            if stake_account.bonus_claimed && !stake_account.redistribution_claimed {
+                pro_rata_share(
+                    self.redistribution_snapshot_vault,
+                    stake_account.enrolled_stake,
+                    self.redistribution_snapshot_weight,
+                )
+            } else {
+                0
+            }
+        } else if self.unclaimed_bonus_still_claimable() && stake_account.in_bonus_pool() {
+            self.bonus_share(stake_account.enrolled_stake)
+        } else {
+            0
+        }
+    }
+
+    // Sum of settlement_bonus over all positions, held out of settlement_penalty_vault
+    pub fn settlement_bonus_reserve(&self) -> u64 {
+        if self.redistribution_snapshot_weight > 0 || self.unclaimed_bonus_still_claimable() {
+            self.owed_bonus().min(self.penalty_vault_amount)
+        } else {
+            0
+        }
+    }
+
+    // Past the claim deadline, withdraw_bonus still pays under KeepClaimable, and under Sweep
+    // until collect_dust has taken the unclaimed shares
+    fn unclaimed_bonus_still_claimable(&self) -> bool {
+        match self.unclaimed_bonus_policy {
+            UnclaimedBonusPolicy::Sweep => !self.unclaimed_bonus_swept,
+            UnclaimedBonusPolicy::Redistribute | UnclaimedBonusPolicy::RollOver => false,
+            UnclaimedBonusPolicy::KeepClaimable => true,
+        }
+    }
+
+    // Users get the claim period to claim bonus rewards before the unclaimed bonus policy applies
+    pub fn bonus_claim_deadline(&self) -> i64 {
+        self.bonus_withdrawal_time() + self.bonus_claim_period
+    }
+
+    // Whether withdraw_bonus closes at the claim deadline
+    pub fn late_claims_refused(&self) -> bool {
+        matches!(
+            self.unclaimed_bonus_policy,
+            UnclaimedBonusPolicy::Redistribute | UnclaimedBonusPolicy::RollOver
+        )
+    }
 
-    #[account(
-        mut,
-        constraint = user_token_account.owner == user.key(),
-        constraint = user_token_account.mint == staking_pool.qst_mint
-    )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    // Whether collect_dust may also take the bonus still owed to positions once the claim
+    // deadline has passed. Redistribute only falls back to the sweep when no on-time claimant
+    // is left to pay.
+    pub fn unclaimed_bonus_sweepable(&self) -> bool {
+        match self.unclaimed_bonus_policy {
+            UnclaimedBonusPolicy::Sweep => true,
+            UnclaimedBonusPolicy::Redistribute => self.claimed_enrolled_stake == 0,
+            UnclaimedBonusPolicy::KeepClaimable | UnclaimedBonusPolicy::RollOver => false,
+        }
+    }
 
-    #[account(
-        mut,
-        constraint = pool_token_account.owner == staking_pool.key(),
-        constraint = pool_token_account.mint == staking_pool.qst_mint
-    )]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    // Emissions the current stream has released by `current_time`
+    pub fn rewards_released(&self, current_time: i64) -> u64 {
+        stream_released(self.reward_stream_amount, self.reward_start, self.reward_end, current_time)
+    }
 
-    pub token_program: Program<'info, Token>,
-}
+    // reward_per_weight including what was released since the last accrual
+    pub fn reward_per_weight_at(&self, current_time: i64) -> u128 {
+        if self.total_reward_weight == 0 {
+            return self.reward_per_weight;
+        }
+        let pending = self.rewards_released(current_time).saturating_sub(self.reward_released);
+        self.reward_per_weight
+            + u128::from(pending) * REWARD_PRECISION / u128::from(self.total_reward_weight)
+    }
 
-#[derive(Accounts)]
-pub struct GetStakeInfo<'info> {
-    #[account(
-        seeds = [b"staking_pool"],
-        bump = staking_pool.bump
-    )]
-    pub staking_pool: Account<'info, StakingPool>,
+    // Credits released emissions to the current reward weight, or leaves them unallocated
+    // when nothing earns
+    pub fn accrue_rewards(&mut self, current_time: i64) -> Result<()> {
+        let released = self.rewards_released(current_time);
+        if self.total_reward_weight == 0 {
+            self.reward_unallocated = self
+                .reward_unallocated
+                .checked_add(released.saturating_sub(self.reward_released))
+                .ok_or(ErrorCode::NumericOverflow)?;
+        } else {
+            self.reward_per_weight = self.reward_per_weight_at(current_time);
+        }
+        self.reward_released = released;
+        Ok(())
+    }
 
-    #[account(
-        seeds = [b"stake_account", user.key().as_ref()],
-        bump = stake_account.bump,
-        constraint = stake_account.user == user.key()
-    )]
-    pub stake_account: Account<'info, StakeAccount>,
+    // Replaces a position's old reward weight with its new one; the position must be synced first
+    pub fn update_reward_weight(&mut self, old_weight: u64, new_weight: u64) -> Result<()> {
+        self.total_reward_weight = self
+            .total_reward_weight
+            .checked_sub(old_weight)
+            .and_then(|weight| weight.checked_add(new_weight))
+            .ok_or(ErrorCode::NumericOverflow)?;
+        Ok(())
+    }
 
-    pub user: Signer<'info>,
-}
+    // True once the window has opened; a scheduled start still in the future doesn't count
+    pub fn window_opened(&self, current_time: i64) -> bool {
+        self.first_stake_timestamp != 0 && current_time >= self.first_stake_timestamp
+    }
 
-#[account]
-pub struct StakingPool {
-    pub authority: Pubkey,
-    pub total_staked: u64,
-    pub total_enrolled_stake: u64,
-    pub penalty_vault_amount: u64,
-    pub first_stake_timestamp: i64,
-    pub bonus_enrollment_deadline: i64,
-    pub stake_window_end: i64,
-    pub latest_bonus_unlock_time: i64, // HAL-02 fix: track latest unlock time for bonus withdrawal
-    pub qst_mint: Pubkey,
-    pub bump: u8,
+    // The window end may only be moved while stakes are being taken
+    pub fn require_window_open(&self, current_time: i64) -> Result<()> {
+        match self.window_phase(current_time) {
+            StakeWindowPhase::NotStarted => err!(ErrorCode::StakeWindowNotStarted),
+            StakeWindowPhase::Scheduled => err!(ErrorCode::StakeWindowNotOpenYet),
+            StakeWindowPhase::Open => Ok(()),
+            StakeWindowPhase::Closed => err!(ErrorCode::StakeWindowClosed),
+        }
+    }
+
+    pub fn window_phase(&self, current_time: i64) -> StakeWindowPhase {
+        if self.first_stake_timestamp == 0 {
+            StakeWindowPhase::NotStarted
+        } else if current_time < self.first_stake_timestamp {
+            StakeWindowPhase::Scheduled
+        } else if current_time <= self.stake_window_end {
+            StakeWindowPhase::Open
+        } else {
+            StakeWindowPhase::Closed
+        }
+    }
 }
 
 #[account]
@@ -907,9 +3341,100 @@ pub struct StakeAccount {
     pub amount: u64,
     pub node_keys_earned: u32,
     pub principal_unlock_time: i64,
-    pub bonus_unlock_time: i64, // 0 if not enrolled; principal+10d if enrolled
+    pub bonus_unlock_time: i64, // 0 if not enrolled; principal+10d as of the last stake before the enrollment deadline
     pub enrolled_in_bonus: bool,
     pub bump: u8,
+    pub enrolled_stake: u64, // bonus weight; frozen at bonus_enrollment_deadline
+    pub bonus_claimed: bool, // set by withdraw_bonus; enrolled_in_bonus stays set
+    pub redistribution_claimed: bool, // set by withdraw_redistributed_bonus
+    pub reward_per_weight_paid: u128, // pool reward_per_weight at the last sync
+    pub rewards_accrued: u64,         // emissions synced and not yet claimed
+    pub late_stake: u64,              // part of `amount` staked while enrolled after the enrollment deadline
+    pub late_stake_unlock_time: i64,  // unlock of late_stake; the enrolled principal keeps bonus_unlock_time
+    pub round: u32,                   // pool round the fields above belong to
+}
+
+impl StakeAccount {
+    // Total stake behind the position's node keys, including any withdrawn since
+    pub fn original_stake(&self) -> u64 {
+        STAKE_RULES.original_stake(self.node_keys_earned)
+    }
+
+    // start_next_round requires every position to be withdrawn, so a position last touched in
+    // an earlier round holds no stake. What it recorded about that round's lock and bonus no
+    // longer applies; node keys and unclaimed emissions are kept.
+    pub fn enter_round(&mut self, round: u32) {
+        if self.round != round {
+            self.round = round;
+            self.principal_unlock_time = 0;
+            self.bonus_unlock_time = 0;
+            self.enrolled_in_bonus = false;
+            self.enrolled_stake = 0;
+            self.bonus_claimed = false;
+            self.redistribution_claimed = false;
+            self.late_stake = 0;
+            self.late_stake_unlock_time = 0;
+        }
+    }
+
+    // Copy of the position as enter_round would leave it, for views that can't write it back
+    pub fn in_round(&self, round: u32) -> StakeAccount {
+        let mut position = self.clone();
+        position.enter_round(round);
+        position
+    }
+
+    // Enrolled weight still counted in total_enrolled_stake
+    pub fn in_bonus_pool(&self) -> bool {
+        self.enrolled_in_bonus && !self.bonus_claimed
+    }
+
+    // Weight earning emissions: the enrolled stake while it counts in total_enrolled_stake and
+    // the enrolled principal is still staked. Only a late stake can remain once withdraw_all
+    // has released the enrolled principal.
+    pub fn reward_weight(&self) -> u64 {
+        if self.in_bonus_pool() && self.amount > self.late_stake {
+            self.enrolled_stake
+        } else {
+            0
+        }
+    }
+
+    // Emissions owed to the position at the pool's `reward_per_weight`
+    pub fn accrued_rewards(&self, reward_per_weight: u128) -> Result<u64> {
+        let per_weight = reward_per_weight.saturating_sub(self.reward_per_weight_paid);
+        let earned = u64::try_from(u128::from(self.reward_weight()) * per_weight / REWARD_PRECISION)
+            .map_err(|_| ErrorCode::NumericOverflow)?;
+        Ok(self.rewards_accrued.checked_add(earned).ok_or(ErrorCode::NumericOverflow)?)
+    }
+
+    // When withdraw_all may release the principal. An enrolled position's late stake
+    // unlocks separately, at late_stake_unlock_time.
+    pub fn unlock_time(&self) -> i64 {
+        if self.enrolled_in_bonus {
+            self.bonus_unlock_time
+        } else {
+            self.principal_unlock_time
+        }
+    }
+
+    // Principal withdraw_all releases at `current_time`: the enrolled principal from the
+    // bonus unlock and the late stake from its own unlock
+    pub fn withdrawable_principal(&self, current_time: i64) -> u64 {
+        let late_stake = if current_time >= self.late_stake_unlock_time { self.late_stake } else { 0 };
+        let principal = if current_time >= self.unlock_time() { self.amount - self.late_stake } else { 0 };
+        principal + late_stake
+    }
+
+    // Stake unstake_tokens may take and the unlock its penalty tiers count down to. Enrolled
+    // principal is locked in; only a late stake can leave early.
+    pub fn unstakeable(&self) -> (u64, i64) {
+        if self.enrolled_in_bonus {
+            (self.late_stake, self.late_stake_unlock_time)
+        } else {
+            (self.amount, self.principal_unlock_time)
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -923,6 +3448,182 @@ pub struct StakeInfo {
     pub unlock_time: i64,        // The actual unlock time (principal or bonus)
     pub is_unlocked: bool,       // Whether currently unlocked
     pub time_until_unlock: i64,  // Seconds remaining until unlock (0 if unlocked)
+    pub user: Pubkey,            // Owner of the position (identifies batch results)
+    pub bonus_claimed: bool,     // withdraw_bonus already paid this position
+    pub accrued_rewards: u64,    // emissions claimable through claim_rewards
+}
+
+// What happens to bonus still in the penalty vault after the claim period. Rounding dust
+// goes to the fee recipient under every policy.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UnclaimedBonusPolicy {
+    Sweep,         // collect_dust sends it to the fee recipient; later claims are refused
+    Redistribute,  // late claims are refused; on-time claimants split it pro rata
+    KeepClaimable, // withdraw_bonus stays open; collect_dust only takes rounding dust
+    RollOver,      // late claims are refused; start_next_round carries it into the next round
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StakeWindowPhase {
+    NotStarted,
+    Open,
+    Closed,
+    Scheduled, // start time set by schedule_stake_window, not reached yet
+}
+
+// Early unstake tiers, relative to the position's principal unlock time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PenaltyTier {
+    Unlocked, // unlocked: no penalty
+    Late,     // 7—0 days remaining: 20% penalty
+    Early,    // 15—7 days remaining: 30% penalty
+    Blocked,  // more than 15 days remaining: unstake blocked
+}
+
+impl PenaltyTier {
+    pub fn from_time_until_unlock(time_until_unlock: i64) -> Self {
+        PENALTY_SCHEDULE.tier(time_until_unlock).into()
+    }
+
+    // Penalty percentage; Blocked has no rate because the unstake is rejected
+    pub fn rate(&self) -> u64 {
+        PENALTY_SCHEDULE.rate((*self).into())
+    }
+
+    pub fn penalty_for(&self, amount: u64) -> u64 {
+        penalty_amount(amount, self.rate())
+    }
+
+    // Seconds until the position moves to the next (cheaper) tier; 0 once unlocked
+    pub fn seconds_until_next(&self, time_until_unlock: i64) -> i64 {
+        PENALTY_SCHEDULE.seconds_until_next((*self).into(), time_until_unlock)
+    }
+}
+
+impl From<Tier> for PenaltyTier {
+    fn from(tier: Tier) -> Self {
+        match tier {
+            Tier::Unlocked => PenaltyTier::Unlocked,
+            Tier::Late => PenaltyTier::Late,
+            Tier::Early => PenaltyTier::Early,
+            Tier::Blocked => PenaltyTier::Blocked,
+        }
+    }
+}
+
+impl From<PenaltyTier> for Tier {
+    fn from(tier: PenaltyTier) -> Self {
+        match tier {
+            PenaltyTier::Unlocked => Tier::Unlocked,
+            PenaltyTier::Late => Tier::Late,
+            PenaltyTier::Early => Tier::Early,
+            PenaltyTier::Blocked => Tier::Blocked,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct UnstakeQuote {
+    pub amount: u64,
+    pub penalty: u64,
+    pub net_amount: u64,
+    pub tier: PenaltyTier,
+    pub penalty_rate: u64,
+    pub time_until_next_tier: i64, // seconds until the penalty tier changes (0 once unlocked)
+    pub unstake_allowed: bool,     // false while blocked, enrolled in bonus or in settlement
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct PoolInfo {
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub qst_mint: Pubkey,
+    pub total_staked: u64,
+    pub total_enrolled_stake: u64,
+    pub penalty_vault_amount: u64,
+    pub max_total_staked: u64,
+    pub max_user_stake: u64,
+    pub window_phase: StakeWindowPhase,
+    pub first_stake_timestamp: i64,
+    pub stake_window_end: i64,
+    pub bonus_enrollment_deadline: i64,
+    pub bonus_enrollment_open: bool,
+    pub allowlist_gated: bool,          // only allowlisted wallets may stake right now
+    pub allowlist_public_time: i64,
+    pub latest_bonus_unlock_time: i64,
+    pub bonus_withdrawal_time: i64,     // max(latest bonus unlock, enrollment deadline) + BONUS_WITHDRAWAL_DELAY
+    pub bonus_withdrawal_available: bool,
+    pub dust_claim_deadline: i64,       // earliest collect_dust time
+    pub dust_collectable: bool,
+    pub current_penalty_tier: PenaltyTier, // tier for a position staked at window close
+    pub current_penalty_rate: u64,
+    pub settlement_active: bool,
+    pub settlement_available_time: i64,
+    pub event_seq: u64,
+    pub current_time: i64,
+    pub unclaimed_bonus_policy: UnclaimedBonusPolicy,
+    pub bonus_claim_period: i64,
+    pub rounding_dust: u64,             // precision loss in the penalty vault; the rest is owed
+    pub reward_vault_amount: u64,       // emissions funded and not yet claimed
+    pub reward_stream_amount: u64,
+    pub reward_start: i64,
+    pub reward_end: i64,
+    pub rewards_released: u64,          // part of the current stream released so far
+    pub round: u32,                     // stake windows run before the current one
+}
+
+#[event]
+pub struct PoolInitialized {
+    pub authority: Pubkey,
+    pub qst_mint: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct AuthorityChanged {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct FeeRecipientChanged {
+    pub previous_fee_recipient: Pubkey,
+    pub new_fee_recipient: Pubkey,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct AdminHeartbeatRecorded {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct EnrolledExitPenaltyUpdated {
+    pub penalty_rate: u64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct AdminInactivityPeriodUpdated {
+    pub inactivity_period: i64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct UnclaimedBonusPolicyUpdated {
+    pub policy: UnclaimedBonusPolicy,
+    pub claim_period: i64,
+    pub timestamp: i64,
+    pub seq: u64,
 }
 
 #[event]
@@ -930,6 +3631,66 @@ pub struct StakeWindowStarted {
     pub start_time: i64,
     pub stake_window_end: i64,
     pub bonus_enrollment_deadline: i64,
+    pub max_total_staked: u64,
+    pub max_user_stake: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct NextRoundStarted {
+    pub round: u32,
+    pub rolled_over: u64, // penalty vault carried into the round
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StakeWindowScheduled {
+    pub start_time: i64,
+    pub stake_window_end: i64,
+    pub bonus_enrollment_deadline: i64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StakeWindowScheduleCancelled {
+    pub cancelled_start_time: i64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StakeWindowExtended {
+    pub previous_window_end: i64,
+    pub stake_window_end: i64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StakeWindowClosedEarly {
+    pub previous_window_end: i64,
+    pub stake_window_end: i64,
+    pub bonus_enrollment_deadline: i64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct StakeCapsUpdated {
+    pub max_total_staked: u64,
+    pub max_user_stake: u64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct AllowlistConfigured {
+    pub merkle_root: [u8; 32],
+    pub public_time: i64,
+    pub timestamp: i64,
+    pub seq: u64,
 }
 
 #[event]
@@ -937,6 +3698,15 @@ pub struct BonusEnrollment {
     pub user: Pubkey,
     pub enrolled_stake: u64,
     pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct BonusUnenrollment {
+    pub user: Pubkey,
+    pub unenrolled_stake: u64,
+    pub timestamp: i64,
+    pub seq: u64,
 }
 
 #[event]
@@ -949,6 +3719,7 @@ pub struct StakeEvent {
     pub bonus_unlock_time: i64,
     pub enrolled_in_bonus: bool,
     pub timestamp: i64,
+    pub seq: u64,
 }
 
 #[event]
@@ -960,6 +3731,18 @@ pub struct UnstakeEvent {
     pub net_to_user: u64,
     pub penalty_vault_total: u64,
     pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct EnrolledExitEvent {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub penalty_applied: u64,
+    pub net_to_user: u64,
+    pub penalty_vault_total: u64,
+    pub timestamp: i64,
+    pub seq: u64,
 }
 
 #[event]
@@ -970,6 +3753,27 @@ pub struct WithdrawAllEvent {
     pub total_withdrawn: u64,
     pub node_keys_retained: u32,
     pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct SettlementTriggered {
+    pub triggered_by: Pubkey,
+    pub penalty_vault: u64,
+    pub total_stake: u64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct PositionSettled {
+    pub user: Pubkey,
+    pub principal_amount: u64,
+    pub penalty_share: u64,
+    pub bonus_amount: u64, // bonus or redistribution share the position could still claim
+    pub settled_by: Pubkey,
+    pub timestamp: i64,
+    pub seq: u64,
 }
 
 // HAL-02 fix: New event for bonus withdrawal
@@ -978,6 +3782,65 @@ pub struct BonusWithdrawEvent {
     pub user: Pubkey,
     pub bonus_amount: u64,
     pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct BonusRedistributed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct RewardsFunded {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub stream_amount: u64, // amount plus emissions the previous stream left unallocated
+    pub start: i64,
+    pub end: i64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct RewardsClaimed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct UnallocatedRewardsReclaimed {
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct DustCollected {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub rounding_dust: u64,   // precision loss from flooring bonus shares
+    pub unclaimed_bonus: u64, // everything else: forfeited shares, post-snapshot penalties, unenrolled vault
+    pub timestamp: i64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct AuditEvent {
+    pub auditor: Pubkey,
+    pub vault_balance: u64,
+    pub total_staked: u64,
+    pub penalty_vault_amount: u64,
+    pub total_enrolled_stake: u64,
+    pub accounts_audited: u32,
+    pub audited_stake: u64,
+    pub timestamp: i64,
+    pub seq: u64,
 }
 
 #[error_code]
@@ -1004,7 +3867,7 @@ pub enum ErrorCode {
     StakeWindowClosed,
     #[msg("Cannot unstake while stake window is still active")]
     StakeWindowStillActive,
-    #[msg("Stake window is already active and cannot be restarted")]
+    #[msg("Stake window is already active or scheduled and cannot be restarted")]
     StakeWindowAlreadyActive,
     #[msg("Bonus enrollment period has closed")]
     BonusEnrollmentClosed,
@@ -1018,8 +3881,8 @@ pub enum ErrorCode {
     BonusEnrolledCannotUnstake,
     #[msg("No dust to collect or too many enrolled users remaining")]
     NoDustToCollect,
-    #[msg("Invalid dev wallet address")]
-    InvalidDevWallet,
+    #[msg("Invalid fee recipient")]
+    InvalidFeeRecipient,
     #[msg("Unauthorized")]
     Unauthorized,
     #[msg("QST mint must use 6 decimals")]
@@ -1033,6 +3896,104 @@ pub enum ErrorCode {
     NotEnrolledInBonus,
     #[msg("Must withdraw principal first before withdrawing bonus")]
     MustWithdrawPrincipalFirst,
-    #[msg("Bonus claim period has not expired. Users have 2 months to claim rewards.")]
+    #[msg("Bonus claim period has not expired")]
     BonusClaimPeriodNotExpired,
-}
\ No newline at end of file
+    #[msg("Stake would exceed the pool-wide staking cap")]
+    PoolStakeCapExceeded,
+    #[msg("Stake would exceed the per-wallet staking cap")]
+    UserStakeCapExceeded,
+    #[msg("Invalid stake caps. Per-wallet cap must be between the minimum stake and the pool cap")]
+    InvalidStakeCaps,
+    #[msg("Staking is allowlist-only. Use stake_tokens_allowlisted with a valid proof")]
+    AllowlistProofRequired,
+    #[msg("Invalid allowlist proof")]
+    InvalidAllowlistProof,
+    #[msg("Stake would exceed the wallet's allowlist allocation")]
+    AllowlistAllocationExceeded,
+    #[msg("Invalid penalty rate. Enrolled exit penalty must exceed the early unstake penalty and be at most 100%")]
+    InvalidPenaltyRate,
+    #[msg("Stake is already unlocked. Use withdraw_all instead")]
+    AlreadyUnlocked,
+    #[msg("Invalid inactivity period. Must be at least 30 days")]
+    InvalidInactivityPeriod,
+    #[msg("Settlement not available. Admin inactivity period has not elapsed")]
+    SettlementNotAvailable,
+    #[msg("Pool is in settlement. Use settle_position")]
+    SettlementActive,
+    #[msg("Pool is not in settlement")]
+    SettlementNotActive,
+    #[msg("Position has nothing to settle")]
+    NothingToSettle,
+    #[msg("Invalid authority")]
+    InvalidAuthority,
+    #[msg("Invalid stake account")]
+    InvalidStakeAccount,
+    #[msg("Too many stake accounts. Maximum is 10 per batch")]
+    TooManyStakeAccounts,
+    #[msg("Unstake penalty exceeds the maximum accepted by the user")]
+    PenaltyExceedsMaximum,
+    #[msg("Audit failed: vault balance is below total staked plus the penalty vault")]
+    VaultBalanceShortfall,
+    #[msg("Audit failed: total enrolled stake exceeds total staked")]
+    EnrolledStakeExceedsTotalStaked,
+    #[msg("Audit failed: an enrollee's bonus unlock is later than the pool's latest bonus unlock")]
+    BonusUnlockAfterLatest,
+    #[msg("Audit failed: audited stake accounts exceed the pool totals")]
+    AuditedStakeExceedsTotals,
+    #[msg("Stake account passed more than once")]
+    DuplicateStakeAccount,
+    #[msg("Scheduled window start must be in the future and within a year")]
+    InvalidWindowStart,
+    #[msg("No stake window is scheduled")]
+    NoScheduledStakeWindow,
+    #[msg("Stake window is scheduled but has not opened yet")]
+    StakeWindowNotOpenYet,
+    #[msg("New window end must move the current end in the requested direction")]
+    InvalidWindowEnd,
+    #[msg("Stake window cannot run longer than the maximum window period")]
+    StakeWindowTooLong,
+    #[msg("Early close must leave at least the notice period")]
+    InsufficientCloseNotice,
+    #[msg("Bonus already claimed for this position")]
+    BonusAlreadyClaimed,
+    #[msg("Invalid bonus claim period. Must be at least 30 days")]
+    InvalidClaimPeriod,
+    #[msg("Bonus claim period has ended. Unclaimed shares go to on-time claimants or the next round")]
+    BonusClaimPeriodExpired,
+    #[msg("Unclaimed bonus policy does not allow collecting the penalty vault")]
+    UnclaimedBonusNotSweepable,
+    #[msg("Unclaimed bonus is not redistributed in this pool")]
+    RedistributionNotEnabled,
+    #[msg("Only positions that claimed their bonus on time share the redistribution")]
+    NotEligibleForRedistribution,
+    #[msg("Redistributed bonus already claimed for this position")]
+    RedistributionAlreadyClaimed,
+    #[msg("Invalid reward period. Start must not be in the past and end must be after start")]
+    InvalidRewardPeriod,
+    #[msg("A reward stream is still running")]
+    RewardStreamActive,
+    #[msg("No rewards to claim")]
+    NoRewardsToClaim,
+    #[msg("Unclaimed bonus was swept to the fee recipient after the claim period")]
+    UnclaimedBonusSwept,
+    #[msg("No unallocated rewards to reclaim")]
+    NoUnallocatedRewards,
+    #[msg("Unclaimed bonus does not roll over in this pool")]
+    RollOverNotEnabled,
+    #[msg("Every position must be withdrawn before the next round")]
+    RoundStillStaked,
+    #[msg("Allowlist public time must be after the stake window start")]
+    InvalidAllowlistPublicTime,
+}
+
+impl From<StakeError> for ErrorCode {
+    fn from(error: StakeError) -> Self {
+        match error {
+            StakeError::Zero => ErrorCode::InvalidAmount,
+            StakeError::BelowMinimum => ErrorCode::InsufficientStakeAmount,
+            StakeError::AboveMaximum => ErrorCode::StakeAmountTooLarge,
+            StakeError::NotMultiple => ErrorCode::InvalidStakeAmount,
+            StakeError::KeyOverflow => ErrorCode::NumericOverflow,
+        }
+    }
+}
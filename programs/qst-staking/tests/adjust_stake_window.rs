@@ -0,0 +1,157 @@
+mod common;
+
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use common::{error, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, BONUS_ENROLLMENT_PERIOD, ID as PROGRAM_ID, MAX_STAKE_WINDOW_PERIOD, STAKE_WINDOW_PERIOD,
+    WINDOW_CLOSE_NOTICE_PERIOD,
+};
+
+const WINDOW_END: i64 = START_TIME + STAKE_WINDOW_PERIOD;
+
+fn extend(test: &mut TestPool, admin: Pubkey, new_window_end: i64) -> Result<(), ProgramError> {
+    test.process(instructions::extend_stake_window(&PROGRAM_ID, &admin, new_window_end), &[admin])
+}
+
+fn close_early(test: &mut TestPool, admin: Pubkey, new_window_end: i64) -> Result<(), ProgramError> {
+    test.process(instructions::close_stake_window_early(&PROGRAM_ID, &admin, new_window_end), &[admin])
+}
+
+#[test]
+fn admin_extends_the_window() {
+    let mut test = TestPool::started();
+    test.warp_to(START_TIME + DAY);
+    let seq_before = test.pool().event_seq;
+    let admin = test.admin;
+    extend(&mut test, admin, WINDOW_END + 3 * DAY).unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.stake_window_end, WINDOW_END + 3 * DAY);
+    assert_eq!(pool.bonus_enrollment_deadline, START_TIME + BONUS_ENROLLMENT_PERIOD);
+    assert_eq!(pool.last_admin_action, START_TIME + DAY);
+    assert_eq!(pool.event_seq, seq_before + 1);
+    assert_eq!(test.events().len(), 1);
+}
+
+#[test]
+fn extension_keeps_unstaking_blocked_until_the_new_end() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(200_000 * QST);
+    test.stake(&staker, 200_000 * QST).unwrap();
+    let admin = test.admin;
+    extend(&mut test, admin, WINDOW_END + 3 * DAY).unwrap();
+
+    // Staking is still open past the original end
+    test.warp_to(WINDOW_END + 1);
+    let late = test.add_staker(200_000 * QST);
+    test.stake(&late, 200_000 * QST).unwrap();
+    assert_eq!(
+        test.unstake(&staker, 200_000 * QST, u64::MAX),
+        Err(error(ErrorCode::StakeWindowStillActive))
+    );
+
+    test.warp_to(WINDOW_END + 3 * DAY);
+    assert_eq!(
+        test.unstake(&staker, 200_000 * QST, u64::MAX),
+        Err(error(ErrorCode::StakeWindowStillActive))
+    );
+
+    test.warp_to(WINDOW_END + 3 * DAY + 1);
+    test.unstake(&staker, 200_000 * QST, u64::MAX).unwrap();
+}
+
+#[test]
+fn extension_is_capped_at_the_maximum_window() {
+    let mut test = TestPool::started();
+    let admin = test.admin;
+    let max_end = START_TIME + MAX_STAKE_WINDOW_PERIOD;
+
+    assert_eq!(extend(&mut test, admin, max_end + 1), Err(error(ErrorCode::StakeWindowTooLong)));
+    extend(&mut test, admin, max_end).unwrap();
+    assert_eq!(test.pool().stake_window_end, max_end);
+}
+
+#[test]
+fn extension_must_move_the_end_later() {
+    let mut test = TestPool::started();
+    let admin = test.admin;
+
+    for new_window_end in [WINDOW_END, WINDOW_END - 1] {
+        assert_eq!(extend(&mut test, admin, new_window_end), Err(error(ErrorCode::InvalidWindowEnd)));
+    }
+    assert_eq!(test.pool().stake_window_end, WINDOW_END);
+}
+
+#[test]
+fn admin_closes_the_window_early() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(400_000 * QST);
+    test.stake(&staker, 200_000 * QST).unwrap();
+    let seq_before = test.pool().event_seq;
+    let admin = test.admin;
+    let new_end = START_TIME + WINDOW_CLOSE_NOTICE_PERIOD;
+    close_early(&mut test, admin, new_end).unwrap();
+
+    // Enrollment can't outlast the window
+    let pool = test.pool();
+    assert_eq!(pool.stake_window_end, new_end);
+    assert_eq!(pool.bonus_enrollment_deadline, new_end);
+    assert_eq!(pool.event_seq, seq_before + 1);
+
+    test.warp_to(new_end + 1);
+    assert_eq!(test.stake(&staker, 200_000 * QST), Err(error(ErrorCode::StakeWindowClosed)));
+    assert_eq!(test.enroll(&staker), Err(error(ErrorCode::BonusEnrollmentClosed)));
+}
+
+#[test]
+fn early_close_keeps_a_later_enrollment_deadline_untouched() {
+    let mut test = TestPool::started();
+    test.warp_to(START_TIME + DAY);
+    let admin = test.admin;
+    let new_end = START_TIME + 5 * DAY;
+    close_early(&mut test, admin, new_end).unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.stake_window_end, new_end);
+    assert_eq!(pool.bonus_enrollment_deadline, START_TIME + BONUS_ENROLLMENT_PERIOD);
+}
+
+#[test]
+fn early_close_requires_notice() {
+    let mut test = TestPool::started();
+    test.warp_to(START_TIME + 3 * DAY);
+    let admin = test.admin;
+
+    let too_soon = START_TIME + 3 * DAY + WINDOW_CLOSE_NOTICE_PERIOD - 1;
+    assert_eq!(close_early(&mut test, admin, too_soon), Err(error(ErrorCode::InsufficientCloseNotice)));
+    assert_eq!(close_early(&mut test, admin, WINDOW_END), Err(error(ErrorCode::InvalidWindowEnd)));
+    assert_eq!(test.pool().stake_window_end, WINDOW_END);
+}
+
+#[test]
+fn window_end_is_fixed_outside_the_window() {
+    let mut test = TestPool::new();
+    let admin = test.admin;
+    assert_eq!(extend(&mut test, admin, WINDOW_END), Err(error(ErrorCode::StakeWindowNotStarted)));
+
+    let mut test = TestPool::started();
+    let admin = test.admin;
+    test.warp_to(WINDOW_END + 1);
+    assert_eq!(extend(&mut test, admin, WINDOW_END + DAY), Err(error(ErrorCode::StakeWindowClosed)));
+    assert_eq!(
+        close_early(&mut test, admin, WINDOW_END + 2 * DAY),
+        Err(error(ErrorCode::StakeWindowClosed))
+    );
+}
+
+#[test]
+fn rejects_non_admin() {
+    let mut test = TestPool::started();
+    let intruder = test.add_staker(0).wallet;
+
+    assert_eq!(extend(&mut test, intruder, WINDOW_END + DAY), Err(error(ErrorCode::Unauthorized)));
+    assert_eq!(close_early(&mut test, intruder, WINDOW_END - DAY), Err(error(ErrorCode::Unauthorized)));
+    assert_eq!(test.pool().stake_window_end, WINDOW_END);
+}
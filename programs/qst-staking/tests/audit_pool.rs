@@ -0,0 +1,108 @@
+mod common;
+
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use common::{error, Staker, TestPool, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, BONUS_LOCK_PERIOD, EARLY_UNSTAKE_THRESHOLD_1, ID as PROGRAM_ID, PRINCIPAL_LOCK_PERIOD,
+};
+
+const PRINCIPAL_UNLOCK: i64 = START_TIME + PRINCIPAL_LOCK_PERIOD;
+
+// An enrollee (400k) and a staker (200k) who leaves in the late tier, adding 40k of penalties
+fn audited_pool() -> (TestPool, Staker, Staker) {
+    let mut test = TestPool::started();
+    let enrollee = test.add_staker(400_000 * QST);
+    let leaver = test.add_staker(200_000 * QST);
+    test.stake(&enrollee, 400_000 * QST).unwrap();
+    test.stake(&leaver, 200_000 * QST).unwrap();
+    test.enroll(&enrollee).unwrap();
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 100_000 * QST, 20_000 * QST).unwrap();
+    (test, enrollee, leaver)
+}
+
+fn audit(test: &mut TestPool, users: &[Pubkey]) -> Result<(), ProgramError> {
+    let auditor = test.add_staker(0).wallet;
+    let instruction = instructions::audit_pool(&PROGRAM_ID, &auditor, &test.pool_token_account, users);
+    test.process(instruction, &[auditor])
+}
+
+#[test]
+fn healthy_pool_passes() {
+    let (mut test, enrollee, leaver) = audited_pool();
+    let seq = test.pool().event_seq;
+
+    audit(&mut test, &[enrollee.wallet, leaver.wallet]).unwrap();
+    assert_eq!(test.pool().event_seq, seq + 1);
+    assert_eq!(test.events().len(), 1);
+
+    // Without any positions only the pool-level checks run
+    audit(&mut test, &[]).unwrap();
+}
+
+#[test]
+fn detects_a_vault_shortfall() {
+    let (mut test, enrollee, _) = audited_pool();
+    // 500k staked plus 20k of penalties, one token short
+    let pool_token_account = test.pool_token_account;
+    test.set_token_balance(&pool_token_account, 520_000 * QST - 1);
+
+    assert_eq!(audit(&mut test, &[enrollee.wallet]), Err(error(ErrorCode::VaultBalanceShortfall)));
+}
+
+#[test]
+fn detects_enrolled_stake_above_total_staked() {
+    let (mut test, _, _) = audited_pool();
+    test.update_pool(|pool| pool.total_enrolled_stake = pool.total_staked + 1);
+
+    assert_eq!(audit(&mut test, &[]), Err(error(ErrorCode::EnrolledStakeExceedsTotalStaked)));
+}
+
+#[test]
+fn allows_enrolled_weight_after_principal_withdrawals() {
+    let (mut test, enrollee, leaver) = audited_pool();
+    test.warp_to(START_TIME + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD);
+    test.withdraw_all(&enrollee).unwrap();
+
+    // The enrollee's 400k weight stays until withdraw_bonus while only 100k is staked
+    let pool = test.pool();
+    assert!(pool.total_enrolled_stake > pool.total_staked);
+    audit(&mut test, &[enrollee.wallet, leaver.wallet]).unwrap();
+}
+
+#[test]
+fn detects_a_bonus_unlock_past_the_latest() {
+    let (mut test, enrollee, leaver) = audited_pool();
+    test.update_pool(|pool| pool.latest_bonus_unlock_time -= 1);
+
+    audit(&mut test, &[leaver.wallet]).unwrap();
+    assert_eq!(audit(&mut test, &[enrollee.wallet]), Err(error(ErrorCode::BonusUnlockAfterLatest)));
+}
+
+#[test]
+fn detects_positions_above_the_pool_totals() {
+    let (mut test, enrollee, leaver) = audited_pool();
+    test.update_pool(|pool| pool.total_staked -= 100_000 * QST);
+    test.update_pool(|pool| pool.total_enrolled_stake -= 1);
+
+    assert_eq!(
+        audit(&mut test, &[enrollee.wallet, leaver.wallet]),
+        Err(error(ErrorCode::AuditedStakeExceedsTotals))
+    );
+}
+
+#[test]
+fn rejects_duplicate_and_foreign_accounts() {
+    let (mut test, enrollee, leaver) = audited_pool();
+
+    assert_eq!(
+        audit(&mut test, &[enrollee.wallet, leaver.wallet, enrollee.wallet]),
+        Err(error(ErrorCode::DuplicateStakeAccount))
+    );
+
+    // A wallet without a stake account fails Anchor's owner check
+    let stranger = test.add_staker(0).wallet;
+    assert!(audit(&mut test, &[stranger]).is_err());
+}
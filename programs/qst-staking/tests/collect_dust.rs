@@ -0,0 +1,212 @@
+mod common;
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use common::{error, TestPool, QST, START_TIME};
+use qst_staking_client::events::{decode_event, QstEvent};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, ID as PROGRAM_ID, BONUS_CLAIM_PERIOD, BONUS_LOCK_PERIOD, BONUS_WITHDRAWAL_DELAY,
+    EARLY_UNSTAKE_THRESHOLD_1, PRINCIPAL_LOCK_PERIOD,
+};
+
+const PRINCIPAL_UNLOCK: i64 = START_TIME + PRINCIPAL_LOCK_PERIOD;
+const CLAIM_DEADLINE: i64 =
+    PRINCIPAL_UNLOCK + BONUS_LOCK_PERIOD + BONUS_WITHDRAWAL_DELAY + BONUS_CLAIM_PERIOD;
+
+// An enrollee who never claims, plus a late-tier unstake leaving 40k in the vault
+fn pool_with_unclaimed_bonus() -> TestPool {
+    let mut test = TestPool::started();
+    let enrollee = test.add_staker(200_000 * QST);
+    let leaver = test.add_staker(200_000 * QST);
+    test.stake(&enrollee, 200_000 * QST).unwrap();
+    test.stake(&leaver, 200_000 * QST).unwrap();
+    test.enroll(&enrollee).unwrap();
+
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+    test
+}
+
+#[test]
+fn sweeps_unclaimed_bonus_to_the_fee_recipient() {
+    let mut test = pool_with_unclaimed_bonus();
+    test.warp_to(CLAIM_DEADLINE);
+
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+
+    assert_eq!(test.token_balance(&fee_recipient_token_account), 40_000 * QST);
+    assert_eq!(test.token_balance(&test.pool_token_account), 200_000 * QST);
+    let pool = test.pool();
+    assert_eq!(pool.penalty_vault_amount, 0);
+    assert_eq!(pool.last_admin_action, CLAIM_DEADLINE);
+    assert_eq!(test.events().len(), 1);
+
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::NoDustToCollect))
+    );
+}
+
+#[test]
+fn separates_rounding_dust_from_unclaimed_bonus() {
+    // Three equal enrollees split 40k: each share is floored and one base unit is lost
+    let mut test = TestPool::started();
+    let enrollees: Vec<_> = (0..3).map(|_| test.add_staker(200_000 * QST)).collect();
+    let leaver = test.add_staker(200_000 * QST);
+    for staker in enrollees.iter().chain([&leaver]) {
+        test.stake(staker, 200_000 * QST).unwrap();
+    }
+    for staker in &enrollees {
+        test.enroll(staker).unwrap();
+    }
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+
+    let share = 40_000 * QST / 3;
+    test.warp_to(CLAIM_DEADLINE - BONUS_CLAIM_PERIOD);
+    test.withdraw_bonus(&enrollees[0]).unwrap();
+    test.withdraw_bonus(&enrollees[1]).unwrap();
+    let pool = test.pool();
+    assert_eq!(pool.penalty_vault_amount, 40_000 * QST - 2 * share);
+    assert_eq!(pool.rounding_dust, 1);
+
+    test.warp_to(CLAIM_DEADLINE);
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+
+    let Some(QstEvent::DustCollected(event)) = decode_event(&test.events()[0]) else {
+        panic!("expected DustCollected");
+    };
+    assert_eq!(event.amount, share + 1);
+    assert_eq!(event.rounding_dust, 1);
+    assert_eq!(event.unclaimed_bonus, share);
+    assert_eq!(test.pool().rounding_dust, 0);
+
+    // The swept share is forfeited and the late claim is refused
+    assert_eq!(test.withdraw_bonus(&enrollees[2]), Err(error(ErrorCode::UnclaimedBonusSwept)));
+    assert!(!test.stake_account(&enrollees[2].wallet).unwrap().bonus_claimed);
+}
+
+#[test]
+fn a_vault_with_no_enrolled_weight_is_all_unclaimed_bonus() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(200_000 * QST);
+    let leaver = test.add_staker(200_000 * QST);
+    test.stake(&staker, 200_000 * QST).unwrap();
+    test.stake(&leaver, 200_000 * QST).unwrap();
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+
+    // The claim period still runs from the enrollment deadline, as if someone had enrolled
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::BonusClaimPeriodNotExpired))
+    );
+
+    // Nothing was enrolled, so no share was floored
+    test.warp_to(test.pool().bonus_claim_deadline());
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+    let Some(QstEvent::DustCollected(event)) = decode_event(&test.events()[0]) else {
+        panic!("expected DustCollected");
+    };
+    assert_eq!(event.amount, 40_000 * QST);
+    assert_eq!(event.rounding_dust, 0);
+    assert_eq!(event.unclaimed_bonus, 40_000 * QST);
+    let pool = test.pool();
+    assert_eq!((pool.bonus_snapshot_vault, pool.bonus_snapshot_weight), (40_000 * QST, 0));
+}
+
+#[test]
+fn waits_for_the_claim_period() {
+    let mut test = pool_with_unclaimed_bonus();
+    test.warp_to(CLAIM_DEADLINE - 1);
+
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::BonusClaimPeriodNotExpired))
+    );
+}
+
+#[test]
+fn rejects_non_admin() {
+    let mut test = pool_with_unclaimed_bonus();
+    test.warp_to(CLAIM_DEADLINE);
+
+    let intruder = test.add_staker(0);
+    let fee_recipient_token_account = test.fee_recipient_token_account;
+    assert_eq!(
+        test.collect_dust(&intruder.wallet, &fee_recipient_token_account),
+        Err(error(ErrorCode::Unauthorized))
+    );
+}
+
+#[test]
+fn only_pays_the_fee_recipient() {
+    let mut test = pool_with_unclaimed_bonus();
+    test.warp_to(CLAIM_DEADLINE);
+
+    let admin = test.admin;
+    let other = test.add_staker(0);
+    assert_eq!(
+        test.collect_dust(&admin, &other.token_account),
+        Err(error(ErrorCode::InvalidFeeRecipient))
+    );
+    assert_eq!(test.pool().penalty_vault_amount, 40_000 * QST);
+}
+
+#[test]
+fn nothing_to_collect_without_penalties() {
+    let mut test = TestPool::started();
+    let enrollee = test.add_staker(200_000 * QST);
+    test.stake(&enrollee, 200_000 * QST).unwrap();
+    test.enroll(&enrollee).unwrap();
+    test.warp_to(CLAIM_DEADLINE);
+
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::NoDustToCollect))
+    );
+}
+
+#[test]
+fn admin_rotates_the_fee_recipient() {
+    let mut test = pool_with_unclaimed_bonus();
+    test.warp_to(CLAIM_DEADLINE);
+    let admin = test.admin;
+    let treasury = test.add_staker(0);
+
+    test.process(instructions::set_fee_recipient(&PROGRAM_ID, &admin, &treasury.wallet), &[admin])
+        .unwrap();
+    let pool = test.pool();
+    assert_eq!(pool.fee_recipient, treasury.wallet);
+    assert_eq!(pool.last_admin_action, CLAIM_DEADLINE);
+    assert_eq!(test.events().len(), 1);
+
+    let old_recipient_token_account = test.fee_recipient_token_account;
+    assert_eq!(
+        test.collect_dust(&admin, &old_recipient_token_account),
+        Err(error(ErrorCode::InvalidFeeRecipient))
+    );
+    test.collect_dust(&admin, &treasury.token_account).unwrap();
+    assert_eq!(test.token_balance(&treasury.token_account), 40_000 * QST);
+}
+
+#[test]
+fn fee_recipient_rotation_is_admin_only() {
+    let mut test = TestPool::started();
+    let intruder = test.add_staker(0).wallet;
+    assert_eq!(
+        test.process(instructions::set_fee_recipient(&PROGRAM_ID, &intruder, &intruder), &[intruder]),
+        Err(error(ErrorCode::Unauthorized))
+    );
+
+    let admin = test.admin;
+    assert_eq!(
+        test.process(instructions::set_fee_recipient(&PROGRAM_ID, &admin, &Pubkey::default()), &[admin]),
+        Err(error(ErrorCode::InvalidFeeRecipient))
+    );
+}
@@ -0,0 +1,555 @@
+// Harness for the integration tests: a solana-program-test bank with the real SPL Token
+// program, time set through the Clock sysvar. The staking program is the SBF build from
+// target/deploy (or $SBF_OUT_DIR) when one exists, so `anchor build` followed by `cargo test`
+// runs the deployed binary under the runtime's compute, CPI and realloc checks. Without a
+// build it runs as a host-compiled builtin in the same bank. emit_cpi! events are captured
+// from the builtin only; against the SBF build `events()` stays empty.
+#![allow(dead_code)]
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Once;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::{bpf_loader, bpf_loader_upgradeable, system_program};
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize};
+use anchor_spl::token::spl_token;
+use qst_staking_client::instructions;
+use qst_staking_client::pda::{
+    find_program_data_address, find_reward_vault_address, find_stake_account_address, find_staking_pool_address,
+};
+use qst_staking_mainnet::{StakeAccount, StakingPool, ID as PROGRAM_ID};
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::{Account, AccountSharedData};
+use solana_sdk::bpf_loader_upgradeable::UpgradeableLoaderState;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use tokio::runtime::Runtime;
+
+pub const QST: u64 = 1_000_000;
+pub const DAY: i64 = 24 * 60 * 60;
+pub const START_TIME: i64 = 1_700_000_000;
+
+const SOL: u64 = 1_000_000_000;
+
+// The SBF build, if one was produced by `cargo build-sbf` / `anchor build`
+fn sbf_program() -> Option<Vec<u8>> {
+    let dir = std::env::var_os("SBF_OUT_DIR")
+        .or_else(|| std::env::var_os("BPF_OUT_DIR"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy")));
+    std::fs::read(dir.join("qst_staking_mainnet.so")).ok()
+}
+
+// Anchor's entry ties the account slice to the AccountInfo lifetime, which the builtin
+// processor signature can't express
+fn builtin_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    qst_staking_mainnet::entry(program_id, accounts, data)
+}
+
+thread_local! {
+    static EVENTS: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+// The 1.18 banks interface returns no inner instructions, so emit_cpi! payloads are taken from
+// the builtin's self-invocations on their way into program-test's syscall stubs. The bank runs
+// on the test's current-thread runtime, so a thread-local keeps parallel tests apart.
+struct EventRecorder(Box<dyn SyscallStubs>);
+
+impl SyscallStubs for EventRecorder {
+    fn sol_log(&self, message: &str) {
+        self.0.sol_log(message)
+    }
+
+    fn sol_log_compute_units(&self) {
+        self.0.sol_log_compute_units()
+    }
+
+    fn sol_remaining_compute_units(&self) -> u64 {
+        self.0.sol_remaining_compute_units()
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if instruction.program_id == PROGRAM_ID && instruction.data.starts_with(&EVENT_IX_TAG_LE) {
+            EVENTS.with(|events| events.borrow_mut().push(instruction.data.clone()));
+        }
+        self.0.sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_clock_sysvar(var_addr)
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_fees_sysvar(var_addr)
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_rent_sysvar(var_addr)
+    }
+
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_epoch_rewards_sysvar(var_addr)
+    }
+
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        self.0.sol_get_last_restart_slot(var_addr)
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.0.sol_get_return_data()
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.0.sol_set_return_data(data)
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        self.0.sol_log_data(fields)
+    }
+
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.0.sol_get_processed_sibling_instruction(index)
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        self.0.sol_get_stack_height()
+    }
+}
+
+// Wraps the stubs program-test installs when its first bank starts
+fn record_events() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let stubs = program_stubs::set_syscall_stubs(Box::new(NoStubs));
+        program_stubs::set_syscall_stubs(Box::new(EventRecorder(stubs)));
+    });
+}
+
+// Placeholder while the installed stubs are moved into the recorder
+struct NoStubs;
+
+impl SyscallStubs for NoStubs {}
+
+// The error a test sees: the program's (or a CPI's) InstructionError; anything the runtime
+// rejects before running the program is a harness bug
+fn program_error(error: TransactionError) -> ProgramError {
+    match error {
+        TransactionError::InstructionError(_, error) => {
+            ProgramError::try_from(error.clone()).unwrap_or_else(|_| panic!("unmapped instruction error {error:?}"))
+        }
+        error => panic!("transaction rejected: {error:?}"),
+    }
+}
+
+pub struct Staker {
+    pub wallet: Pubkey,
+    pub token_account: Pubkey,
+}
+
+// A pool initialized by the program's upgrade authority (also the admin) with a funded QST mint
+pub struct TestPool {
+    runtime: Runtime,
+    context: ProgramTestContext,
+    keypairs: HashMap<Pubkey, Keypair>,
+    events: Vec<Vec<u8>>,
+    return_data: Option<Vec<u8>>,
+    pub admin: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub mint: Pubkey,
+    pub pool_token_account: Pubkey,
+    pub fee_recipient_token_account: Pubkey,
+}
+
+impl TestPool {
+    pub fn new() -> Self {
+        let mut test = Self::uninitialized();
+        let (admin, mint, fee_recipient) = (test.admin, test.mint, test.fee_recipient);
+        test.process(instructions::initialize(&PROGRAM_ID, &admin, &mint, &admin, &fee_recipient), &[admin])
+            .expect("initialize");
+        test
+    }
+
+    // Mint, token accounts and the program's ProgramData (upgrade authority: admin), no pool yet
+    pub fn uninitialized() -> Self {
+        let mut program_test = ProgramTest::default();
+        match sbf_program() {
+            Some(elf) => program_test.add_account(
+                PROGRAM_ID,
+                Account {
+                    lamports: Rent::default().minimum_balance(elf.len()).max(1),
+                    data: elf,
+                    owner: bpf_loader::ID,
+                    executable: true,
+                    rent_epoch: 0,
+                },
+            ),
+            None => program_test.add_program("qst_staking_mainnet", PROGRAM_ID, processor!(builtin_entry)),
+        }
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let context = runtime.block_on(program_test.start_with_context());
+        record_events();
+        let mut test = Self {
+            runtime,
+            context,
+            keypairs: HashMap::new(),
+            events: Vec::new(),
+            return_data: None,
+            admin: Pubkey::default(),
+            fee_recipient: Pubkey::new_unique(),
+            mint: Pubkey::default(),
+            pool_token_account: Pubkey::default(),
+            fee_recipient_token_account: Pubkey::default(),
+        };
+        test.warp_to(START_TIME);
+
+        test.admin = test.new_key();
+        test.add_wallet(test.admin);
+        test.set_upgrade_authority(Some(test.admin));
+
+        test.mint = test.new_key();
+        let mint = spl_token::state::Mint {
+            mint_authority: COption::None,
+            supply: u64::MAX / 2,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        test.add_packed(test.mint, spl_token::ID, mint);
+        let (pool, _) = find_staking_pool_address(&PROGRAM_ID);
+        test.pool_token_account = test.add_token_account(pool, 0);
+        test.fee_recipient_token_account = test.add_token_account(test.fee_recipient, 0);
+        test
+    }
+
+    // A pool whose stake window opened at START_TIME
+    pub fn started() -> Self {
+        let mut test = Self::new();
+        test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+            .expect("start_stake_window");
+        test
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock().unix_timestamp
+    }
+
+    // Moves the Clock sysvar's unix_timestamp; the slot stays where the bank is
+    pub fn warp_to(&mut self, unix_timestamp: i64) {
+        let clock = Clock {
+            unix_timestamp,
+            ..self.clock()
+        };
+        self.context.set_sysvar(&clock);
+    }
+
+    // A fresh keypair the harness can sign with
+    pub fn new_key(&mut self) -> Pubkey {
+        let keypair = Keypair::new();
+        let key = keypair.pubkey();
+        self.keypairs.insert(key, keypair);
+        key
+    }
+
+    // A fresh wallet with SOL for rent and `qst` base units in a token account
+    pub fn add_staker(&mut self, qst: u64) -> Staker {
+        let wallet = self.new_key();
+        self.add_wallet(wallet);
+        let token_account = self.add_token_account(wallet, qst);
+        Staker { wallet, token_account }
+    }
+
+    pub fn add_token_account(&mut self, owner: Pubkey, amount: u64) -> Pubkey {
+        let key = self.new_key();
+        let account = spl_token::state::Account {
+            mint: self.mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        self.add_packed(key, spl_token::ID, account);
+        key
+    }
+
+    // Sends one instruction in its own transaction. Each transaction has a fresh fee payer, so
+    // repeating an identical instruction never reuses a signature.
+    pub fn process(&mut self, instruction: Instruction, signers: &[Pubkey]) -> Result<(), ProgramError> {
+        let payer = Keypair::new();
+        self.add_wallet(payer.pubkey());
+        let blockhash = self
+            .runtime
+            .block_on(self.context.banks_client.get_latest_blockhash())
+            .expect("blockhash");
+        let mut keypairs = vec![&payer];
+        for signer in signers {
+            let required = instruction
+                .accounts
+                .iter()
+                .any(|meta| meta.pubkey == *signer && meta.is_signer);
+            if required {
+                keypairs.push(&self.keypairs[signer]);
+            }
+        }
+        let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+        transaction
+            .try_sign(&keypairs, blockhash)
+            .map_err(|_| ProgramError::MissingRequiredSignature)?;
+
+        EVENTS.with(|events| events.borrow_mut().clear());
+        let processed = self
+            .runtime
+            .block_on(self.context.banks_client.process_transaction_with_metadata(transaction))
+            .expect("process_transaction");
+        processed.result.map_err(program_error)?;
+
+        self.events = EVENTS.with(|events| events.take());
+        self.return_data = processed
+            .metadata
+            .and_then(|metadata| metadata.return_data)
+            .filter(|return_data| return_data.program_id == PROGRAM_ID)
+            .map(|return_data| return_data.data);
+        Ok(())
+    }
+
+    // Runs a view instruction and decodes its return data
+    pub fn view<T: AnchorDeserialize>(&mut self, instruction: Instruction) -> Result<T, ProgramError> {
+        self.process(instruction, &[])?;
+        let data = self.return_data.take().expect("no return data");
+        Ok(T::try_from_slice(&data).unwrap())
+    }
+
+    // emit_cpi! payloads of the last successful instruction (tag stripped)
+    pub fn events(&self) -> Vec<Vec<u8>> {
+        self.events
+            .iter()
+            .map(|data| data[EVENT_IX_TAG_LE.len()..].to_vec())
+            .collect()
+    }
+
+    pub fn pool(&self) -> StakingPool {
+        let (pool, _) = find_staking_pool_address(&PROGRAM_ID);
+        self.decode(&pool).expect("staking pool")
+    }
+
+    pub fn stake_account(&self, user: &Pubkey) -> Option<StakeAccount> {
+        let (address, _) = find_stake_account_address(&PROGRAM_ID, user);
+        self.decode(&address)
+    }
+
+    pub fn token_balance(&self, token_account: &Pubkey) -> u64 {
+        let account = self.account(token_account).expect("token account");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    // Edits the pool account directly, to simulate corrupted bookkeeping
+    pub fn update_pool(&mut self, update: impl FnOnce(&mut StakingPool)) {
+        let (address, _) = find_staking_pool_address(&PROGRAM_ID);
+        let mut pool = self.pool();
+        update(&mut pool);
+        let mut account = self.account(&address).unwrap();
+        pool.try_serialize(&mut account.data.as_mut_slice()).unwrap();
+        self.set_account(address, account);
+    }
+
+    // Overwrites a token balance directly, e.g. to simulate a drained vault
+    pub fn set_token_balance(&mut self, token_account: &Pubkey, amount: u64) {
+        let mut account = self.account(token_account).unwrap();
+        let mut state = spl_token::state::Account::unpack(&account.data).unwrap();
+        state.amount = amount;
+        spl_token::state::Account::pack(state, &mut account.data).unwrap();
+        self.set_account(*token_account, account);
+    }
+
+    pub fn stake(&mut self, staker: &Staker, amount: u64) -> Result<(), ProgramError> {
+        let instruction = instructions::stake_tokens(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &self.pool_token_account,
+            amount,
+        );
+        self.process(instruction, &[staker.wallet])
+    }
+
+    pub fn stake_and_enroll(&mut self, staker: &Staker, amount: u64) -> Result<(), ProgramError> {
+        let instruction = instructions::stake_and_enroll(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &self.pool_token_account,
+            amount,
+        );
+        self.process(instruction, &[staker.wallet])
+    }
+
+    pub fn enroll(&mut self, staker: &Staker) -> Result<(), ProgramError> {
+        self.process(instructions::enroll_in_bonus(&PROGRAM_ID, &staker.wallet), &[staker.wallet])
+    }
+
+    pub fn unstake(&mut self, staker: &Staker, amount: u64, max_penalty: u64) -> Result<(), ProgramError> {
+        let instruction = instructions::unstake_tokens(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &self.pool_token_account,
+            amount,
+            max_penalty,
+        );
+        self.process(instruction, &[staker.wallet])
+    }
+
+    pub fn withdraw_all(&mut self, staker: &Staker) -> Result<(), ProgramError> {
+        let instruction = instructions::withdraw_all(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &self.pool_token_account,
+        );
+        self.process(instruction, &[staker.wallet])
+    }
+
+    pub fn withdraw_bonus(&mut self, staker: &Staker) -> Result<(), ProgramError> {
+        let instruction = instructions::withdraw_bonus(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &self.pool_token_account,
+        );
+        self.process(instruction, &[staker.wallet])
+    }
+
+    pub fn withdraw_redistributed_bonus(&mut self, staker: &Staker) -> Result<(), ProgramError> {
+        let instruction = instructions::withdraw_redistributed_bonus(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &self.pool_token_account,
+        );
+        self.process(instruction, &[staker.wallet])
+    }
+
+    pub fn collect_dust(&mut self, admin: &Pubkey, fee_recipient_token_account: &Pubkey) -> Result<(), ProgramError> {
+        let instruction =
+            instructions::collect_dust(&PROGRAM_ID, admin, &self.pool_token_account, fee_recipient_token_account);
+        self.process(instruction, &[*admin])
+    }
+
+    // Funds come from a fresh admin token account holding exactly `amount`
+    pub fn fund_rewards(&mut self, amount: u64, start: i64, end: i64) -> Result<(), ProgramError> {
+        let admin = self.admin;
+        let admin_token_account = self.add_token_account(admin, amount);
+        let instruction =
+            instructions::fund_rewards(&PROGRAM_ID, &admin, &admin_token_account, &self.mint, amount, start, end);
+        self.process(instruction, &[admin])
+    }
+
+    pub fn claim_rewards(&mut self, staker: &Staker) -> Result<(), ProgramError> {
+        let instruction = instructions::claim_rewards(&PROGRAM_ID, &staker.wallet, &staker.token_account);
+        self.process(instruction, &[staker.wallet])
+    }
+
+    pub fn reward_vault_balance(&self) -> u64 {
+        self.token_balance(&find_reward_vault_address(&PROGRAM_ID).0)
+    }
+
+    // The program's ProgramData account as the upgradeable loader writes it
+    pub fn set_upgrade_authority(&mut self, authority: Option<Pubkey>) {
+        let key = find_program_data_address(&PROGRAM_ID).0;
+        let state = UpgradeableLoaderState::ProgramData {
+            slot: 0,
+            upgrade_authority_address: authority,
+        };
+        let data = bincode::serialize(&state).unwrap();
+        self.set_account(
+            key,
+            Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: bpf_loader_upgradeable::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    fn clock(&self) -> Clock {
+        let mut banks_client = self.context.banks_client.clone();
+        self.runtime.block_on(banks_client.get_sysvar::<Clock>()).expect("clock")
+    }
+
+    fn account(&self, address: &Pubkey) -> Option<Account> {
+        let mut banks_client = self.context.banks_client.clone();
+        self.runtime.block_on(banks_client.get_account(*address)).expect("get_account")
+    }
+
+    fn set_account(&mut self, address: Pubkey, account: Account) {
+        self.context.set_account(&address, &AccountSharedData::from(account));
+    }
+
+    fn decode<T: AccountDeserialize>(&self, address: &Pubkey) -> Option<T> {
+        let account = self.account(address).filter(|account| !account.data.is_empty())?;
+        Some(T::try_deserialize(&mut account.data.as_slice()).unwrap())
+    }
+
+    fn add_wallet(&mut self, key: Pubkey) {
+        self.set_account(
+            key,
+            Account {
+                lamports: 10 * SOL,
+                data: Vec::new(),
+                owner: system_program::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    fn add_packed<T: Pack>(&mut self, key: Pubkey, owner: Pubkey, state: T) {
+        let mut data = vec![0u8; T::LEN];
+        T::pack(state, &mut data).unwrap();
+        self.set_account(
+            key,
+            Account {
+                lamports: Rent::default().minimum_balance(T::LEN),
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+}
+
+// Anchor custom error for a program ErrorCode, as surfaced by the runtime
+pub fn error(code: qst_staking_mainnet::ErrorCode) -> ProgramError {
+    ProgramError::Custom(code.into())
+}
@@ -0,0 +1,66 @@
+// initialize is gated on the program's upgrade authority rather than a hard-coded wallet
+mod common;
+
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use common::{error, TestPool};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{ErrorCode, ID as PROGRAM_ID};
+
+fn initialize(test: &mut TestPool, payer: Pubkey, fee_recipient: Pubkey) -> Result<(), ProgramError> {
+    let instruction = instructions::initialize(&PROGRAM_ID, &payer, &test.mint, &payer, &fee_recipient);
+    test.process(instruction, &[payer])
+}
+
+#[test]
+fn upgrade_authority_initializes() {
+    let mut test = TestPool::uninitialized();
+    let (admin, fee_recipient) = (test.admin, test.fee_recipient);
+    initialize(&mut test, admin, fee_recipient).unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.authority, admin);
+    assert_eq!(pool.fee_recipient, fee_recipient);
+    assert_eq!(pool.event_seq, 1);
+}
+
+#[test]
+fn rejects_other_signers() {
+    let mut test = TestPool::uninitialized();
+    let intruder = test.add_staker(0).wallet;
+    let fee_recipient = test.fee_recipient;
+
+    assert_eq!(initialize(&mut test, intruder, fee_recipient), Err(error(ErrorCode::Unauthorized)));
+}
+
+#[test]
+fn follows_upgrade_authority_rotation() {
+    let mut test = TestPool::uninitialized();
+    let new_authority = test.add_staker(0).wallet;
+    test.set_upgrade_authority(Some(new_authority));
+    let (admin, fee_recipient) = (test.admin, test.fee_recipient);
+
+    assert_eq!(initialize(&mut test, admin, fee_recipient), Err(error(ErrorCode::Unauthorized)));
+    initialize(&mut test, new_authority, fee_recipient).unwrap();
+    assert_eq!(test.pool().authority, new_authority);
+}
+
+#[test]
+fn rejects_immutable_program() {
+    let mut test = TestPool::uninitialized();
+    test.set_upgrade_authority(None);
+    let (admin, fee_recipient) = (test.admin, test.fee_recipient);
+
+    assert_eq!(initialize(&mut test, admin, fee_recipient), Err(error(ErrorCode::Unauthorized)));
+}
+
+#[test]
+fn requires_a_fee_recipient() {
+    let mut test = TestPool::uninitialized();
+    let admin = test.admin;
+
+    assert_eq!(
+        initialize(&mut test, admin, Pubkey::default()),
+        Err(error(ErrorCode::InvalidFeeRecipient))
+    );
+}
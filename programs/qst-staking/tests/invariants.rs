@@ -0,0 +1,265 @@
+// Property-based fuzzing of the pool's hand-maintained counters: random sequences of stakes,
+// enrollments, unstakes, withdrawals, bonus claims and dust sweeps across several users and
+// warped times, with the accounting invariants checked after every step.
+mod common;
+
+use common::{Staker, TestPool, DAY, QST, START_TIME};
+use proptest::prelude::*;
+use qst_staking_mainnet::{
+    BONUS_ENROLLMENT_PERIOD, BONUS_LOCK_PERIOD, BONUS_WITHDRAWAL_DELAY, MINIMUM_STAKE_AMOUNT,
+    PRINCIPAL_LOCK_PERIOD, STAKE_WINDOW_PERIOD,
+};
+
+const USERS: usize = 4;
+const FUNDING: u64 = 2_000_000 * QST;
+
+#[derive(Clone, Debug)]
+enum Op {
+    Stake { user: usize, units: u64 },
+    Enroll { user: usize },
+    // Unstake `percent` of the position, accepting any penalty
+    Unstake { user: usize, percent: u64 },
+    WithdrawAll { user: usize },
+    WithdrawBonus { user: usize },
+    CollectDust,
+    Warp { seconds: i64 },
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    let user = 0..USERS;
+    prop_oneof![
+        3 => (user.clone(), 1..=5u64).prop_map(|(user, units)| Op::Stake { user, units }),
+        2 => user.clone().prop_map(|user| Op::Enroll { user }),
+        2 => (user.clone(), 1..=100u64).prop_map(|(user, percent)| Op::Unstake { user, percent }),
+        2 => user.clone().prop_map(|user| Op::WithdrawAll { user }),
+        2 => user.prop_map(|user| Op::WithdrawBonus { user }),
+        1 => Just(Op::CollectDust),
+        // Short hops keep early-window activity likely; long ones reach unlocks and deadlines
+        2 => (0..DAY).prop_map(|seconds| Op::Warp { seconds }),
+        2 => (0..15 * DAY).prop_map(|seconds| Op::Warp { seconds }),
+    ]
+}
+
+// Token flows observed from balances, independent of the pool's own counters
+#[derive(Default)]
+struct Flows {
+    penalties: u64,
+    bonus_paid: u64,
+    dust_collected: u64,
+}
+
+struct Fuzz {
+    test: TestPool,
+    users: Vec<Staker>,
+    flows: Flows,
+    node_keys: Vec<u32>,
+}
+
+impl Fuzz {
+    fn new() -> Self {
+        let mut test = TestPool::started();
+        let users = (0..USERS).map(|_| test.add_staker(FUNDING)).collect();
+        Self {
+            test,
+            users,
+            flows: Flows::default(),
+            node_keys: vec![0; USERS],
+        }
+    }
+
+    fn apply(&mut self, op: &Op) {
+        let test = &mut self.test;
+        match *op {
+            Op::Stake { user, units } => {
+                let _ = test.stake(&self.users[user], units * MINIMUM_STAKE_AMOUNT);
+            }
+            Op::Enroll { user } => {
+                let _ = test.enroll(&self.users[user]);
+            }
+            Op::Unstake { user, percent } => {
+                let staker = &self.users[user];
+                let staked = test.stake_account(&staker.wallet).map_or(0, |position| position.amount);
+                let amount = staked * percent / 100;
+                let before = test.token_balance(&staker.token_account);
+                if test.unstake(staker, amount, u64::MAX).is_ok() {
+                    let received = test.token_balance(&staker.token_account) - before;
+                    self.flows.penalties += amount - received;
+                }
+            }
+            Op::WithdrawAll { user } => {
+                let _ = test.withdraw_all(&self.users[user]);
+            }
+            Op::WithdrawBonus { user } => {
+                let staker = &self.users[user];
+                let before = test.token_balance(&staker.token_account);
+                if test.withdraw_bonus(staker).is_ok() {
+                    self.flows.bonus_paid += test.token_balance(&staker.token_account) - before;
+                }
+            }
+            Op::CollectDust => {
+                let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+                let before = test.token_balance(&fee_recipient_token_account);
+                if test.collect_dust(&admin, &fee_recipient_token_account).is_ok() {
+                    self.flows.dust_collected += test.token_balance(&fee_recipient_token_account) - before;
+                }
+            }
+            Op::Warp { seconds } => {
+                let now = test.now();
+                test.warp_to(now + seconds);
+            }
+        }
+    }
+
+    fn check_invariants(&mut self) -> Result<(), TestCaseError> {
+        let test = &self.test;
+        let pool = test.pool();
+        let vault = test.token_balance(&test.pool_token_account);
+        let positions: Vec<_> = self
+            .users
+            .iter()
+            .map(|staker| test.stake_account(&staker.wallet))
+            .collect();
+
+        // Solvency: the vault covers every principal plus the unclaimed penalties
+        prop_assert!(
+            vault >= pool.total_staked + pool.penalty_vault_amount,
+            "vault {} < total_staked {} + penalty_vault {}",
+            vault,
+            pool.total_staked,
+            pool.penalty_vault_amount
+        );
+
+        // Counters match the positions they summarise
+        let staked: u64 = positions.iter().flatten().map(|position| position.amount).sum();
+        prop_assert_eq!(pool.total_staked, staked);
+        let enrolled: u64 = positions
+            .iter()
+            .flatten()
+            .filter(|position| position.in_bonus_pool())
+            .map(|position| position.enrolled_stake)
+            .sum();
+        prop_assert_eq!(pool.total_enrolled_stake, enrolled);
+
+        // Penalties only leave the vault as bonus or dust, never more than came in
+        let flows = &self.flows;
+        prop_assert!(flows.bonus_paid + flows.dust_collected <= flows.penalties);
+        prop_assert_eq!(
+            pool.penalty_vault_amount,
+            flows.penalties - flows.bonus_paid - flows.dust_collected
+        );
+
+        // Flooring loses less than one base unit per position, and only inside the vault
+        prop_assert!(pool.rounding_dust <= pool.penalty_vault_amount);
+        prop_assert!(pool.rounding_dust < USERS as u64);
+
+        // Tokens are conserved between users, the vault and the fee recipient
+        let held: u64 = self.users.iter().map(|staker| test.token_balance(&staker.token_account)).sum();
+        let dev = test.token_balance(&test.fee_recipient_token_account);
+        prop_assert_eq!(held + vault + dev, FUNDING * USERS as u64);
+
+        // Node keys are permanent
+        for (keys, position) in self.node_keys.iter_mut().zip(&positions) {
+            let current = position.as_ref().map_or(0, |position| position.node_keys_earned);
+            prop_assert!(current >= *keys);
+            *keys = current;
+        }
+        Ok(())
+    }
+}
+
+// One user's path through a full pool lifecycle: stake during the enrollment period, then
+// either enroll and claim the bonus, or leave early (or at unlock) by unstaking
+#[derive(Clone, Debug)]
+struct Plan {
+    units: u64,
+    stake_at: i64,
+    enroll: bool,
+    exit_after: i64,
+    claim_after: i64,
+}
+
+fn plan() -> impl Strategy<Value = Plan> {
+    (1..=5u64, 0..BONUS_ENROLLMENT_PERIOD, any::<bool>(), 1..20 * DAY, 0..30 * DAY).prop_map(
+        |(units, stake_at, enroll, exit_after, claim_after)| Plan {
+            units,
+            stake_at,
+            enroll,
+            exit_after,
+            claim_after,
+        },
+    )
+}
+
+// Every user's actions, ordered by time
+fn timeline(plans: &[Plan]) -> Vec<(i64, Op)> {
+    let window_end = START_TIME + STAKE_WINDOW_PERIOD;
+    let latest_bonus_unlock = plans
+        .iter()
+        .filter(|plan| plan.enroll)
+        .map(|plan| START_TIME + plan.stake_at + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD)
+        .max()
+        .unwrap_or(0);
+
+    let mut steps = Vec::new();
+    for (user, plan) in plans.iter().enumerate() {
+        let staked_at = START_TIME + plan.stake_at;
+        steps.push((staked_at, Op::Stake { user, units: plan.units }));
+        if plan.enroll {
+            let bonus_unlock = staked_at + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD;
+            steps.push((staked_at, Op::Enroll { user }));
+            steps.push((bonus_unlock + plan.exit_after, Op::WithdrawAll { user }));
+            let claim_at = latest_bonus_unlock + BONUS_WITHDRAWAL_DELAY + plan.claim_after;
+            steps.push((claim_at.max(bonus_unlock + plan.exit_after), Op::WithdrawBonus { user }));
+        } else {
+            steps.push((window_end + plan.exit_after, Op::Unstake { user, percent: 100 }));
+        }
+    }
+    steps.sort_by_key(|(time, _)| *time);
+    steps
+}
+
+proptest! {
+    // Every case boots its own bank, so this stays well below the proptest default
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn pool_accounting_stays_consistent(ops in prop::collection::vec(op(), 1..120)) {
+        let mut fuzz = Fuzz::new();
+        for op in &ops {
+            fuzz.apply(op);
+            fuzz.check_invariants()?;
+        }
+    }
+
+    #[test]
+    fn full_lifecycle_pays_out_penalties(plans in prop::collection::vec(plan(), USERS)) {
+        let mut fuzz = Fuzz::new();
+        for (time, op) in timeline(&plans) {
+            fuzz.test.warp_to(time);
+            fuzz.apply(&op);
+            fuzz.check_invariants()?;
+        }
+
+        // Unstakes inside the blocked tier are rejected; whoever is left exits at unlock
+        let last_principal_unlock = START_TIME + BONUS_ENROLLMENT_PERIOD + PRINCIPAL_LOCK_PERIOD;
+        let now = fuzz.test.now();
+        fuzz.test.warp_to(now.max(last_principal_unlock));
+        for (user, plan) in plans.iter().enumerate() {
+            if !plan.enroll {
+                fuzz.apply(&Op::WithdrawAll { user });
+                fuzz.check_invariants()?;
+            }
+        }
+
+        let pool = fuzz.test.pool();
+        prop_assert_eq!(pool.total_staked, 0);
+        prop_assert_eq!(pool.total_enrolled_stake, 0);
+        if plans.iter().any(|plan| plan.enroll) {
+            // Enrollees shared the penalties, leaving only rounding dust behind
+            prop_assert!(pool.penalty_vault_amount < USERS as u64);
+            prop_assert_eq!(pool.rounding_dust, pool.penalty_vault_amount);
+        }
+        let vault = fuzz.test.token_balance(&fuzz.test.pool_token_account);
+        prop_assert_eq!(vault, pool.penalty_vault_amount);
+    }
+}
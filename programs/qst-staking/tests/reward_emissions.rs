@@ -0,0 +1,240 @@
+mod common;
+
+use anchor_lang::solana_program::pubkey::Pubkey;
+use common::{error, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::events::{decode_event, QstEvent};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, StakeInfo, BONUS_LOCK_PERIOD, ID as PROGRAM_ID, PRINCIPAL_LOCK_PERIOD, STAKE_WINDOW_PERIOD,
+};
+
+fn accrued_rewards(test: &mut TestPool, wallet: &Pubkey) -> u64 {
+    let info: StakeInfo = test.view(instructions::get_stake_info(&PROGRAM_ID, wallet)).unwrap();
+    info.accrued_rewards
+}
+
+#[test]
+fn admin_funds_a_stream() {
+    let mut test = TestPool::started();
+    let seq_before = test.pool().event_seq;
+    test.fund_rewards(30_000 * QST, START_TIME + DAY, START_TIME + 31 * DAY).unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.reward_vault_amount, 30_000 * QST);
+    assert_eq!(pool.reward_stream_amount, 30_000 * QST);
+    assert_eq!((pool.reward_start, pool.reward_end), (START_TIME + DAY, START_TIME + 31 * DAY));
+    assert_eq!(pool.last_admin_action, START_TIME);
+    assert_eq!(pool.event_seq, seq_before + 1);
+    assert_eq!(test.reward_vault_balance(), 30_000 * QST);
+
+    // Emissions are kept apart from principal and penalties
+    assert_eq!(test.token_balance(&test.pool_token_account), 0);
+    assert_eq!(pool.penalty_vault_amount, 0);
+}
+
+#[test]
+fn streams_pro_rata_to_enrolled_stake() {
+    let mut test = TestPool::started();
+    let alice = test.add_staker(200_000 * QST);
+    let bob = test.add_staker(400_000 * QST);
+    let carol = test.add_staker(200_000 * QST);
+    test.stake_and_enroll(&alice, 200_000 * QST).unwrap();
+    test.stake_and_enroll(&bob, 400_000 * QST).unwrap();
+    test.stake(&carol, 200_000 * QST).unwrap();
+    test.fund_rewards(30_000 * QST, START_TIME, START_TIME + 30 * DAY).unwrap();
+
+    test.warp_to(START_TIME + 15 * DAY);
+    assert_eq!(accrued_rewards(&mut test, &alice.wallet), 5_000 * QST);
+    assert_eq!(accrued_rewards(&mut test, &bob.wallet), 10_000 * QST);
+    assert_eq!(accrued_rewards(&mut test, &carol.wallet), 0);
+
+    test.claim_rewards(&alice).unwrap();
+    match decode_event(&test.events()[0]) {
+        Some(QstEvent::RewardsClaimed(event)) => {
+            assert_eq!(event.user, alice.wallet);
+            assert_eq!(event.amount, 5_000 * QST);
+        }
+        _ => panic!("expected RewardsClaimed"),
+    }
+    assert_eq!(test.token_balance(&alice.token_account), 5_000 * QST);
+    assert_eq!(accrued_rewards(&mut test, &alice.wallet), 0);
+
+    // Nothing more is released after the end
+    test.warp_to(START_TIME + 40 * DAY);
+    test.claim_rewards(&alice).unwrap();
+    test.claim_rewards(&bob).unwrap();
+    assert_eq!(test.token_balance(&alice.token_account), 10_000 * QST);
+    assert_eq!(test.token_balance(&bob.token_account), 20_000 * QST);
+    assert_eq!(test.claim_rewards(&carol), Err(error(ErrorCode::NoRewardsToClaim)));
+    assert_eq!(test.pool().reward_vault_amount, 0);
+    assert_eq!(test.reward_vault_balance(), 0);
+}
+
+#[test]
+fn weight_changes_accrue_at_the_old_weight() {
+    let mut test = TestPool::started();
+    let alice = test.add_staker(200_000 * QST);
+    let bob = test.add_staker(200_000 * QST);
+    test.stake_and_enroll(&alice, 200_000 * QST).unwrap();
+    test.fund_rewards(10_000 * QST, START_TIME, START_TIME + 10 * DAY).unwrap();
+
+    // Alice earns the first day alone, then splits the rest with Bob
+    test.warp_to(START_TIME + DAY);
+    test.stake_and_enroll(&bob, 200_000 * QST).unwrap();
+
+    test.warp_to(START_TIME + 10 * DAY);
+    assert_eq!(accrued_rewards(&mut test, &alice.wallet), 5_500 * QST);
+    assert_eq!(accrued_rewards(&mut test, &bob.wallet), 4_500 * QST);
+}
+
+#[test]
+fn exiting_keeps_accrued_emissions() {
+    let mut test = TestPool::started();
+    let alice = test.add_staker(200_000 * QST);
+    let bob = test.add_staker(200_000 * QST);
+    test.stake_and_enroll(&alice, 200_000 * QST).unwrap();
+    test.stake_and_enroll(&bob, 200_000 * QST).unwrap();
+    test.fund_rewards(20_000 * QST, START_TIME, START_TIME + 20 * DAY).unwrap();
+
+    // Bob leaves after the window with half the stream released; only his bonus share is forfeited
+    test.warp_to(START_TIME + STAKE_WINDOW_PERIOD + DAY);
+    let exit = instructions::exit_enrolled(&PROGRAM_ID, &bob.wallet, &bob.token_account, &test.pool_token_account);
+    test.process(exit, &[bob.wallet]).unwrap();
+    assert_eq!(test.stake_account(&bob.wallet).unwrap().rewards_accrued, 5_000 * QST);
+
+    test.warp_to(START_TIME + 20 * DAY);
+    assert_eq!(accrued_rewards(&mut test, &alice.wallet), 15_000 * QST);
+    assert_eq!(accrued_rewards(&mut test, &bob.wallet), 5_000 * QST);
+}
+
+#[test]
+fn emissions_with_nothing_enrolled_roll_into_the_next_stream() {
+    let mut test = TestPool::started();
+    test.fund_rewards(10_000 * QST, START_TIME, START_TIME + DAY).unwrap();
+
+    test.warp_to(START_TIME + DAY);
+    test.fund_rewards(5_000 * QST, START_TIME + DAY, START_TIME + 11 * DAY).unwrap();
+    let pool = test.pool();
+    assert_eq!(pool.reward_stream_amount, 15_000 * QST);
+    assert_eq!(pool.reward_unallocated, 0);
+    match decode_event(&test.events()[0]) {
+        Some(QstEvent::RewardsFunded(event)) => {
+            assert_eq!(event.amount, 5_000 * QST);
+            assert_eq!(event.stream_amount, 15_000 * QST);
+        }
+        _ => panic!("expected RewardsFunded"),
+    }
+
+    let alice = test.add_staker(200_000 * QST);
+    test.stake_and_enroll(&alice, 200_000 * QST).unwrap();
+    test.warp_to(START_TIME + 11 * DAY);
+    test.claim_rewards(&alice).unwrap();
+    assert_eq!(test.token_balance(&alice.token_account), 15_000 * QST);
+    assert_eq!(test.reward_vault_balance(), 0);
+}
+
+#[test]
+fn withdrawing_the_enrolled_principal_stops_emissions() {
+    let mut test = TestPool::started();
+    let alice = test.add_staker(200_000 * QST);
+    let bob = test.add_staker(200_000 * QST);
+    test.stake_and_enroll(&alice, 200_000 * QST).unwrap();
+    test.stake_and_enroll(&bob, 200_000 * QST).unwrap();
+    test.fund_rewards(100_000 * QST, START_TIME, START_TIME + 100 * DAY).unwrap();
+
+    // Alice takes her principal at the bonus unlock but leaves the bonus unclaimed
+    let bonus_unlock = START_TIME + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD;
+    let earned_each = (bonus_unlock - START_TIME) as u64 / DAY as u64 * 500 * QST;
+    test.warp_to(bonus_unlock);
+    test.withdraw_all(&alice).unwrap();
+    let pool = test.pool();
+    assert_eq!(pool.total_reward_weight, 200_000 * QST);
+    assert_eq!(pool.total_enrolled_stake, 400_000 * QST);
+
+    test.warp_to(bonus_unlock + 10 * DAY);
+    assert_eq!(accrued_rewards(&mut test, &alice.wallet), earned_each);
+    assert_eq!(accrued_rewards(&mut test, &bob.wallet), earned_each + 10_000 * QST);
+}
+
+#[test]
+fn admin_reclaims_unallocated_rewards_after_the_stream() {
+    let mut test = TestPool::started();
+    test.fund_rewards(10_000 * QST, START_TIME, START_TIME + 10 * DAY).unwrap();
+    let admin = test.admin;
+    let admin_token_account = test.add_token_account(admin, 0);
+    let reclaim = instructions::reclaim_unallocated_rewards(&PROGRAM_ID, &admin, &admin_token_account);
+
+    test.warp_to(START_TIME + 5 * DAY);
+    assert_eq!(test.process(reclaim.clone(), &[admin]), Err(error(ErrorCode::RewardStreamActive)));
+
+    test.warp_to(START_TIME + 10 * DAY);
+    let intruder = test.add_staker(0);
+    let steal = instructions::reclaim_unallocated_rewards(&PROGRAM_ID, &intruder.wallet, &intruder.token_account);
+    assert_eq!(test.process(steal, &[intruder.wallet]), Err(error(ErrorCode::Unauthorized)));
+
+    test.process(reclaim.clone(), &[admin]).unwrap();
+    match decode_event(&test.events()[0]) {
+        Some(QstEvent::UnallocatedRewardsReclaimed(event)) => {
+            assert_eq!(event.admin, admin);
+            assert_eq!(event.amount, 10_000 * QST);
+        }
+        _ => panic!("expected UnallocatedRewardsReclaimed"),
+    }
+    assert_eq!(test.token_balance(&admin_token_account), 10_000 * QST);
+    let pool = test.pool();
+    assert_eq!((pool.reward_unallocated, pool.reward_vault_amount), (0, 0));
+    assert_eq!(pool.last_admin_action, START_TIME + 10 * DAY);
+    assert_eq!(test.reward_vault_balance(), 0);
+
+    assert_eq!(test.process(reclaim, &[admin]), Err(error(ErrorCode::NoUnallocatedRewards)));
+}
+
+#[test]
+fn one_stream_at_a_time() {
+    let mut test = TestPool::started();
+    test.fund_rewards(10_000 * QST, START_TIME + DAY, START_TIME + 10 * DAY).unwrap();
+
+    // Scheduled or running, the stream can't be replaced before its end
+    assert_eq!(
+        test.fund_rewards(1_000 * QST, START_TIME + 10 * DAY, START_TIME + 20 * DAY),
+        Err(error(ErrorCode::RewardStreamActive))
+    );
+    test.warp_to(START_TIME + 10 * DAY - 1);
+    assert_eq!(
+        test.fund_rewards(1_000 * QST, START_TIME + 10 * DAY, START_TIME + 20 * DAY),
+        Err(error(ErrorCode::RewardStreamActive))
+    );
+
+    test.warp_to(START_TIME + 10 * DAY);
+    test.fund_rewards(1_000 * QST, START_TIME + 10 * DAY, START_TIME + 20 * DAY).unwrap();
+}
+
+#[test]
+fn rejects_invalid_funding() {
+    let mut test = TestPool::started();
+    test.warp_to(START_TIME + DAY);
+    let now = START_TIME + DAY;
+
+    assert_eq!(test.fund_rewards(0, now, now + DAY), Err(error(ErrorCode::InvalidAmount)));
+    assert_eq!(
+        test.fund_rewards(1_000 * QST, now - 1, now + DAY),
+        Err(error(ErrorCode::InvalidRewardPeriod))
+    );
+    assert_eq!(
+        test.fund_rewards(1_000 * QST, now + DAY, now + DAY),
+        Err(error(ErrorCode::InvalidRewardPeriod))
+    );
+
+    let intruder = test.add_staker(1_000 * QST);
+    let fund = instructions::fund_rewards(
+        &PROGRAM_ID,
+        &intruder.wallet,
+        &intruder.token_account,
+        &test.mint,
+        1_000 * QST,
+        now,
+        now + DAY,
+    );
+    assert_eq!(test.process(fund, &[intruder.wallet]), Err(error(ErrorCode::Unauthorized)));
+    assert_eq!(test.pool().reward_stream_amount, 0);
+}
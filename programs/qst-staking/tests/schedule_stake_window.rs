@@ -0,0 +1,161 @@
+mod common;
+
+use common::{error, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, StakeWindowPhase, BONUS_ENROLLMENT_PERIOD, ID as PROGRAM_ID, MAX_SCHEDULE_HORIZON,
+    STAKE_WINDOW_PERIOD,
+};
+
+const OPENS_AT: i64 = START_TIME + 3 * DAY;
+
+fn scheduled() -> TestPool {
+    let mut test = TestPool::new();
+    test.process(instructions::schedule_stake_window(&PROGRAM_ID, &test.admin, OPENS_AT), &[test.admin])
+        .unwrap();
+    test
+}
+
+#[test]
+fn admin_schedules_the_window() {
+    let mut test = TestPool::new();
+    let seq_before = test.pool().event_seq;
+    test.process(instructions::schedule_stake_window(&PROGRAM_ID, &test.admin, OPENS_AT), &[test.admin])
+        .unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.first_stake_timestamp, OPENS_AT);
+    assert_eq!(pool.stake_window_end, OPENS_AT + STAKE_WINDOW_PERIOD);
+    assert_eq!(pool.bonus_enrollment_deadline, OPENS_AT + BONUS_ENROLLMENT_PERIOD);
+    assert_eq!(pool.last_admin_action, START_TIME);
+    assert_eq!(pool.event_seq, seq_before + 1);
+    assert_eq!(pool.window_phase(START_TIME), StakeWindowPhase::Scheduled);
+    assert_eq!(test.events().len(), 1);
+}
+
+#[test]
+fn staking_waits_for_the_scheduled_start() {
+    let mut test = scheduled();
+    let staker = test.add_staker(200_000 * QST);
+
+    assert_eq!(test.stake(&staker, 200_000 * QST), Err(error(ErrorCode::StakeWindowNotOpenYet)));
+    test.warp_to(OPENS_AT - 1);
+    assert_eq!(test.stake(&staker, 200_000 * QST), Err(error(ErrorCode::StakeWindowNotOpenYet)));
+
+    test.warp_to(OPENS_AT);
+    test.stake(&staker, 200_000 * QST).unwrap();
+    test.enroll(&staker).unwrap();
+    assert_eq!(test.pool().total_staked, 200_000 * QST);
+}
+
+#[test]
+fn admin_reschedules_before_the_window_opens() {
+    let mut test = scheduled();
+    let later = OPENS_AT + DAY;
+    test.process(instructions::schedule_stake_window(&PROGRAM_ID, &test.admin, later), &[test.admin])
+        .unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.first_stake_timestamp, later);
+    assert_eq!(pool.stake_window_end, later + STAKE_WINDOW_PERIOD);
+    assert_eq!(pool.bonus_enrollment_deadline, later + BONUS_ENROLLMENT_PERIOD);
+}
+
+#[test]
+fn admin_cancels_the_schedule() {
+    let mut test = scheduled();
+    test.process(instructions::cancel_stake_window_schedule(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.first_stake_timestamp, 0);
+    assert_eq!(pool.stake_window_end, 0);
+    assert_eq!(pool.bonus_enrollment_deadline, 0);
+    assert_eq!(pool.window_phase(START_TIME), StakeWindowPhase::NotStarted);
+
+    // With nothing scheduled the window can be opened immediately again
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+    assert_eq!(test.pool().first_stake_timestamp, START_TIME);
+}
+
+#[test]
+fn schedule_is_fixed_once_the_window_opens() {
+    let mut test = scheduled();
+    test.warp_to(OPENS_AT);
+
+    let result = test.process(instructions::cancel_stake_window_schedule(&PROGRAM_ID, &test.admin), &[test.admin]);
+    assert_eq!(result, Err(error(ErrorCode::StakeWindowAlreadyActive)));
+
+    let result = test.process(
+        instructions::schedule_stake_window(&PROGRAM_ID, &test.admin, OPENS_AT + DAY),
+        &[test.admin],
+    );
+    assert_eq!(result, Err(error(ErrorCode::StakeWindowAlreadyActive)));
+    assert_eq!(test.pool().first_stake_timestamp, OPENS_AT);
+}
+
+#[test]
+fn cannot_start_the_window_while_scheduled() {
+    let mut test = scheduled();
+
+    let result = test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin]);
+    assert_eq!(result, Err(error(ErrorCode::StakeWindowAlreadyActive)));
+    assert_eq!(test.pool().first_stake_timestamp, OPENS_AT);
+}
+
+#[test]
+fn rejects_a_start_outside_the_schedule_horizon() {
+    let mut test = TestPool::new();
+
+    for start_ts in [START_TIME - 1, START_TIME, START_TIME + MAX_SCHEDULE_HORIZON + 1, i64::MAX] {
+        let result = test.process(
+            instructions::schedule_stake_window(&PROGRAM_ID, &test.admin, start_ts),
+            &[test.admin],
+        );
+        assert_eq!(result, Err(error(ErrorCode::InvalidWindowStart)));
+    }
+    assert_eq!(test.pool().first_stake_timestamp, 0);
+}
+
+#[test]
+fn cancel_requires_a_schedule() {
+    let mut test = TestPool::new();
+
+    let result = test.process(instructions::cancel_stake_window_schedule(&PROGRAM_ID, &test.admin), &[test.admin]);
+    assert_eq!(result, Err(error(ErrorCode::NoScheduledStakeWindow)));
+}
+
+#[test]
+fn rejects_non_admin() {
+    let mut test = scheduled();
+    let intruder = test.add_staker(0).wallet;
+
+    let result = test.process(
+        instructions::schedule_stake_window(&PROGRAM_ID, &intruder, OPENS_AT + DAY),
+        &[intruder],
+    );
+    assert_eq!(result, Err(error(ErrorCode::Unauthorized)));
+
+    let result = test.process(instructions::cancel_stake_window_schedule(&PROGRAM_ID, &intruder), &[intruder]);
+    assert_eq!(result, Err(error(ErrorCode::Unauthorized)));
+    assert_eq!(test.pool().first_stake_timestamp, OPENS_AT);
+}
+
+#[test]
+fn pool_stays_configurable_until_the_window_opens() {
+    let mut test = scheduled();
+    test.process(
+        instructions::configure_enrolled_exit_penalty(&PROGRAM_ID, &test.admin, 50),
+        &[test.admin],
+    )
+    .unwrap();
+    assert_eq!(test.pool().enrolled_exit_penalty_rate, 50);
+
+    test.warp_to(OPENS_AT);
+    let result = test.process(
+        instructions::configure_enrolled_exit_penalty(&PROGRAM_ID, &test.admin, 60),
+        &[test.admin],
+    );
+    assert_eq!(result, Err(error(ErrorCode::StakeWindowAlreadyActive)));
+}
@@ -0,0 +1,134 @@
+// Once settlement starts, the regular exits are closed and positions settle via settle_position
+mod common;
+
+use anchor_lang::solana_program::program_error::ProgramError;
+use common::{error, Staker, TestPool, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, UnclaimedBonusPolicy, BONUS_LOCK_PERIOD, BONUS_WITHDRAWAL_DELAY, EARLY_UNSTAKE_THRESHOLD_1,
+    ID as PROGRAM_ID, PRINCIPAL_LOCK_PERIOD,
+};
+
+const PRINCIPAL_UNLOCK: i64 = START_TIME + PRINCIPAL_LOCK_PERIOD;
+
+fn trigger(test: &mut TestPool, caller: &Staker) {
+    test.warp_to(test.pool().settlement_available_time());
+    test.process(instructions::trigger_settlement(&PROGRAM_ID, &caller.wallet), &[caller.wallet])
+        .unwrap();
+}
+
+fn settle(test: &mut TestPool, cranker: &Staker, owner: &Staker) -> Result<(), ProgramError> {
+    let pool_token_account = test.pool_token_account;
+    test.process(
+        instructions::settle_position(
+            &PROGRAM_ID,
+            &cranker.wallet,
+            &owner.wallet,
+            &owner.token_account,
+            &pool_token_account,
+        ),
+        &[cranker.wallet],
+    )
+}
+
+#[test]
+fn settlement_closes_regular_exits() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(200_000 * QST);
+    let enrollee = test.add_staker(200_000 * QST);
+    test.stake(&staker, 200_000 * QST).unwrap();
+    test.stake(&enrollee, 200_000 * QST).unwrap();
+    test.enroll(&enrollee).unwrap();
+
+    let available = test.pool().settlement_available_time();
+    test.warp_to(available - 1);
+    assert_eq!(
+        test.process(instructions::trigger_settlement(&PROGRAM_ID, &staker.wallet), &[staker.wallet]),
+        Err(error(ErrorCode::SettlementNotAvailable))
+    );
+    test.warp_to(available);
+    test.process(instructions::trigger_settlement(&PROGRAM_ID, &staker.wallet), &[staker.wallet])
+        .unwrap();
+    assert!(test.pool().settlement_active);
+
+    let settlement_active = Err(error(ErrorCode::SettlementActive));
+    assert_eq!(test.unstake(&staker, 200_000 * QST, 0), settlement_active);
+    assert_eq!(test.withdraw_all(&staker), settlement_active);
+    assert_eq!(test.withdraw_bonus(&enrollee), settlement_active);
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    assert_eq!(test.collect_dust(&admin, &fee_recipient_token_account), settlement_active);
+}
+
+#[test]
+fn settlement_pays_unclaimed_bonuses_before_the_penalty_split() {
+    let mut test = TestPool::new();
+    test.process(
+        instructions::configure_unclaimed_bonus(
+            &PROGRAM_ID,
+            &test.admin,
+            UnclaimedBonusPolicy::KeepClaimable,
+            test.pool().bonus_claim_period,
+        ),
+        &[test.admin],
+    )
+    .unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+    let on_time = test.add_staker(200_000 * QST);
+    let late = test.add_staker(200_000 * QST);
+    let leaver = test.add_staker(200_000 * QST);
+    for staker in [&on_time, &late, &leaver] {
+        test.stake(staker, 200_000 * QST).unwrap();
+    }
+    test.enroll(&on_time).unwrap();
+    test.enroll(&late).unwrap();
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+    test.warp_to(PRINCIPAL_UNLOCK + BONUS_LOCK_PERIOD + BONUS_WITHDRAWAL_DELAY);
+    test.withdraw_bonus(&on_time).unwrap();
+
+    // The 20k left in the vault is the late enrollee's bonus, not a penalty to split
+    trigger(&mut test, &leaver);
+    assert_eq!(test.pool().settlement_penalty_vault, 0);
+    settle(&mut test, &leaver, &on_time).unwrap();
+    settle(&mut test, &leaver, &late).unwrap();
+    assert_eq!(test.token_balance(&on_time.token_account), 220_000 * QST);
+    assert_eq!(test.token_balance(&late.token_account), 220_000 * QST);
+    assert!(test.stake_account(&late.wallet).unwrap().bonus_claimed);
+    assert_eq!(test.pool().penalty_vault_amount, 0);
+}
+
+#[test]
+fn settlement_splits_the_penalty_vault_pro_rata() {
+    let mut test = TestPool::started();
+    let amounts = [200_000 * QST, 400_000 * QST, 800_000 * QST];
+    let stakers: Vec<_> = amounts.iter().map(|&amount| test.add_staker(amount)).collect();
+    let leaver = test.add_staker(200_000 * QST);
+    for (staker, &amount) in stakers.iter().zip(&amounts) {
+        test.stake(staker, amount).unwrap();
+    }
+    test.stake(&leaver, 200_000 * QST).unwrap();
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+
+    trigger(&mut test, &leaver);
+    let pool = test.pool();
+    assert_eq!(pool.settlement_penalty_vault, 40_000 * QST);
+    assert_eq!(pool.settlement_total_stake, 1_400_000 * QST);
+
+    let mut paid = 0;
+    for (staker, &amount) in stakers.iter().zip(&amounts) {
+        settle(&mut test, &leaver, staker).unwrap();
+        let share = (40_000 * QST as u128 * amount as u128 / (1_400_000 * QST) as u128) as u64;
+        assert_eq!(test.token_balance(&staker.token_account), amount + share);
+        paid += share;
+    }
+
+    // Flooring 40k over 1.4M in sevenths leaves one base unit in the vault
+    let dust = 40_000 * QST - paid;
+    assert_eq!(dust, 1);
+    let pool = test.pool();
+    assert_eq!(pool.total_staked, 0);
+    assert_eq!(pool.penalty_vault_amount, dust);
+    assert_eq!(settle(&mut test, &leaver, &stakers[0]), Err(error(ErrorCode::NothingToSettle)));
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use anchor_lang::Discriminator;
+use anchor_lang::AnchorDeserialize;
+use common::{error, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    allowlist_leaf, BonusEnrollment, ErrorCode, StakeEvent, BONUS_ENROLLMENT_PERIOD, BONUS_LOCK_PERIOD,
+    ID as PROGRAM_ID, MINIMUM_STAKE_AMOUNT, PRINCIPAL_LOCK_PERIOD,
+};
+
+#[test]
+fn stakes_and_enrolls_in_one_instruction() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+
+    test.stake_and_enroll(&staker, 400_000 * QST).unwrap();
+
+    let position = test.stake_account(&staker.wallet).unwrap();
+    let bonus_unlock = START_TIME + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD;
+    assert_eq!(position.amount, 400_000 * QST);
+    assert_eq!(position.node_keys_earned, 4);
+    assert!(position.enrolled_in_bonus);
+    assert_eq!(position.bonus_unlock_time, bonus_unlock);
+
+    let pool = test.pool();
+    assert_eq!(pool.total_staked, 400_000 * QST);
+    assert_eq!(pool.total_enrolled_stake, 400_000 * QST);
+    assert_eq!(pool.latest_bonus_unlock_time, bonus_unlock);
+    assert_eq!(test.token_balance(&test.pool_token_account), 400_000 * QST);
+
+    let events = test.events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0][..8], StakeEvent::DISCRIMINATOR);
+    assert_eq!(events[1][..8], BonusEnrollment::DISCRIMINATOR);
+
+    // The stake is reported as enrolled, with its bonus unlock
+    let stake_event = StakeEvent::try_from_slice(&events[0][8..]).unwrap();
+    assert!(stake_event.enrolled_in_bonus);
+    assert_eq!(stake_event.bonus_unlock_time, bonus_unlock);
+}
+
+#[test]
+fn enrolls_an_existing_position_in_full() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    test.warp_to(START_TIME + DAY);
+    test.stake_and_enroll(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert_eq!(position.amount, 2 * MINIMUM_STAKE_AMOUNT);
+    assert_eq!(position.bonus_unlock_time, START_TIME + DAY + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD);
+    assert_eq!(test.pool().total_enrolled_stake, 2 * MINIMUM_STAKE_AMOUNT);
+}
+
+#[test]
+fn rejects_after_the_enrollment_deadline_without_staking() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    test.warp_to(START_TIME + BONUS_ENROLLMENT_PERIOD + 1);
+
+    assert_eq!(
+        test.stake_and_enroll(&staker, MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::BonusEnrollmentClosed))
+    );
+    assert!(test.stake_account(&staker.wallet).is_none());
+    assert_eq!(test.pool().total_staked, 0);
+    assert_eq!(test.token_balance(&staker.token_account), 1_000_000 * QST);
+
+    // A plain stake still goes through while the window is open
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+}
+
+#[test]
+fn rejects_an_enrolled_position() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    test.stake_and_enroll(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    assert_eq!(
+        test.stake_and_enroll(&staker, MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::AlreadyEnrolledInBonus))
+    );
+    assert_eq!(test.pool().total_enrolled_stake, MINIMUM_STAKE_AMOUNT);
+}
+
+#[test]
+fn follows_the_stake_rules() {
+    let mut test = TestPool::new();
+    let staker = test.add_staker(1_000_000 * QST);
+    assert_eq!(
+        test.stake_and_enroll(&staker, MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::StakeWindowNotStarted))
+    );
+
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    assert_eq!(
+        test.stake_and_enroll(&staker, MINIMUM_STAKE_AMOUNT + 1),
+        Err(error(ErrorCode::InvalidStakeAmount))
+    );
+}
+
+#[test]
+fn allowlisted_wallets_stake_and_enroll_during_the_allowlist_phase() {
+    let mut test = TestPool::new();
+    let staker = test.add_staker(1_000_000 * QST);
+    let allocation = MINIMUM_STAKE_AMOUNT;
+    // A single-leaf tree: the root is the leaf and the proof is empty
+    let root = allowlist_leaf(&staker.wallet, allocation);
+    test.process(instructions::configure_allowlist(&PROGRAM_ID, &test.admin, root, START_TIME + DAY), &[test.admin])
+        .unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+
+    assert_eq!(
+        test.stake_and_enroll(&staker, MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::AllowlistProofRequired))
+    );
+
+    let allowlisted = |amount, proof_allocation| {
+        instructions::stake_allowlisted_and_enroll(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &test.pool_token_account,
+            amount,
+            proof_allocation,
+            Vec::new(),
+        )
+    };
+    let forged = allowlisted(MINIMUM_STAKE_AMOUNT, 2 * allocation);
+    let over_allocation = allowlisted(2 * MINIMUM_STAKE_AMOUNT, allocation);
+    let within_allocation = allowlisted(MINIMUM_STAKE_AMOUNT, allocation);
+    assert_eq!(
+        test.process(forged, &[staker.wallet]),
+        Err(error(ErrorCode::InvalidAllowlistProof))
+    );
+    assert_eq!(
+        test.process(over_allocation, &[staker.wallet]),
+        Err(error(ErrorCode::AllowlistAllocationExceeded))
+    );
+    test.process(within_allocation, &[staker.wallet]).unwrap();
+
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert!(position.enrolled_in_bonus);
+    assert_eq!(position.enrolled_stake, MINIMUM_STAKE_AMOUNT);
+    assert_eq!(test.pool().total_enrolled_stake, MINIMUM_STAKE_AMOUNT);
+}
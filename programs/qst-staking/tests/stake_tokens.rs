@@ -0,0 +1,239 @@
+mod common;
+
+use common::{error, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    allowlist_leaf, ErrorCode, BONUS_ENROLLMENT_PERIOD, BONUS_LOCK_PERIOD, ID as PROGRAM_ID, MAXIMUM_STAKE_AMOUNT,
+    MINIMUM_STAKE_AMOUNT, PRINCIPAL_LOCK_PERIOD, STAKE_WINDOW_PERIOD,
+};
+
+#[test]
+fn stakes_into_the_pool() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+
+    test.stake(&staker, 400_000 * QST).unwrap();
+
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert_eq!(position.user, staker.wallet);
+    assert_eq!(position.amount, 400_000 * QST);
+    assert_eq!(position.node_keys_earned, 4);
+    assert_eq!(position.principal_unlock_time, START_TIME + PRINCIPAL_LOCK_PERIOD);
+    assert_eq!(position.bonus_unlock_time, 0);
+    assert!(!position.enrolled_in_bonus);
+    assert_eq!(test.pool().total_staked, 400_000 * QST);
+    assert_eq!(test.token_balance(&staker.token_account), 600_000 * QST);
+    assert_eq!(test.token_balance(&test.pool_token_account), 400_000 * QST);
+    assert_eq!(test.events().len(), 1);
+}
+
+#[test]
+fn restaking_adds_keys_and_resets_the_lock() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    test.warp_to(START_TIME + 3 * DAY);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert_eq!(position.amount, 2 * MINIMUM_STAKE_AMOUNT);
+    assert_eq!(position.node_keys_earned, 4);
+    assert_eq!(position.principal_unlock_time, START_TIME + 3 * DAY + PRINCIPAL_LOCK_PERIOD);
+    assert_eq!(test.pool().total_enrolled_stake, 0);
+}
+
+#[test]
+fn restaking_while_enrolled_grows_the_bonus_weight() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+    test.enroll(&staker).unwrap();
+
+    test.warp_to(START_TIME + DAY);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    let position = test.stake_account(&staker.wallet).unwrap();
+    let bonus_unlock = START_TIME + DAY + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD;
+    assert_eq!(position.bonus_unlock_time, bonus_unlock);
+    assert_eq!(position.enrolled_stake, 2 * MINIMUM_STAKE_AMOUNT);
+    let pool = test.pool();
+    assert_eq!(pool.total_enrolled_stake, 2 * MINIMUM_STAKE_AMOUNT);
+    assert_eq!(pool.latest_bonus_unlock_time, bonus_unlock);
+}
+
+#[test]
+fn restaking_after_the_enrollment_deadline_is_principal_only() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+    test.enroll(&staker).unwrap();
+    let bonus_unlock = START_TIME + PRINCIPAL_LOCK_PERIOD + BONUS_LOCK_PERIOD;
+
+    test.warp_to(START_TIME + BONUS_ENROLLMENT_PERIOD + 1);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    // Weight and unlocks are frozen; the new stake is tracked apart with its own unlock
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert_eq!(position.amount, 2 * MINIMUM_STAKE_AMOUNT);
+    assert_eq!(position.node_keys_earned, 4);
+    assert_eq!(position.enrolled_stake, MINIMUM_STAKE_AMOUNT);
+    assert_eq!(position.bonus_unlock_time, bonus_unlock);
+    assert_eq!(position.principal_unlock_time, START_TIME + PRINCIPAL_LOCK_PERIOD);
+    assert_eq!(position.late_stake, MINIMUM_STAKE_AMOUNT);
+    assert_eq!(
+        position.late_stake_unlock_time,
+        START_TIME + BONUS_ENROLLMENT_PERIOD + 1 + PRINCIPAL_LOCK_PERIOD
+    );
+    let pool = test.pool();
+    assert_eq!(pool.total_staked, 2 * MINIMUM_STAKE_AMOUNT);
+    assert_eq!(pool.total_enrolled_stake, MINIMUM_STAKE_AMOUNT);
+    assert_eq!(pool.latest_bonus_unlock_time, bonus_unlock);
+}
+
+#[test]
+fn rejects_invalid_amounts() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(20_000_000 * QST);
+
+    let cases = [
+        (0, ErrorCode::InvalidAmount),
+        (MINIMUM_STAKE_AMOUNT - 1, ErrorCode::InsufficientStakeAmount),
+        (MAXIMUM_STAKE_AMOUNT + MINIMUM_STAKE_AMOUNT, ErrorCode::StakeAmountTooLarge),
+        (MINIMUM_STAKE_AMOUNT + QST, ErrorCode::InvalidStakeAmount),
+    ];
+    for (amount, code) in cases {
+        assert_eq!(test.stake(&staker, amount), Err(error(code)), "amount {}", amount);
+    }
+    assert!(test.stake_account(&staker.wallet).is_none());
+}
+
+#[test]
+fn rejects_stakes_outside_the_window() {
+    let mut test = TestPool::new();
+    let staker = test.add_staker(1_000_000 * QST);
+    assert_eq!(
+        test.stake(&staker, MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::StakeWindowNotStarted))
+    );
+
+    let mut test = TestPool::started();
+    let staker = test.add_staker(1_000_000 * QST);
+    test.warp_to(START_TIME + STAKE_WINDOW_PERIOD);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+
+    test.warp_to(START_TIME + STAKE_WINDOW_PERIOD + 1);
+    assert_eq!(
+        test.stake(&staker, MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::StakeWindowClosed))
+    );
+}
+
+#[test]
+fn enforces_stake_caps() {
+    let mut test = TestPool::new();
+    let caps = instructions::configure_stake_caps(
+        &PROGRAM_ID,
+        &test.admin,
+        3 * MINIMUM_STAKE_AMOUNT,
+        2 * MINIMUM_STAKE_AMOUNT,
+    );
+    test.process(caps, &[test.admin]).unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+    let alice = test.add_staker(1_000_000 * QST);
+    let bob = test.add_staker(1_000_000 * QST);
+
+    assert_eq!(
+        test.stake(&alice, 3 * MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::UserStakeCapExceeded))
+    );
+    test.stake(&alice, 2 * MINIMUM_STAKE_AMOUNT).unwrap();
+    assert_eq!(
+        test.stake(&bob, 2 * MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::PoolStakeCapExceeded))
+    );
+    test.stake(&bob, MINIMUM_STAKE_AMOUNT).unwrap();
+}
+
+#[test]
+fn allowlist_public_time_must_follow_the_window_start() {
+    let mut test = TestPool::new();
+    let admin = test.admin;
+    let root = allowlist_leaf(&admin, MINIMUM_STAKE_AMOUNT);
+    let configure = |public_time| instructions::configure_allowlist(&PROGRAM_ID, &admin, root, public_time);
+    let invalid = Err(error(ErrorCode::InvalidAllowlistPublicTime));
+
+    // Unscheduled: the window can open now at the earliest
+    assert_eq!(test.process(configure(START_TIME), &[admin]), invalid);
+    test.process(
+        instructions::configure_allowlist(&PROGRAM_ID, &admin, [0u8; 32], 0),
+        &[admin],
+    )
+    .unwrap();
+
+    // Scheduled: the public time has to come after the scheduled start, in either order
+    test.process(instructions::schedule_stake_window(&PROGRAM_ID, &admin, START_TIME + 2 * DAY), &[admin])
+        .unwrap();
+    assert_eq!(test.process(configure(START_TIME + 2 * DAY), &[admin]), invalid);
+    test.process(configure(START_TIME + 3 * DAY), &[admin]).unwrap();
+    assert_eq!(
+        test.process(instructions::schedule_stake_window(&PROGRAM_ID, &admin, START_TIME + 3 * DAY), &[admin]),
+        invalid
+    );
+    assert_eq!(test.pool().first_stake_timestamp, START_TIME + 2 * DAY);
+
+    // Started by hand after the public time has passed
+    let mut test = TestPool::new();
+    let admin = test.admin;
+    test.process(instructions::configure_allowlist(&PROGRAM_ID, &admin, root, START_TIME + DAY), &[admin])
+        .unwrap();
+    test.warp_to(START_TIME + DAY);
+    assert_eq!(
+        test.process(instructions::start_stake_window(&PROGRAM_ID, &admin), &[admin]),
+        invalid
+    );
+}
+
+#[test]
+fn allowlist_phase_requires_a_proof() {
+    let mut test = TestPool::new();
+    let staker = test.add_staker(1_000_000 * QST);
+    let allocation = MINIMUM_STAKE_AMOUNT;
+    // A single-leaf tree: the root is the leaf and the proof is empty
+    let root = allowlist_leaf(&staker.wallet, allocation);
+    let public_time = START_TIME + DAY;
+    test.process(instructions::configure_allowlist(&PROGRAM_ID, &test.admin, root, public_time), &[test.admin])
+        .unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+
+    assert_eq!(
+        test.stake(&staker, MINIMUM_STAKE_AMOUNT),
+        Err(error(ErrorCode::AllowlistProofRequired))
+    );
+
+    let allowlisted = |amount| {
+        instructions::stake_tokens_allowlisted(
+            &PROGRAM_ID,
+            &staker.wallet,
+            &staker.token_account,
+            &test.pool_token_account,
+            amount,
+            allocation,
+            Vec::new(),
+        )
+    };
+    let over_allocation = allowlisted(2 * MINIMUM_STAKE_AMOUNT);
+    let within_allocation = allowlisted(MINIMUM_STAKE_AMOUNT);
+    assert_eq!(
+        test.process(over_allocation, &[staker.wallet]),
+        Err(error(ErrorCode::AllowlistAllocationExceeded))
+    );
+    test.process(within_allocation, &[staker.wallet]).unwrap();
+
+    // Public phase: plain stake_tokens works again
+    test.warp_to(public_time);
+    test.stake(&staker, MINIMUM_STAKE_AMOUNT).unwrap();
+    assert_eq!(test.stake_account(&staker.wallet).unwrap().amount, 2 * MINIMUM_STAKE_AMOUNT);
+}
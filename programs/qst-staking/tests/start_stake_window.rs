@@ -0,0 +1,43 @@
+mod common;
+
+use common::{error, TestPool, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, BONUS_ENROLLMENT_PERIOD, ID as PROGRAM_ID, STAKE_WINDOW_PERIOD,
+};
+
+#[test]
+fn admin_opens_the_window() {
+    let mut test = TestPool::new();
+    let seq_before = test.pool().event_seq;
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.first_stake_timestamp, START_TIME);
+    assert_eq!(pool.stake_window_end, START_TIME + STAKE_WINDOW_PERIOD);
+    assert_eq!(pool.bonus_enrollment_deadline, START_TIME + BONUS_ENROLLMENT_PERIOD);
+    assert_eq!(pool.last_admin_action, START_TIME);
+    assert_eq!(pool.event_seq, seq_before + 1);
+    assert_eq!(test.events().len(), 1);
+}
+
+#[test]
+fn rejects_non_admin() {
+    let mut test = TestPool::new();
+    let intruder = test.add_staker(0).wallet;
+
+    let result = test.process(instructions::start_stake_window(&PROGRAM_ID, &intruder), &[intruder]);
+    assert_eq!(result, Err(error(ErrorCode::Unauthorized)));
+    assert_eq!(test.pool().first_stake_timestamp, 0);
+}
+
+#[test]
+fn cannot_restart_the_window() {
+    let mut test = TestPool::started();
+    test.warp_to(START_TIME + STAKE_WINDOW_PERIOD + 1);
+
+    let result = test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin]);
+    assert_eq!(result, Err(error(ErrorCode::StakeWindowAlreadyActive)));
+    assert_eq!(test.pool().first_stake_timestamp, START_TIME);
+}
@@ -0,0 +1,422 @@
+mod common;
+
+use common::{error, Staker, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, UnclaimedBonusPolicy, BONUS_CLAIM_PERIOD, BONUS_ENROLLMENT_PERIOD, BONUS_LOCK_PERIOD,
+    BONUS_WITHDRAWAL_DELAY, EARLY_UNSTAKE_THRESHOLD_1, ID as PROGRAM_ID, MIN_BONUS_CLAIM_PERIOD,
+    PRINCIPAL_LOCK_PERIOD,
+};
+
+const PRINCIPAL_UNLOCK: i64 = START_TIME + PRINCIPAL_LOCK_PERIOD;
+const BONUS_WITHDRAWAL: i64 = PRINCIPAL_UNLOCK + BONUS_LOCK_PERIOD + BONUS_WITHDRAWAL_DELAY;
+
+struct Round {
+    test: TestPool,
+    on_time: Staker,
+    late: Staker,
+}
+
+// Two equal enrollees and a late-tier unstake leaving 40k in the vault, 20k per enrollee
+fn round(policy: UnclaimedBonusPolicy, claim_period: i64) -> Round {
+    let mut test = TestPool::new();
+    test.process(
+        instructions::configure_unclaimed_bonus(&PROGRAM_ID, &test.admin, policy, claim_period),
+        &[test.admin],
+    )
+    .unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+
+    let on_time = test.add_staker(200_000 * QST);
+    let late = test.add_staker(200_000 * QST);
+    let leaver = test.add_staker(200_000 * QST);
+    for staker in [&on_time, &late, &leaver] {
+        test.stake(staker, 200_000 * QST).unwrap();
+    }
+    test.enroll(&on_time).unwrap();
+    test.enroll(&late).unwrap();
+
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+    Round { test, on_time, late }
+}
+
+#[test]
+fn pool_defaults_to_sweeping_after_sixty_days() {
+    let test = TestPool::new();
+    let pool = test.pool();
+    assert_eq!(pool.unclaimed_bonus_policy, UnclaimedBonusPolicy::Sweep);
+    assert_eq!(pool.bonus_claim_period, BONUS_CLAIM_PERIOD);
+}
+
+#[test]
+fn admin_configures_the_policy() {
+    let mut test = TestPool::new();
+    let seq_before = test.pool().event_seq;
+    test.process(
+        instructions::configure_unclaimed_bonus(
+            &PROGRAM_ID,
+            &test.admin,
+            UnclaimedBonusPolicy::Redistribute,
+            90 * DAY,
+        ),
+        &[test.admin],
+    )
+    .unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.unclaimed_bonus_policy, UnclaimedBonusPolicy::Redistribute);
+    assert_eq!(pool.bonus_claim_period, 90 * DAY);
+    assert_eq!(pool.last_admin_action, START_TIME);
+    assert_eq!(pool.event_seq, seq_before + 1);
+    assert_eq!(test.events().len(), 1);
+}
+
+#[test]
+fn configuration_is_fixed_once_the_window_opens() {
+    let mut test = TestPool::new();
+    let result = test.process(
+        instructions::configure_unclaimed_bonus(
+            &PROGRAM_ID,
+            &test.admin,
+            UnclaimedBonusPolicy::Sweep,
+            MIN_BONUS_CLAIM_PERIOD - 1,
+        ),
+        &[test.admin],
+    );
+    assert_eq!(result, Err(error(ErrorCode::InvalidClaimPeriod)));
+
+    let intruder = test.add_staker(0).wallet;
+    let result = test.process(
+        instructions::configure_unclaimed_bonus(&PROGRAM_ID, &intruder, UnclaimedBonusPolicy::Sweep, 90 * DAY),
+        &[intruder],
+    );
+    assert_eq!(result, Err(error(ErrorCode::Unauthorized)));
+
+    let mut test = TestPool::started();
+    let result = test.process(
+        instructions::configure_unclaimed_bonus(
+            &PROGRAM_ID,
+            &test.admin,
+            UnclaimedBonusPolicy::KeepClaimable,
+            90 * DAY,
+        ),
+        &[test.admin],
+    );
+    assert_eq!(result, Err(error(ErrorCode::StakeWindowAlreadyActive)));
+    assert_eq!(test.pool().unclaimed_bonus_policy, UnclaimedBonusPolicy::Sweep);
+}
+
+#[test]
+fn configured_claim_period_moves_the_sweep() {
+    let Round { mut test, .. } = round(UnclaimedBonusPolicy::Sweep, 90 * DAY);
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+
+    test.warp_to(BONUS_WITHDRAWAL + 90 * DAY - 1);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::BonusClaimPeriodNotExpired))
+    );
+
+    test.warp_to(BONUS_WITHDRAWAL + 90 * DAY);
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+    assert_eq!(test.token_balance(&fee_recipient_token_account), 40_000 * QST);
+}
+
+#[test]
+fn redistributes_unclaimed_shares_to_on_time_claimants() {
+    let Round { mut test, on_time, late } = round(UnclaimedBonusPolicy::Redistribute, MIN_BONUS_CLAIM_PERIOD);
+    let deadline = BONUS_WITHDRAWAL + MIN_BONUS_CLAIM_PERIOD;
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+
+    test.warp_to(BONUS_WITHDRAWAL);
+    test.withdraw_bonus(&on_time).unwrap();
+    assert_eq!(test.pool().claimed_enrolled_stake, 200_000 * QST);
+    assert_eq!(
+        test.withdraw_redistributed_bonus(&on_time),
+        Err(error(ErrorCode::BonusClaimPeriodNotExpired))
+    );
+
+    test.warp_to(deadline);
+    assert_eq!(test.withdraw_bonus(&late), Err(error(ErrorCode::BonusClaimPeriodExpired)));
+    assert_eq!(
+        test.withdraw_redistributed_bonus(&late),
+        Err(error(ErrorCode::NotEligibleForRedistribution))
+    );
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::UnclaimedBonusNotSweepable))
+    );
+
+    test.withdraw_redistributed_bonus(&on_time).unwrap();
+    assert_eq!(test.token_balance(&on_time.token_account), 40_000 * QST);
+    assert_eq!(
+        test.withdraw_redistributed_bonus(&on_time),
+        Err(error(ErrorCode::RedistributionAlreadyClaimed))
+    );
+
+    let pool = test.pool();
+    assert_eq!(pool.penalty_vault_amount, 0);
+    assert_eq!(pool.claimed_enrolled_stake, 0);
+    assert!(test.stake_account(&on_time.wallet).unwrap().redistribution_claimed);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::NoDustToCollect))
+    );
+}
+
+#[test]
+fn redistribution_sweeps_when_nobody_claimed_on_time() {
+    let Round { mut test, .. } = round(UnclaimedBonusPolicy::Redistribute, MIN_BONUS_CLAIM_PERIOD);
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+
+    test.warp_to(BONUS_WITHDRAWAL + MIN_BONUS_CLAIM_PERIOD);
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+    assert_eq!(test.token_balance(&fee_recipient_token_account), 40_000 * QST);
+}
+
+#[test]
+fn keep_claimable_never_sweeps() {
+    let Round { mut test, on_time, late } = round(UnclaimedBonusPolicy::KeepClaimable, MIN_BONUS_CLAIM_PERIOD);
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+
+    test.warp_to(BONUS_WITHDRAWAL);
+    test.withdraw_bonus(&on_time).unwrap();
+
+    test.warp_to(BONUS_WITHDRAWAL + 365 * DAY);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::UnclaimedBonusNotSweepable))
+    );
+    assert_eq!(
+        test.withdraw_redistributed_bonus(&on_time),
+        Err(error(ErrorCode::RedistributionNotEnabled))
+    );
+
+    test.withdraw_bonus(&late).unwrap();
+    assert_eq!(test.token_balance(&late.token_account), 20_000 * QST);
+    assert_eq!(test.pool().penalty_vault_amount, 0);
+}
+
+#[test]
+fn keep_claimable_sweeps_a_vault_with_no_enrolled_weight() {
+    let mut test = TestPool::new();
+    test.process(
+        instructions::configure_unclaimed_bonus(
+            &PROGRAM_ID,
+            &test.admin,
+            UnclaimedBonusPolicy::KeepClaimable,
+            MIN_BONUS_CLAIM_PERIOD,
+        ),
+        &[test.admin],
+    )
+    .unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+    let staker = test.add_staker(200_000 * QST);
+    let leaver = test.add_staker(200_000 * QST);
+    test.stake(&staker, 200_000 * QST).unwrap();
+    test.stake(&leaver, 200_000 * QST).unwrap();
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+
+    // Nobody enrolled, so no position can ever be paid from the vault: the policy doesn't keep it
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    let deadline = START_TIME + BONUS_ENROLLMENT_PERIOD + BONUS_WITHDRAWAL_DELAY + MIN_BONUS_CLAIM_PERIOD;
+    assert_eq!(test.pool().bonus_claim_deadline(), deadline);
+    test.warp_to(deadline);
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+    assert_eq!(test.token_balance(&fee_recipient_token_account), 40_000 * QST);
+    let pool = test.pool();
+    assert_eq!(pool.penalty_vault_amount, 0);
+    assert_eq!(pool.rounding_dust, 0);
+    assert!(!pool.unclaimed_bonus_swept);
+}
+
+#[test]
+fn keep_claimable_collects_penalties_paid_after_the_snapshot() {
+    let mut test = TestPool::new();
+    let admin = test.admin;
+    test.process(
+        instructions::configure_unclaimed_bonus(
+            &PROGRAM_ID,
+            &admin,
+            UnclaimedBonusPolicy::KeepClaimable,
+            MIN_BONUS_CLAIM_PERIOD,
+        ),
+        &[admin],
+    )
+    .unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &admin), &[admin])
+        .unwrap();
+    test.process(
+        instructions::extend_stake_window(&PROGRAM_ID, &admin, START_TIME + 20 * DAY),
+        &[admin],
+    )
+    .unwrap();
+    let on_time = test.add_staker(200_000 * QST);
+    let late = test.add_staker(200_000 * QST);
+    let leaver = test.add_staker(200_000 * QST);
+    for staker in [&on_time, &late, &leaver] {
+        test.stake(staker, 200_000 * QST).unwrap();
+    }
+    test.enroll(&on_time).unwrap();
+    test.enroll(&late).unwrap();
+
+    // Staked in the extended window and unstaked after the first claim froze the split
+    let dave = test.add_staker(200_000 * QST);
+    test.warp_to(START_TIME + 19 * DAY);
+    test.stake(&dave, 200_000 * QST).unwrap();
+    test.warp_to(START_TIME + 21 * DAY);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+    test.warp_to(BONUS_WITHDRAWAL);
+    test.withdraw_bonus(&on_time).unwrap();
+    test.warp_to(START_TIME + 19 * DAY + PRINCIPAL_LOCK_PERIOD - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&dave, 200_000 * QST, 40_000 * QST).unwrap();
+    assert_eq!(test.pool().post_snapshot_penalties, 40_000 * QST);
+
+    test.warp_to(BONUS_WITHDRAWAL + MIN_BONUS_CLAIM_PERIOD);
+    let fee_recipient_token_account = test.fee_recipient_token_account;
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+    assert_eq!(test.token_balance(&fee_recipient_token_account), 40_000 * QST);
+    assert_eq!(test.pool().post_snapshot_penalties, 0);
+
+    // The late enrollee's share is untouched
+    test.withdraw_bonus(&late).unwrap();
+    assert_eq!(test.token_balance(&late.token_account), 20_000 * QST);
+    assert_eq!(test.pool().penalty_vault_amount, 0);
+}
+
+#[test]
+fn keep_claimable_still_releases_rounding_dust() {
+    let mut test = TestPool::new();
+    test.process(
+        instructions::configure_unclaimed_bonus(
+            &PROGRAM_ID,
+            &test.admin,
+            UnclaimedBonusPolicy::KeepClaimable,
+            MIN_BONUS_CLAIM_PERIOD,
+        ),
+        &[test.admin],
+    )
+    .unwrap();
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &test.admin), &[test.admin])
+        .unwrap();
+    let enrollees: Vec<_> = (0..3).map(|_| test.add_staker(200_000 * QST)).collect();
+    let leaver = test.add_staker(200_000 * QST);
+    for staker in enrollees.iter().chain([&leaver]) {
+        test.stake(staker, 200_000 * QST).unwrap();
+    }
+    for staker in &enrollees {
+        test.enroll(staker).unwrap();
+    }
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&leaver, 200_000 * QST, 40_000 * QST).unwrap();
+
+    // 40k over three equal weights floors away one base unit
+    let share = 40_000 * QST / 3;
+    test.warp_to(BONUS_WITHDRAWAL);
+    test.withdraw_bonus(&enrollees[0]).unwrap();
+
+    test.warp_to(BONUS_WITHDRAWAL + MIN_BONUS_CLAIM_PERIOD);
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    test.collect_dust(&admin, &fee_recipient_token_account).unwrap();
+    assert_eq!(test.token_balance(&fee_recipient_token_account), 1);
+    assert_eq!(test.pool().penalty_vault_amount, 2 * share);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::UnclaimedBonusNotSweepable))
+    );
+
+    // Unclaimed shares stay claimable in full
+    test.withdraw_bonus(&enrollees[1]).unwrap();
+    test.withdraw_bonus(&enrollees[2]).unwrap();
+    assert_eq!(test.token_balance(&enrollees[2].token_account), share);
+    assert_eq!(test.pool().penalty_vault_amount, 0);
+}
+
+#[test]
+fn roll_over_carries_unclaimed_shares_into_the_next_round() {
+    let Round { mut test, on_time, late } = round(UnclaimedBonusPolicy::RollOver, MIN_BONUS_CLAIM_PERIOD);
+    let deadline = BONUS_WITHDRAWAL + MIN_BONUS_CLAIM_PERIOD;
+    let (admin, fee_recipient_token_account) = (test.admin, test.fee_recipient_token_account);
+    let start_next_round = instructions::start_next_round(&PROGRAM_ID, &admin);
+
+    test.warp_to(BONUS_WITHDRAWAL);
+    test.withdraw_bonus(&on_time).unwrap();
+    assert_eq!(
+        test.process(start_next_round.clone(), &[admin]),
+        Err(error(ErrorCode::BonusClaimPeriodNotExpired))
+    );
+
+    test.warp_to(deadline);
+    assert_eq!(test.withdraw_bonus(&late), Err(error(ErrorCode::BonusClaimPeriodExpired)));
+    assert_eq!(
+        test.withdraw_redistributed_bonus(&on_time),
+        Err(error(ErrorCode::RedistributionNotEnabled))
+    );
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::UnclaimedBonusNotSweepable))
+    );
+    assert_eq!(
+        test.process(start_next_round.clone(), &[admin]),
+        Err(error(ErrorCode::RoundStillStaked))
+    );
+
+    test.withdraw_all(&on_time).unwrap();
+    test.withdraw_all(&late).unwrap();
+    let intruder = test.add_staker(0).wallet;
+    assert_eq!(
+        test.process(instructions::start_next_round(&PROGRAM_ID, &intruder), &[intruder]),
+        Err(error(ErrorCode::Unauthorized))
+    );
+    test.process(start_next_round, &[admin]).unwrap();
+
+    let pool = test.pool();
+    assert_eq!(pool.round, 1);
+    assert_eq!(pool.penalty_vault_amount, 20_000 * QST);
+    assert_eq!(pool.first_stake_timestamp, 0);
+    assert_eq!(pool.total_enrolled_stake, 0);
+    assert!(!pool.bonus_snapshot_taken);
+    assert_eq!(pool.last_admin_action, deadline);
+    assert_eq!(
+        test.collect_dust(&admin, &fee_recipient_token_account),
+        Err(error(ErrorCode::StakeWindowNotStarted))
+    );
+
+    // The stale enrollment doesn't carry a claim into the new round
+    assert_eq!(test.withdraw_bonus(&late), Err(error(ErrorCode::NotEnrolledInBonus)));
+
+    // The rolled-over vault is the next round's bonus
+    test.process(instructions::start_stake_window(&PROGRAM_ID, &admin), &[admin])
+        .unwrap();
+    test.stake_and_enroll(&on_time, 200_000 * QST).unwrap();
+    test.stake_and_enroll(&late, 200_000 * QST).unwrap();
+    let account = test.stake_account(&late.wallet).unwrap();
+    assert_eq!(account.round, 1);
+    assert!(!account.bonus_claimed);
+
+    test.warp_to(test.pool().bonus_withdrawal_time());
+    test.withdraw_bonus(&on_time).unwrap();
+    test.withdraw_bonus(&late).unwrap();
+    assert_eq!(test.token_balance(&on_time.token_account), 30_000 * QST);
+    assert_eq!(test.token_balance(&late.token_account), 10_000 * QST);
+    assert_eq!(test.pool().penalty_vault_amount, 0);
+}
+
+#[test]
+fn next_round_requires_the_roll_over_policy() {
+    let Round { mut test, on_time, late } = round(UnclaimedBonusPolicy::Sweep, MIN_BONUS_CLAIM_PERIOD);
+    test.warp_to(BONUS_WITHDRAWAL + MIN_BONUS_CLAIM_PERIOD);
+    test.withdraw_all(&on_time).unwrap();
+    test.withdraw_all(&late).unwrap();
+
+    let admin = test.admin;
+    assert_eq!(
+        test.process(instructions::start_next_round(&PROGRAM_ID, &admin), &[admin]),
+        Err(error(ErrorCode::RollOverNotEnabled))
+    );
+    assert_eq!(test.pool().round, 0);
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use common::{error, Staker, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, BONUS_ENROLLMENT_PERIOD, EARLY_UNSTAKE_THRESHOLD_1, EARLY_UNSTAKE_THRESHOLD_2, ID as PROGRAM_ID,
+    PRINCIPAL_LOCK_PERIOD, STAKE_WINDOW_PERIOD,
+};
+
+const STAKE: u64 = 400_000 * QST;
+const UNLOCK: i64 = START_TIME + PRINCIPAL_LOCK_PERIOD;
+
+// One 400k position staked when the window opened (principal unlocks at UNLOCK)
+fn staked() -> (TestPool, Staker) {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(STAKE);
+    test.stake(&staker, STAKE).unwrap();
+    (test, staker)
+}
+
+fn assert_unstaked(test: &TestPool, staker: &Staker, amount: u64, penalty: u64) {
+    assert_eq!(test.token_balance(&staker.token_account), amount - penalty);
+    assert_eq!(test.token_balance(&test.pool_token_account), STAKE - amount + penalty);
+    let pool = test.pool();
+    assert_eq!(pool.total_staked, STAKE - amount);
+    assert_eq!(pool.penalty_vault_amount, penalty);
+    assert_eq!(test.stake_account(&staker.wallet).unwrap().amount, STAKE - amount);
+}
+
+#[test]
+fn blocked_more_than_fifteen_days_out() {
+    let (mut test, staker) = staked();
+    test.warp_to(UNLOCK - EARLY_UNSTAKE_THRESHOLD_2 - 1);
+
+    assert_eq!(test.unstake(&staker, STAKE, STAKE), Err(error(ErrorCode::UnstakeBlocked)));
+}
+
+#[test]
+fn early_tier_charges_thirty_percent() {
+    let (mut test, staker) = staked();
+    test.warp_to(UNLOCK - EARLY_UNSTAKE_THRESHOLD_2);
+
+    test.unstake(&staker, STAKE, STAKE).unwrap();
+    assert_unstaked(&test, &staker, STAKE, 120_000 * QST);
+    assert_eq!(test.events().len(), 1);
+}
+
+#[test]
+fn late_tier_charges_twenty_percent() {
+    let (mut test, staker) = staked();
+    test.warp_to(UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+
+    test.unstake(&staker, STAKE / 2, STAKE).unwrap();
+    assert_unstaked(&test, &staker, STAKE / 2, 40_000 * QST);
+
+    test.warp_to(UNLOCK - 1);
+    test.unstake(&staker, STAKE / 2, STAKE).unwrap();
+    assert_unstaked(&test, &staker, STAKE, 80_000 * QST);
+}
+
+#[test]
+fn unlocked_positions_pay_no_penalty() {
+    let (mut test, staker) = staked();
+    test.warp_to(UNLOCK);
+
+    test.unstake(&staker, STAKE, 0).unwrap();
+    assert_unstaked(&test, &staker, STAKE, 0);
+}
+
+#[test]
+fn rejects_penalty_above_the_agreed_maximum() {
+    let (mut test, staker) = staked();
+    test.warp_to(UNLOCK - EARLY_UNSTAKE_THRESHOLD_2);
+
+    assert_eq!(
+        test.unstake(&staker, STAKE, 120_000 * QST - 1),
+        Err(error(ErrorCode::PenaltyExceedsMaximum))
+    );
+    assert_unstaked(&test, &staker, 0, 0);
+}
+
+#[test]
+fn rejects_invalid_amounts() {
+    let (mut test, staker) = staked();
+    test.warp_to(UNLOCK);
+
+    assert_eq!(test.unstake(&staker, 0, 0), Err(error(ErrorCode::InvalidAmount)));
+    assert_eq!(
+        test.unstake(&staker, STAKE + 1, 0),
+        Err(error(ErrorCode::InsufficientStakeBalance))
+    );
+}
+
+#[test]
+fn rejects_unstaking_while_the_window_is_open() {
+    let (mut test, staker) = staked();
+    test.warp_to(START_TIME + STAKE_WINDOW_PERIOD);
+
+    assert_eq!(
+        test.unstake(&staker, STAKE, STAKE),
+        Err(error(ErrorCode::StakeWindowStillActive))
+    );
+}
+
+#[test]
+fn rejects_bonus_enrollees() {
+    let (mut test, staker) = staked();
+    test.enroll(&staker).unwrap();
+    test.warp_to(UNLOCK + DAY);
+
+    assert_eq!(
+        test.unstake(&staker, STAKE, STAKE),
+        Err(error(ErrorCode::BonusEnrolledCannotUnstake))
+    );
+}
+
+// An enrolled 400k position that staked another 200k after the enrollment deadline
+fn enrolled_with_late_stake() -> (TestPool, Staker, i64) {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(STAKE + STAKE / 2);
+    test.stake(&staker, STAKE).unwrap();
+    test.enroll(&staker).unwrap();
+    let late_stake_time = START_TIME + BONUS_ENROLLMENT_PERIOD + 1;
+    test.warp_to(late_stake_time);
+    test.stake(&staker, STAKE / 2).unwrap();
+    (test, staker, late_stake_time + PRINCIPAL_LOCK_PERIOD)
+}
+
+#[test]
+fn bonus_enrollees_unstake_only_their_late_stake() {
+    let (mut test, staker, late_unlock) = enrolled_with_late_stake();
+    test.warp_to(late_unlock - EARLY_UNSTAKE_THRESHOLD_1);
+
+    assert_eq!(
+        test.unstake(&staker, STAKE / 2 + 1, STAKE),
+        Err(error(ErrorCode::BonusEnrolledCannotUnstake))
+    );
+
+    // Late tier counted from the late stake's own unlock
+    test.unstake(&staker, STAKE / 2, STAKE).unwrap();
+    assert_eq!(test.token_balance(&staker.token_account), 160_000 * QST);
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert_eq!((position.amount, position.late_stake, position.late_stake_unlock_time), (STAKE, 0, 0));
+    assert!(position.enrolled_in_bonus);
+    let pool = test.pool();
+    assert_eq!(pool.total_enrolled_stake, STAKE);
+    assert_eq!(pool.penalty_vault_amount, 40_000 * QST);
+}
+
+#[test]
+fn exiting_the_bonus_leaves_the_late_stake_staked() {
+    let (mut test, staker, late_unlock) = enrolled_with_late_stake();
+    test.warp_to(START_TIME + STAKE_WINDOW_PERIOD + DAY);
+
+    let exit = instructions::exit_enrolled(&PROGRAM_ID, &staker.wallet, &staker.token_account, &test.pool_token_account);
+    test.process(exit, &[staker.wallet]).unwrap();
+
+    // Only the enrolled principal pays the exit penalty
+    assert_eq!(test.token_balance(&staker.token_account), 240_000 * QST);
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert!(!position.enrolled_in_bonus);
+    assert_eq!(position.amount, STAKE / 2);
+    assert_eq!(position.principal_unlock_time, late_unlock);
+    assert_eq!(position.late_stake, 0);
+    assert_eq!(test.pool().total_staked, STAKE / 2);
+
+    test.warp_to(late_unlock);
+    test.withdraw_all(&staker).unwrap();
+    assert_eq!(test.token_balance(&staker.token_account), 440_000 * QST);
+}
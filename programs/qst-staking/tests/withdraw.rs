@@ -0,0 +1,211 @@
+// withdraw_all and withdraw_bonus
+mod common;
+
+use common::{error, Staker, TestPool, DAY, QST, START_TIME};
+use qst_staking_client::instructions;
+use qst_staking_mainnet::{
+    ErrorCode, BONUS_LOCK_PERIOD, BONUS_WITHDRAWAL_DELAY, EARLY_UNSTAKE_THRESHOLD_1, ID as PROGRAM_ID,
+    PRINCIPAL_LOCK_PERIOD,
+};
+
+const PRINCIPAL_UNLOCK: i64 = START_TIME + PRINCIPAL_LOCK_PERIOD;
+const BONUS_UNLOCK: i64 = PRINCIPAL_UNLOCK + BONUS_LOCK_PERIOD;
+const BONUS_WITHDRAWAL: i64 = BONUS_UNLOCK + BONUS_WITHDRAWAL_DELAY;
+
+// alice (400k) and bob (200k) enroll; carol (200k) unstakes in the late tier and
+// leaves 40k in the penalty vault
+struct BonusPool {
+    test: TestPool,
+    alice: Staker,
+    bob: Staker,
+    carol: Staker,
+}
+
+fn bonus_pool() -> BonusPool {
+    let mut test = TestPool::started();
+    let alice = test.add_staker(400_000 * QST);
+    let bob = test.add_staker(200_000 * QST);
+    let carol = test.add_staker(200_000 * QST);
+    test.stake(&alice, 400_000 * QST).unwrap();
+    test.stake(&bob, 200_000 * QST).unwrap();
+    test.stake(&carol, 200_000 * QST).unwrap();
+    test.enroll(&alice).unwrap();
+    test.enroll(&bob).unwrap();
+
+    test.warp_to(PRINCIPAL_UNLOCK - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&carol, 200_000 * QST, 40_000 * QST).unwrap();
+    assert_eq!(test.pool().penalty_vault_amount, 40_000 * QST);
+
+    BonusPool { test, alice, bob, carol }
+}
+
+#[test]
+fn withdraws_principal_once_unlocked() {
+    let mut test = TestPool::started();
+    let staker = test.add_staker(400_000 * QST);
+    test.stake(&staker, 400_000 * QST).unwrap();
+
+    test.warp_to(PRINCIPAL_UNLOCK - 1);
+    assert_eq!(test.withdraw_all(&staker), Err(error(ErrorCode::StillLocked)));
+
+    test.warp_to(PRINCIPAL_UNLOCK);
+    test.withdraw_all(&staker).unwrap();
+    let position = test.stake_account(&staker.wallet).unwrap();
+    assert_eq!(position.amount, 0);
+    assert_eq!(position.node_keys_earned, 4);
+    assert_eq!(position.principal_unlock_time, 0);
+    assert_eq!(test.pool().total_staked, 0);
+    assert_eq!(test.token_balance(&staker.token_account), 400_000 * QST);
+    assert_eq!(test.events().len(), 1);
+
+    assert_eq!(test.withdraw_all(&staker), Err(error(ErrorCode::NoStakeToWithdraw)));
+}
+
+#[test]
+fn enrollees_wait_for_the_bonus_unlock() {
+    let BonusPool { mut test, alice, .. } = bonus_pool();
+
+    test.warp_to(PRINCIPAL_UNLOCK);
+    assert_eq!(test.withdraw_all(&alice), Err(error(ErrorCode::StillLocked)));
+
+    test.warp_to(BONUS_UNLOCK);
+    test.withdraw_all(&alice).unwrap();
+    assert_eq!(test.token_balance(&alice.token_account), 400_000 * QST);
+    // Enrollment and bonus weight survive until withdraw_bonus
+    assert!(test.stake_account(&alice.wallet).unwrap().enrolled_in_bonus);
+    assert_eq!(test.pool().total_enrolled_stake, 600_000 * QST);
+}
+
+#[test]
+fn bonus_is_shared_pro_rata() {
+    let BonusPool { mut test, alice, bob, .. } = bonus_pool();
+    test.warp_to(BONUS_UNLOCK);
+    test.withdraw_all(&alice).unwrap();
+    test.withdraw_all(&bob).unwrap();
+
+    test.warp_to(BONUS_WITHDRAWAL - 1);
+    assert_eq!(
+        test.withdraw_bonus(&alice),
+        Err(error(ErrorCode::BonusWithdrawalNotYetAvailable))
+    );
+
+    test.warp_to(BONUS_WITHDRAWAL);
+    test.withdraw_bonus(&alice).unwrap();
+    let alice_bonus = 40_000 * QST * 2 / 3;
+    assert_eq!(test.token_balance(&alice.token_account), 400_000 * QST + alice_bonus);
+    assert!(test.stake_account(&alice.wallet).unwrap().bonus_claimed);
+    assert_eq!(test.pool().total_enrolled_stake, 200_000 * QST);
+    assert_eq!(test.events().len(), 1);
+
+    // Bob's share comes from the same snapshot; the unit lost to flooring stays behind as dust
+    test.withdraw_bonus(&bob).unwrap();
+    let bob_bonus = 40_000 * QST / 3;
+    assert_eq!(test.token_balance(&bob.token_account), 200_000 * QST + bob_bonus);
+    let pool = test.pool();
+    assert_eq!(pool.penalty_vault_amount, 1);
+    assert_eq!(pool.rounding_dust, 1);
+    assert_eq!(pool.total_enrolled_stake, 0);
+    assert_eq!(test.token_balance(&test.pool_token_account), 1);
+
+    assert_eq!(test.withdraw_bonus(&alice), Err(error(ErrorCode::BonusAlreadyClaimed)));
+}
+
+#[test]
+fn bonus_requires_enrollment() {
+    let BonusPool { mut test, carol, .. } = bonus_pool();
+    test.warp_to(BONUS_WITHDRAWAL);
+
+    assert_eq!(test.withdraw_bonus(&carol), Err(error(ErrorCode::NotEnrolledInBonus)));
+}
+
+#[test]
+fn bonus_is_claimable_with_principal_still_staked() {
+    let BonusPool { mut test, alice, bob, .. } = bonus_pool();
+    test.warp_to(BONUS_WITHDRAWAL);
+
+    test.withdraw_bonus(&alice).unwrap();
+    let alice_bonus = 40_000 * QST * 2 / 3;
+    assert_eq!(test.token_balance(&alice.token_account), alice_bonus);
+    let position = test.stake_account(&alice.wallet).unwrap();
+    assert_eq!(position.amount, 400_000 * QST);
+    assert!(position.enrolled_in_bonus);
+    assert!(position.bonus_claimed);
+    assert_eq!(test.pool().total_staked, 600_000 * QST);
+    assert_eq!(test.pool().total_enrolled_stake, 200_000 * QST);
+    assert_eq!(test.withdraw_bonus(&alice), Err(error(ErrorCode::BonusAlreadyClaimed)));
+
+    // Claiming first doesn't change anyone's share or the principal
+    test.withdraw_all(&bob).unwrap();
+    test.withdraw_bonus(&bob).unwrap();
+    assert_eq!(test.token_balance(&bob.token_account), 200_000 * QST + 40_000 * QST / 3);
+    test.warp_to(BONUS_WITHDRAWAL + 30 * DAY);
+    test.withdraw_all(&alice).unwrap();
+    assert_eq!(test.token_balance(&alice.token_account), 400_000 * QST + alice_bonus);
+    assert_eq!(test.token_balance(&test.pool_token_account), test.pool().rounding_dust);
+}
+
+#[test]
+fn late_stake_keeps_its_own_unlock_without_moving_the_bonus_date() {
+    let mut test = TestPool::started();
+    let admin = test.admin;
+    test.process(
+        instructions::extend_stake_window(&PROGRAM_ID, &admin, START_TIME + 20 * DAY),
+        &[admin],
+    )
+    .unwrap();
+    let alice = test.add_staker(400_000 * QST);
+    let bob = test.add_staker(200_000 * QST);
+    let carol = test.add_staker(200_000 * QST);
+    let dave = test.add_staker(200_000 * QST);
+    test.stake(&alice, 200_000 * QST).unwrap();
+    test.stake(&bob, 200_000 * QST).unwrap();
+    test.stake(&carol, 200_000 * QST).unwrap();
+    test.enroll(&alice).unwrap();
+    test.enroll(&bob).unwrap();
+
+    // After the enrollment deadline: principal-only, unlocking past alice's bonus unlock
+    test.warp_to(START_TIME + 15 * DAY);
+    test.stake(&alice, 200_000 * QST).unwrap();
+    assert_eq!(test.pool().latest_bonus_unlock_time, BONUS_UNLOCK);
+
+    // Staked in the extended window, so dave's principal unlocks after the claim date
+    test.warp_to(START_TIME + 19 * DAY);
+    test.stake(&dave, 200_000 * QST).unwrap();
+
+    test.warp_to(START_TIME + 20 * DAY + 1);
+    test.unstake(&carol, 200_000 * QST, 40_000 * QST).unwrap();
+
+    // The enrolled principal unlocks on schedule; the late stake waits for its own unlock
+    test.warp_to(BONUS_UNLOCK);
+    test.withdraw_all(&alice).unwrap();
+    assert_eq!(test.token_balance(&alice.token_account), 200_000 * QST);
+    assert_eq!(test.withdraw_all(&alice), Err(error(ErrorCode::StillLocked)));
+    test.withdraw_all(&bob).unwrap();
+
+    test.warp_to(START_TIME + 15 * DAY + PRINCIPAL_LOCK_PERIOD);
+    test.withdraw_all(&alice).unwrap();
+    let position = test.stake_account(&alice.wallet).unwrap();
+    assert_eq!((position.amount, position.late_stake, position.late_stake_unlock_time), (0, 0, 0));
+
+    // Neither the late stake nor the extended window moves the claim date; weights are 200k each
+    assert_eq!(test.pool().bonus_withdrawal_time(), BONUS_WITHDRAWAL);
+    test.warp_to(BONUS_WITHDRAWAL - 1);
+    assert_eq!(
+        test.withdraw_bonus(&bob),
+        Err(error(ErrorCode::BonusWithdrawalNotYetAvailable))
+    );
+    test.warp_to(BONUS_WITHDRAWAL);
+    test.withdraw_bonus(&bob).unwrap();
+
+    // A penalty paid after the snapshot is unclaimed bonus: no share grows, and it isn't dust
+    test.warp_to(START_TIME + 19 * DAY + PRINCIPAL_LOCK_PERIOD - EARLY_UNSTAKE_THRESHOLD_1);
+    test.unstake(&dave, 200_000 * QST, 40_000 * QST).unwrap();
+    test.withdraw_bonus(&alice).unwrap();
+    assert_eq!(test.token_balance(&bob.token_account), 200_000 * QST + 20_000 * QST);
+    assert_eq!(test.token_balance(&alice.token_account), 400_000 * QST + 20_000 * QST);
+    let pool = test.pool();
+    assert_eq!(pool.total_enrolled_stake, 0);
+    assert_eq!(pool.penalty_vault_amount, 40_000 * QST);
+    assert_eq!(pool.post_snapshot_penalties, 40_000 * QST);
+    assert_eq!(pool.rounding_dust, 0);
+}
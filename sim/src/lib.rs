@@ -0,0 +1,9 @@
+//! Replays staking scenarios offline with the same math the program runs on-chain.
+//!
+//! - [`scenario`]: pool parameters (mainnet defaults) and timed user actions
+//! - [`model`]: pool and position state, following the program's checks and accounting
+//! - [`report`]: penalties, bonus yields, dust and key distribution
+
+pub mod model;
+pub mod report;
+pub mod scenario;
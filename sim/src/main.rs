@@ -0,0 +1,41 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::Parser;
+use qst_sim::model::Simulation;
+use qst_sim::report::Report;
+use qst_sim::scenario::Scenario;
+
+#[derive(Parser)]
+#[command(name = "qst-sim", about = "Replay QST staking scenarios with the program's math")]
+struct Cli {
+    /// Scenario files (JSON: optional `params`, timed `actions`)
+    #[arg(required = true)]
+    scenarios: Vec<PathBuf>,
+
+    /// Override a parameter for every scenario, e.g. `--set late_rate=25` (repeatable)
+    #[arg(long = "set", value_name = "NAME=VALUE")]
+    overrides: Vec<String>,
+
+    /// Print reports as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    for path in &cli.scenarios {
+        let mut scenario = Scenario::load(path)?;
+        for assignment in &cli.overrides {
+            scenario.set_param(assignment)?;
+        }
+        let report = Report::new(&Simulation::run(&scenario));
+        if cli.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else {
+            println!("== {} ==", path.display());
+            println!("{report}");
+        }
+    }
+    Ok(())
+}
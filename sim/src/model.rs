@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use qst_staking_math::{penalty_amount, pro_rata_share, PenaltySchedule, StakeError, StakeRules};
+use serde::Serialize;
+
+use crate::scenario::{Action, ActionKind, Params, Scenario, QST};
+
+// A user's stake account; times are seconds after the stake window opened
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Position {
+    pub amount: u64,
+    pub node_keys: u32,
+    pub principal_unlock: i64,
+    pub bonus_unlock: i64,
+    pub enrolled: bool,
+    // Bonus weight, frozen at the enrollment deadline
+    pub enrolled_stake: u64,
+    // Stake an enrollee added after the deadline, with its own unlock
+    pub late_stake: u64,
+    pub late_unlock: i64,
+}
+
+// Tokens a user moved in and out of the pool, in base units
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Ledger {
+    pub deposited: u64,
+    pub principal_returned: u64,
+    pub unstake_penalties: u64,
+    pub exit_penalties: u64,
+    pub bonus: u64,
+}
+
+// An action the program would have rejected, with the ErrorCode it would have returned
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Rejection {
+    pub at: i64,
+    pub user: String,
+    pub action: &'static str,
+    pub error: &'static str,
+}
+
+// Pool state after a scenario has played out
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Outcome {
+    pub positions: BTreeMap<String, Position>,
+    pub ledgers: BTreeMap<String, Ledger>,
+    // Enrolled weight of each user who claimed a bonus (frozen at the enrollment deadline)
+    pub bonus_weights: BTreeMap<String, u64>,
+    pub rejections: Vec<Rejection>,
+    pub bonus_withdrawal_time: Option<i64>,
+    pub dust: u64,
+}
+
+// The stake_tokens / unstake_tokens / exit_enrolled / withdraw_* state machine. Caps, the
+// allowlist phase, settlement and the claim deadline are not modelled.
+pub struct Simulation {
+    params: Params,
+    rules: StakeRules,
+    schedule: PenaltySchedule,
+    positions: BTreeMap<String, Position>,
+    ledgers: BTreeMap<String, Ledger>,
+    total_staked: u64,
+    total_enrolled_stake: u64,
+    penalty_vault: u64,
+    latest_bonus_unlock: i64,
+}
+
+impl Simulation {
+    pub fn new(params: Params) -> Self {
+        Self {
+            rules: params.stake_rules(),
+            schedule: params.penalty_schedule(),
+            params,
+            positions: BTreeMap::new(),
+            ledgers: BTreeMap::new(),
+            total_staked: 0,
+            total_enrolled_stake: 0,
+            penalty_vault: 0,
+            latest_bonus_unlock: 0,
+        }
+    }
+
+    // Plays the actions in time order (ties keep file order), then every remaining position
+    // withdraws at its unlock and every enrollee claims the bonus, in user order
+    pub fn run(scenario: &Scenario) -> Outcome {
+        let mut simulation = Self::new(scenario.params.clone());
+        let mut actions: Vec<&Action> = scenario.actions.iter().collect();
+        actions.sort_by_key(|action| action.at);
+
+        let mut rejections = Vec::new();
+        for action in actions {
+            if let Err(error) = simulation.apply(action) {
+                rejections.push(Rejection {
+                    at: action.at,
+                    user: action.user.clone(),
+                    action: action.kind.name(),
+                    error,
+                });
+            }
+        }
+        let mut outcome = simulation.finish();
+        outcome.rejections = rejections;
+        outcome
+    }
+
+    pub fn apply(&mut self, action: &Action) -> Result<(), &'static str> {
+        let at = action.at;
+        match action.kind {
+            ActionKind::Stake { amount } => self.stake(&action.user, amount, at),
+            ActionKind::Enroll => self.enroll(&action.user, at),
+            ActionKind::Unenroll => self.unenroll(&action.user, at),
+            ActionKind::Unstake { amount } => self.unstake(&action.user, amount, at),
+            ActionKind::ExitEnrolled => self.exit_enrolled(&action.user, at),
+        }
+    }
+
+    fn stake(&mut self, user: &str, amount: u64, at: i64) -> Result<(), &'static str> {
+        let amount = amount.checked_mul(QST).ok_or("NumericOverflow")?;
+        let node_keys = self.rules.node_keys_for(amount).map_err(stake_error)?;
+        if at < 0 {
+            return Err("StakeWindowNotStarted");
+        }
+        if at > self.params.stake_window_period {
+            return Err("StakeWindowClosed");
+        }
+
+        let position = self.positions.entry(user.to_string()).or_default();
+        position.amount = position.amount.checked_add(amount).ok_or("NumericOverflow")?;
+        position.node_keys = position.node_keys.checked_add(node_keys).ok_or("NumericOverflow")?;
+        // Every stake restarts the lock from now; after the enrollment deadline an enrollee's
+        // stake is principal-only and locks apart from the enrolled principal
+        if position.enrolled && at > self.params.bonus_enrollment_period {
+            position.late_stake += amount;
+            position.late_unlock = at + self.params.principal_lock_period;
+        } else {
+            position.principal_unlock = at + self.params.principal_lock_period;
+        }
+        if position.enrolled && at <= self.params.bonus_enrollment_period {
+            position.bonus_unlock = position.principal_unlock + self.params.bonus_lock_period;
+            self.latest_bonus_unlock = self.latest_bonus_unlock.max(position.bonus_unlock);
+            position.enrolled_stake += amount;
+            self.total_enrolled_stake += amount;
+        }
+        self.total_staked += amount;
+        self.ledgers.entry(user.to_string()).or_default().deposited += amount;
+        Ok(())
+    }
+
+    fn enroll(&mut self, user: &str, at: i64) -> Result<(), &'static str> {
+        let deadline = self.params.bonus_enrollment_period;
+        let position = self.positions.get_mut(user).ok_or("AccountNotInitialized")?;
+        if at > deadline {
+            return Err("BonusEnrollmentClosed");
+        }
+        if position.amount == 0 {
+            return Err("NoStakeToEnroll");
+        }
+        if position.enrolled {
+            return Err("AlreadyEnrolledInBonus");
+        }
+
+        position.enrolled = true;
+        position.enrolled_stake = position.amount;
+        position.bonus_unlock = position.principal_unlock + self.params.bonus_lock_period;
+        self.latest_bonus_unlock = self.latest_bonus_unlock.max(position.bonus_unlock);
+        self.total_enrolled_stake += position.amount;
+        Ok(())
+    }
+
+    fn unenroll(&mut self, user: &str, at: i64) -> Result<(), &'static str> {
+        let deadline = self.params.bonus_enrollment_period;
+        let position = self.positions.get_mut(user).ok_or("AccountNotInitialized")?;
+        if at > deadline {
+            return Err("BonusEnrollmentClosed");
+        }
+        if !position.enrolled {
+            return Err("NotEnrolledInBonus");
+        }
+
+        // latest_bonus_unlock stays: it only ever delays withdraw_bonus
+        position.enrolled = false;
+        position.bonus_unlock = 0;
+        self.total_enrolled_stake -= std::mem::take(&mut position.enrolled_stake);
+        Ok(())
+    }
+
+    fn unstake(&mut self, user: &str, amount: u64, at: i64) -> Result<(), &'static str> {
+        let amount = amount.checked_mul(QST).ok_or("NumericOverflow")?;
+        let window_end = self.params.stake_window_period;
+        let position = self.positions.get_mut(user).ok_or("AccountNotInitialized")?;
+        if amount == 0 {
+            return Err("InvalidAmount");
+        }
+        if position.amount < amount {
+            return Err("InsufficientStakeBalance");
+        }
+        let (unstakeable, unlock) = if position.enrolled {
+            (position.late_stake, position.late_unlock)
+        } else {
+            (position.amount, position.principal_unlock)
+        };
+        if amount > unstakeable {
+            return Err("BonusEnrolledCannotUnstake");
+        }
+        if at <= window_end {
+            return Err("StakeWindowStillActive");
+        }
+        let (penalty, net) = self.schedule.unstake_outcome(amount, unlock - at).ok_or("UnstakeBlocked")?;
+
+        if position.enrolled {
+            position.late_stake -= amount;
+        }
+        position.amount -= amount;
+        self.total_staked -= amount;
+        self.penalty_vault += penalty;
+        let ledger = self.ledgers.entry(user.to_string()).or_default();
+        ledger.principal_returned += net;
+        ledger.unstake_penalties += penalty;
+        Ok(())
+    }
+
+    fn exit_enrolled(&mut self, user: &str, at: i64) -> Result<(), &'static str> {
+        let window_end = self.params.stake_window_period;
+        let rate = self.params.enrolled_exit_penalty_rate;
+        let position = self.positions.get_mut(user).ok_or("AccountNotInitialized")?;
+        if !position.enrolled {
+            return Err("NotEnrolledInBonus");
+        }
+        if position.amount == 0 {
+            return Err("NoStakeToWithdraw");
+        }
+        if at <= window_end {
+            return Err("StakeWindowStillActive");
+        }
+        if at >= position.bonus_unlock {
+            return Err("AlreadyUnlocked");
+        }
+
+        // A late stake stays staked as a plain position
+        let amount = position.amount - position.late_stake;
+        let enrolled_stake = position.enrolled_stake;
+        let penalty = penalty_amount(amount, rate);
+        // The bonus share is forfeited; node keys are permanent
+        *position = Position {
+            amount: position.late_stake,
+            node_keys: position.node_keys,
+            principal_unlock: position.late_unlock,
+            ..Position::default()
+        };
+        self.total_staked -= amount;
+        self.total_enrolled_stake -= enrolled_stake;
+        self.penalty_vault += penalty;
+        let ledger = self.ledgers.entry(user.to_string()).or_default();
+        ledger.principal_returned += amount - penalty;
+        ledger.exit_penalties += penalty;
+        Ok(())
+    }
+
+    // withdraw_all for every open position, then withdraw_bonus for every enrollee
+    fn finish(mut self) -> Outcome {
+        for (user, position) in self.positions.iter_mut() {
+            if position.amount > 0 {
+                let amount = std::mem::take(&mut position.amount);
+                self.total_staked -= amount;
+                self.ledgers.entry(user.clone()).or_default().principal_returned += amount;
+            }
+        }
+
+        // Every share comes from the snapshot taken on the first claim, as withdraw_bonus does
+        let (snapshot_vault, snapshot_weight) = (self.penalty_vault, self.total_enrolled_stake);
+        let mut bonus_weights = BTreeMap::new();
+        let mut bonus_withdrawal_time = None;
+        for (user, position) in self.positions.iter_mut() {
+            if !position.enrolled {
+                continue;
+            }
+            bonus_withdrawal_time = Some(self.latest_bonus_unlock + self.params.bonus_withdrawal_delay);
+            let weight = std::mem::take(&mut position.enrolled_stake);
+            let bonus = pro_rata_share(snapshot_vault, weight, snapshot_weight);
+            self.penalty_vault -= bonus;
+            self.total_enrolled_stake -= weight;
+            position.enrolled = false;
+            self.ledgers.entry(user.clone()).or_default().bonus += bonus;
+            bonus_weights.insert(user.clone(), weight);
+        }
+
+        Outcome {
+            positions: self.positions,
+            ledgers: self.ledgers,
+            bonus_weights,
+            rejections: Vec::new(),
+            bonus_withdrawal_time,
+            dust: self.penalty_vault,
+        }
+    }
+}
+
+// The ErrorCode stake_tokens maps each StakeError to
+fn stake_error(error: StakeError) -> &'static str {
+    match error {
+        StakeError::Zero => "InvalidAmount",
+        StakeError::BelowMinimum => "InsufficientStakeAmount",
+        StakeError::AboveMaximum => "StakeAmountTooLarge",
+        StakeError::NotMultiple => "InvalidStakeAmount",
+        StakeError::KeyOverflow => "NumericOverflow",
+    }
+}
+
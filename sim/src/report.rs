@@ -0,0 +1,177 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::model::{Outcome, Rejection};
+use crate::scenario::{DECIMALS, QST};
+
+// What a scenario cost and paid each user, in base units
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UserReport {
+    pub user: String,
+    pub deposited: u64,
+    pub node_keys: u32,
+    pub principal_returned: u64,
+    pub penalties: u64,
+    pub bonus: u64,
+    // Bonus as a percentage of the enrolled weight; None for users who were not paid a bonus
+    pub bonus_yield_percent: Option<f64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Totals {
+    pub deposited: u64,
+    pub unstake_penalties: u64,
+    pub exit_penalties: u64,
+    pub bonus_paid: u64,
+    // Penalties left in the vault after every enrollee claimed (rounding dust)
+    pub dust: u64,
+    pub bonus_withdrawal_time: Option<i64>,
+}
+
+// How node keys ended up spread across holders
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct KeyDistribution {
+    pub total: u64,
+    pub holders: usize,
+    pub largest: u32,
+    // Share of all keys held by the largest holder
+    pub largest_percent: f64,
+    // (keys held, number of holders), ascending
+    pub histogram: Vec<(u32, usize)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Report {
+    pub totals: Totals,
+    pub keys: KeyDistribution,
+    pub users: Vec<UserReport>,
+    pub rejections: Vec<Rejection>,
+}
+
+impl Report {
+    pub fn new(outcome: &Outcome) -> Self {
+        let mut totals = Totals {
+            dust: outcome.dust,
+            bonus_withdrawal_time: outcome.bonus_withdrawal_time,
+            ..Totals::default()
+        };
+        let mut users = Vec::new();
+        for (user, ledger) in &outcome.ledgers {
+            totals.deposited += ledger.deposited;
+            totals.unstake_penalties += ledger.unstake_penalties;
+            totals.exit_penalties += ledger.exit_penalties;
+            totals.bonus_paid += ledger.bonus;
+            let bonus_yield_percent = outcome
+                .bonus_weights
+                .get(user)
+                .filter(|weight| **weight > 0)
+                .map(|weight| ledger.bonus as f64 * 100.0 / *weight as f64);
+            users.push(UserReport {
+                user: user.clone(),
+                deposited: ledger.deposited,
+                node_keys: outcome.positions.get(user).map_or(0, |position| position.node_keys),
+                principal_returned: ledger.principal_returned,
+                penalties: ledger.unstake_penalties + ledger.exit_penalties,
+                bonus: ledger.bonus,
+                bonus_yield_percent,
+            });
+        }
+
+        Self {
+            totals,
+            keys: KeyDistribution::new(users.iter().map(|user| user.node_keys)),
+            users,
+            rejections: outcome.rejections.clone(),
+        }
+    }
+}
+
+impl KeyDistribution {
+    fn new(keys: impl Iterator<Item = u32>) -> Self {
+        let held: Vec<u32> = keys.filter(|keys| *keys > 0).collect();
+        let total: u64 = held.iter().map(|keys| *keys as u64).sum();
+        let largest = held.iter().copied().max().unwrap_or(0);
+        let mut histogram: Vec<(u32, usize)> = Vec::new();
+        let mut sorted = held.clone();
+        sorted.sort_unstable();
+        for keys in sorted {
+            match histogram.last_mut() {
+                Some((held, count)) if *held == keys => *count += 1,
+                _ => histogram.push((keys, 1)),
+            }
+        }
+        Self {
+            total,
+            holders: held.len(),
+            largest,
+            largest_percent: if total == 0 { 0.0 } else { largest as f64 * 100.0 / total as f64 },
+            histogram,
+        }
+    }
+}
+
+// Base units as QST with all decimals
+pub fn format_qst(amount: u64) -> String {
+    format!("{}.{:0width$}", amount / QST, amount % QST, width = DECIMALS as usize)
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let totals = &self.totals;
+        writeln!(f, "Deposited:         {} QST", format_qst(totals.deposited))?;
+        writeln!(f, "Unstake penalties: {} QST", format_qst(totals.unstake_penalties))?;
+        writeln!(f, "Exit penalties:    {} QST", format_qst(totals.exit_penalties))?;
+        writeln!(f, "Bonus paid:        {} QST", format_qst(totals.bonus_paid))?;
+        writeln!(f, "Dust:              {} QST", format_qst(totals.dust))?;
+        if let Some(time) = totals.bonus_withdrawal_time {
+            writeln!(f, "Bonus claimable:   {:.2} days after the window opened", time as f64 / 86_400.0)?;
+        }
+
+        let keys = &self.keys;
+        writeln!(
+            f,
+            "Node keys:         {} across {} holders (largest {} keys, {:.1}%)",
+            keys.total, keys.holders, keys.largest, keys.largest_percent
+        )?;
+        for (held, count) in &keys.histogram {
+            writeln!(f, "  {held:>6} keys x {count}")?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<16} {:>22} {:>6} {:>22} {:>22} {:>22} {:>8}",
+            "user", "deposited", "keys", "returned", "penalties", "bonus", "yield"
+        )?;
+        for user in &self.users {
+            let bonus_yield = user
+                .bonus_yield_percent
+                .map_or_else(|| "-".to_string(), |percent| format!("{percent:.2}%"));
+            writeln!(
+                f,
+                "{:<16} {:>22} {:>6} {:>22} {:>22} {:>22} {:>8}",
+                user.user,
+                format_qst(user.deposited),
+                user.node_keys,
+                format_qst(user.principal_returned),
+                format_qst(user.penalties),
+                format_qst(user.bonus),
+                bonus_yield
+            )?;
+        }
+
+        if !self.rejections.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Rejected actions:")?;
+            for rejection in &self.rejections {
+                writeln!(
+                    f,
+                    "  t={:<10} {:<16} {:<14} {}",
+                    rejection.at, rejection.user, rejection.action, rejection.error
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
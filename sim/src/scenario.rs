@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use qst_staking_math::{PenaltySchedule, StakeRules};
+use serde::{Deserialize, Serialize};
+
+// QST mint decimals; scenario amounts are whole tokens
+pub const DECIMALS: u32 = 6;
+pub const QST: u64 = 10u64.pow(DECIMALS);
+
+const DAY: i64 = 24 * 60 * 60;
+
+// Pool parameters. Defaults are the mainnet program's constants; amounts are whole QST and
+// durations seconds, so a scenario file only lists what it wants to change.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Params {
+    pub minimum_stake: u64,
+    pub maximum_stake: u64,
+    pub keys_per_stake: u32,
+    pub stake_window_period: i64,
+    pub bonus_enrollment_period: i64,
+    pub principal_lock_period: i64,
+    pub bonus_lock_period: i64,
+    pub bonus_withdrawal_delay: i64,
+    pub late_threshold: i64,
+    pub early_threshold: i64,
+    pub late_rate: u64,
+    pub early_rate: u64,
+    pub enrolled_exit_penalty_rate: u64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            minimum_stake: 200_000,
+            maximum_stake: 10_000_000,
+            keys_per_stake: 2,
+            stake_window_period: 9 * DAY,
+            bonus_enrollment_period: 2 * DAY,
+            principal_lock_period: 25 * DAY,
+            bonus_lock_period: 10 * DAY,
+            bonus_withdrawal_delay: DAY,
+            late_threshold: 7 * DAY,
+            early_threshold: 15 * DAY,
+            late_rate: 20,
+            early_rate: 30,
+            enrolled_exit_penalty_rate: 40,
+        }
+    }
+}
+
+impl Params {
+    pub fn stake_rules(&self) -> StakeRules {
+        StakeRules {
+            minimum: self.minimum_stake * QST,
+            maximum: self.maximum_stake * QST,
+            keys_per_stake: self.keys_per_stake,
+        }
+    }
+
+    pub fn penalty_schedule(&self) -> PenaltySchedule {
+        PenaltySchedule {
+            late_threshold: self.late_threshold,
+            early_threshold: self.early_threshold,
+            late_rate: self.late_rate,
+            early_rate: self.early_rate,
+        }
+    }
+
+    // Rejects values the program's own checks would never allow
+    pub fn validate(&self) -> Result<()> {
+        if self.minimum_stake == 0 || self.keys_per_stake == 0 {
+            bail!("minimum_stake and keys_per_stake must be positive");
+        }
+        if self.maximum_stake < self.minimum_stake {
+            bail!("maximum_stake is below minimum_stake");
+        }
+        if self.maximum_stake.checked_mul(QST).is_none() {
+            bail!("maximum_stake overflows u64 base units");
+        }
+        if self.late_threshold > self.early_threshold {
+            bail!("late_threshold is after early_threshold");
+        }
+        if [self.late_rate, self.early_rate, self.enrolled_exit_penalty_rate]
+            .iter()
+            .any(|rate| *rate > 100)
+        {
+            bail!("penalty rates are percentages (0-100)");
+        }
+        Ok(())
+    }
+}
+
+// One user action at `at` seconds after the stake window opened
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    pub at: i64,
+    pub user: String,
+    #[serde(flatten)]
+    pub kind: ActionKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ActionKind {
+    Stake { amount: u64 },
+    Enroll,
+    Unenroll,
+    // Accepts whatever penalty the tier charges
+    Unstake { amount: u64 },
+    ExitEnrolled,
+}
+
+impl ActionKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActionKind::Stake { .. } => "stake",
+            ActionKind::Enroll => "enroll",
+            ActionKind::Unenroll => "unenroll",
+            ActionKind::Unstake { .. } => "unstake",
+            ActionKind::ExitEnrolled => "exit_enrolled",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    #[serde(default)]
+    pub params: Params,
+    pub actions: Vec<Action>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("reading {}", path.display()))?;
+        let scenario: Scenario =
+            serde_json::from_str(&json).with_context(|| format!("parsing {}", path.display()))?;
+        scenario.params.validate()?;
+        Ok(scenario)
+    }
+
+    // Applies a `name=value` override (e.g. `late_rate=25`) to the parameters
+    pub fn set_param(&mut self, assignment: &str) -> Result<()> {
+        let (name, value) = assignment
+            .split_once('=')
+            .with_context(|| format!("expected name=value, got {assignment}"))?;
+        let mut params = serde_json::to_value(&self.params)?;
+        let field = params
+            .get_mut(name)
+            .with_context(|| format!("unknown parameter {name}"))?;
+        *field = serde_json::from_str(value).with_context(|| format!("invalid value for {name}"))?;
+        self.params = serde_json::from_value(params).with_context(|| format!("invalid value for {name}"))?;
+        self.params.validate()
+    }
+}
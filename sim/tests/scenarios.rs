@@ -0,0 +1,137 @@
+// scenarios/launch.json: alice (400k) and bob (1M) enroll; carol leaves in the late tier (40k),
+// dave is blocked at 17 days and then pays the early rate on 200k (60k), greg exits enrolled
+// (80k). erin enrolls after the deadline and frank's 250k is not a multiple of the minimum.
+
+use std::path::Path;
+
+use qst_sim::model::Simulation;
+use qst_sim::report::Report;
+use qst_sim::scenario::{Action, ActionKind, Params, Scenario, QST};
+use qst_staking_mainnet as program;
+
+fn launch() -> Scenario {
+    Scenario::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/launch.json")).unwrap()
+}
+
+#[test]
+fn default_params_match_program() {
+    let params = Params::default();
+    assert_eq!(params.stake_rules(), program::STAKE_RULES);
+    assert_eq!(params.penalty_schedule(), program::PENALTY_SCHEDULE);
+    assert_eq!(params.stake_window_period, program::STAKE_WINDOW_PERIOD);
+    assert_eq!(params.bonus_enrollment_period, program::BONUS_ENROLLMENT_PERIOD);
+    assert_eq!(params.principal_lock_period, program::PRINCIPAL_LOCK_PERIOD);
+    assert_eq!(params.bonus_lock_period, program::BONUS_LOCK_PERIOD);
+    assert_eq!(params.bonus_withdrawal_delay, program::BONUS_WITHDRAWAL_DELAY);
+    assert_eq!(params.enrolled_exit_penalty_rate, program::ENROLLED_EXIT_PENALTY_RATE);
+}
+
+#[test]
+fn launch_scenario_report() {
+    let report = Report::new(&Simulation::run(&launch()));
+    let totals = &report.totals;
+    assert_eq!(totals.deposited, 2_600_000 * QST);
+    assert_eq!(totals.unstake_penalties, 100_000 * QST);
+    assert_eq!(totals.exit_penalties, 80_000 * QST);
+    assert_eq!(totals.bonus_paid + totals.dust, 180_000 * QST);
+
+    let alice = &report.users[0];
+    let bob = &report.users[1];
+    assert_eq!(alice.bonus, 180_000 * QST * 4 / 14);
+    // Shares come from one snapshot, so flooring both leaves a unit of dust
+    assert_eq!(bob.bonus, 180_000 * QST * 10 / 14);
+    assert_eq!(totals.dust, 1);
+    assert_eq!(alice.bonus_yield_percent.map(|percent| percent.round()), Some(13.0));
+
+    // Every deposited token came back as principal or bonus, or stayed as dust
+    let returned: u64 = report.users.iter().map(|user| user.principal_returned + user.bonus).sum();
+    assert_eq!(returned + totals.dust, totals.deposited);
+
+    // frank was never issued keys; greg kept his after exiting
+    assert_eq!(report.keys.total, 26);
+    assert_eq!(report.keys.holders, 6);
+    assert_eq!(report.keys.largest, 10);
+    assert_eq!(report.keys.histogram, vec![(2, 3), (4, 1), (6, 1), (10, 1)]);
+}
+
+#[test]
+fn rejections_name_the_program_error() {
+    let outcome = Simulation::run(&launch());
+    let rejections: Vec<_> = outcome
+        .rejections
+        .iter()
+        .map(|rejection| (rejection.user.as_str(), rejection.action, rejection.error))
+        .collect();
+    assert_eq!(
+        rejections,
+        vec![
+            ("erin", "enroll", "BonusEnrollmentClosed"),
+            ("frank", "stake", "InvalidStakeAmount"),
+            ("dave", "unstake", "UnstakeBlocked"),
+        ]
+    );
+}
+
+#[test]
+fn overrides_change_the_outcome() {
+    let mut scenario = launch();
+    scenario.set_param("late_rate=10").unwrap();
+    scenario.set_param("enrolled_exit_penalty_rate=0").unwrap();
+    let report = Report::new(&Simulation::run(&scenario));
+    assert_eq!(report.totals.unstake_penalties, 80_000 * QST);
+    assert_eq!(report.totals.exit_penalties, 0);
+
+    assert!(scenario.set_param("late_rate=101").is_err());
+    assert!(scenario.set_param("unknown=1").is_err());
+    assert!(scenario.set_param("late_rate").is_err());
+}
+
+#[test]
+fn stake_after_the_enrollment_deadline_earns_no_bonus_weight() {
+    let day = 86_400;
+    let action = |at: i64, user: &str, kind: ActionKind| Action { at, user: user.to_string(), kind };
+    let scenario = Scenario {
+        params: Params::default(),
+        actions: vec![
+            action(0, "alice", ActionKind::Stake { amount: 200_000 }),
+            action(0, "alice", ActionKind::Enroll),
+            action(0, "bob", ActionKind::Stake { amount: 200_000 }),
+            action(0, "bob", ActionKind::Enroll),
+            action(0, "carol", ActionKind::Stake { amount: 200_000 }),
+            action(3 * day, "alice", ActionKind::Stake { amount: 200_000 }),
+            action(20 * day, "carol", ActionKind::Unstake { amount: 200_000 }),
+        ],
+    };
+
+    let report = Report::new(&Simulation::run(&scenario));
+    let (alice, bob) = (&report.users[0], &report.users[1]);
+    assert_eq!(alice.deposited, 400_000 * QST);
+    assert_eq!(alice.bonus, 20_000 * QST);
+    assert_eq!(bob.bonus, 20_000 * QST);
+    assert_eq!(report.totals.dust, 0);
+}
+
+#[test]
+fn enrollees_can_unstake_only_their_late_stake() {
+    let day = 86_400;
+    let action = |at: i64, user: &str, kind: ActionKind| Action { at, user: user.to_string(), kind };
+    let scenario = Scenario {
+        params: Params::default(),
+        actions: vec![
+            action(0, "alice", ActionKind::Stake { amount: 200_000 }),
+            action(0, "alice", ActionKind::Enroll),
+            action(3 * day, "alice", ActionKind::Stake { amount: 200_000 }),
+            // 8 days before the late stake's own unlock: early tier, 30%
+            action(20 * day, "alice", ActionKind::Unstake { amount: 200_000 }),
+            action(20 * day, "alice", ActionKind::Unstake { amount: 200_000 }),
+        ],
+    };
+
+    let outcome = Simulation::run(&scenario);
+    assert_eq!(outcome.rejections.len(), 1);
+    assert_eq!(outcome.rejections[0].error, "BonusEnrolledCannotUnstake");
+    let ledger = &outcome.ledgers["alice"];
+    assert_eq!(ledger.unstake_penalties, 60_000 * QST);
+    assert_eq!(ledger.principal_returned, 340_000 * QST);
+    assert_eq!(outcome.bonus_weights["alice"], 200_000 * QST);
+}
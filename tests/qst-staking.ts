@@ -21,6 +21,7 @@ describe("QST Staking Protocol", () => {
   let adminWallet: anchor.web3.Keypair;
   let userWallet: anchor.web3.Keypair;
   let stakingPoolPda: anchor.web3.PublicKey;
+  let programDataPda: anchor.web3.PublicKey;
   let userTokenAccount: anchor.web3.PublicKey;
   let poolTokenAccount: anchor.web3.PublicKey;
   let userStakeAccount: anchor.web3.PublicKey;
@@ -28,6 +29,8 @@ describe("QST Staking Protocol", () => {
   // Test constants
   const MINIMUM_STAKE = new anchor.BN(200_000 * 1_000_000); // 200k QST
   const MAXIMUM_STAKE = new anchor.BN(10_000_000 * 1_000_000); // 10M QST
+  const MAX_TOTAL_STAKED = new anchor.BN(50_000_000 * 1_000_000); // 50M QST pool cap
+  const MAX_USER_STAKE = new anchor.BN(400_000 * 1_000_000); // 400k QST per wallet
 
   before(async () => {
     // Generate test accounts
@@ -53,6 +56,12 @@ describe("QST Staking Protocol", () => {
       program.programId
     );
     
+    // ProgramData of the deployed program; initialize checks its upgrade authority
+    [programDataPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      anchor.web3.BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+
     [userStakeAccount] = anchor.web3.PublicKey.findProgramAddressSync(
       [Buffer.from("stake_account"), userWallet.publicKey.toBuffer()],
       program.programId
@@ -89,23 +98,182 @@ describe("QST Staking Protocol", () => {
   describe("Initialization", () => {
     it("Should initialize staking pool correctly", async () => {
       await program.methods
-        .initialize(adminWallet.publicKey)
+        .initialize(adminWallet.publicKey, adminWallet.publicKey)
         .accounts({
           stakingPool: stakingPoolPda,
           qstMint: qstMint,
-          payer: provider.wallet.publicKey,
+          payer: provider.wallet.publicKey, // upgrade authority of the test deployment
+          programData: programDataPda,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
 
       const poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
       expect(poolAccount.authority.toString()).to.equal(adminWallet.publicKey.toString());
+      expect(poolAccount.feeRecipient.toString()).to.equal(adminWallet.publicKey.toString());
       expect(poolAccount.totalStaked.toNumber()).to.equal(0);
       expect(poolAccount.qstMint.toString()).to.equal(qstMint.toString());
+      expect(poolAccount.eventSeq.toNumber()).to.equal(1); // PoolInitialized
+    });
+
+    it("Should rotate the fee recipient as admin", async () => {
+      const treasury = anchor.web3.Keypair.generate().publicKey;
+      await program.methods
+        .setFeeRecipient(treasury)
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      let poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.feeRecipient.toString()).to.equal(treasury.toString());
+
+      await program.methods
+        .setFeeRecipient(adminWallet.publicKey)
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+      poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.feeRecipient.toString()).to.equal(adminWallet.publicKey.toString());
+    });
+
+    it("Should reject fee recipient changes from non-admin", async () => {
+      try {
+        await program.methods
+          .setFeeRecipient(userWallet.publicKey)
+          .accounts({
+            stakingPool: stakingPoolPda,
+            admin: userWallet.publicKey,
+          })
+          .signers([userWallet])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+  });
+
+  describe("Stake Caps", () => {
+    it("Should configure stake caps as admin before the window opens", async () => {
+      await program.methods
+        .configureStakeCaps(MAX_TOTAL_STAKED, MAX_USER_STAKE)
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      const poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.maxTotalStaked.toString()).to.equal(MAX_TOTAL_STAKED.toString());
+      expect(poolAccount.maxUserStake.toString()).to.equal(MAX_USER_STAKE.toString());
+    });
+
+    it("Should reject per-wallet cap below the minimum stake", async () => {
+      try {
+        await program.methods
+          .configureStakeCaps(MAX_TOTAL_STAKED, new anchor.BN(1))
+          .accounts({
+            stakingPool: stakingPoolPda,
+            admin: adminWallet.publicKey,
+          })
+          .signers([adminWallet])
+          .rpc();
+
+        expect.fail("Should have rejected invalid caps");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidStakeCaps");
+      }
+    });
+  });
+
+  describe("Unclaimed Bonus Policy", () => {
+    it("Should configure the unclaimed bonus policy before the window opens", async () => {
+      const claimPeriod = new anchor.BN(90 * 24 * 60 * 60);
+      await program.methods
+        .configureUnclaimedBonus({ redistribute: {} }, claimPeriod)
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      const poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.unclaimedBonusPolicy).to.deep.equal({ redistribute: {} });
+      expect(poolAccount.bonusClaimPeriod.toString()).to.equal(claimPeriod.toString());
+    });
+
+    it("Should reject a claim period below the minimum", async () => {
+      try {
+        await program.methods
+          .configureUnclaimedBonus({ sweep: {} }, new anchor.BN(24 * 60 * 60))
+          .accounts({
+            stakingPool: stakingPoolPda,
+            admin: adminWallet.publicKey,
+          })
+          .signers([adminWallet])
+          .rpc();
+
+        expect.fail("Should have rejected a short claim period");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidClaimPeriod");
+      }
     });
   });
 
   describe("Stake Window Management", () => {
+    it("Should schedule and cancel a future stake window as admin", async () => {
+      const startTs = Math.floor(Date.now() / 1000) + 24 * 60 * 60;
+      await program.methods
+        .scheduleStakeWindow(new anchor.BN(startTs))
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      let poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.firstStakeTimestamp.toNumber()).to.equal(startTs);
+
+      await program.methods
+        .cancelStakeWindowSchedule()
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.firstStakeTimestamp.toNumber()).to.equal(0);
+      expect(poolAccount.stakeWindowEnd.toNumber()).to.equal(0);
+    });
+
+    it("Should reject scheduling a window start in the past", async () => {
+      try {
+        await program.methods
+          .scheduleStakeWindow(new anchor.BN(1))
+          .accounts({
+            stakingPool: stakingPoolPda,
+            admin: adminWallet.publicKey,
+          })
+          .signers([adminWallet])
+          .rpc();
+
+        expect.fail("Should have rejected a past start");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidWindowStart");
+      }
+    });
+
     it("Should start stake window as admin", async () => {
       await program.methods
         .startStakeWindow()
@@ -120,6 +288,57 @@ describe("QST Staking Protocol", () => {
       expect(poolAccount.firstStakeTimestamp.toNumber()).to.be.greaterThan(0);
       expect(poolAccount.stakeWindowEnd.toNumber()).to.be.greaterThan(0);
     });
+
+    it("Should reject stake cap changes once the window is open", async () => {
+      try {
+        await program.methods
+          .configureStakeCaps(MAX_TOTAL_STAKED, MAX_TOTAL_STAKED)
+          .accounts({
+            stakingPool: stakingPoolPda,
+            admin: adminWallet.publicKey,
+          })
+          .signers([adminWallet])
+          .rpc();
+
+        expect.fail("Should have rejected caps change after window start");
+      } catch (error) {
+        expect(error.toString()).to.include("StakeWindowAlreadyActive");
+      }
+    });
+
+    it("Should extend the open stake window as admin", async () => {
+      const before = await program.account.stakingPool.fetch(stakingPoolPda);
+      const newWindowEnd = before.stakeWindowEnd.add(new anchor.BN(24 * 60 * 60));
+
+      await program.methods
+        .extendStakeWindow(newWindowEnd)
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      const poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.stakeWindowEnd.toString()).to.equal(newWindowEnd.toString());
+    });
+
+    it("Should reject closing the window early without notice", async () => {
+      try {
+        await program.methods
+          .closeStakeWindowEarly(new anchor.BN(Math.floor(Date.now() / 1000) + 60))
+          .accounts({
+            stakingPool: stakingPoolPda,
+            admin: adminWallet.publicKey,
+          })
+          .signers([adminWallet])
+          .rpc();
+
+        expect.fail("Should have rejected an early close without notice");
+      } catch (error) {
+        expect(error.toString()).to.include("InsufficientCloseNotice");
+      }
+    });
   });
 
   describe("Token Staking", () => {
@@ -191,6 +410,76 @@ describe("QST Staking Protocol", () => {
         expect(error.toString()).to.include("StakeAmountTooLarge");
       }
     });
+
+    it("Should reject stakes above the per-wallet cap", async () => {
+      const overCap = new anchor.BN(400_000 * 1_000_000); // 200k staked + 400k > 400k cap
+
+      try {
+        await program.methods
+          .stakeTokens(overCap)
+          .accounts({
+            stakingPool: stakingPoolPda,
+            stakeAccount: userStakeAccount,
+            user: userWallet.publicKey,
+            userTokenAccount: userTokenAccount,
+            poolTokenAccount: poolTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected stake above per-wallet cap");
+      } catch (error) {
+        expect(error.toString()).to.include("UserStakeCapExceeded");
+      }
+    });
+  });
+
+  describe("Allowlist", () => {
+    it("Should reject allowlisted stakes with an invalid proof", async () => {
+      try {
+        await program.methods
+          .stakeTokensAllowlisted(MINIMUM_STAKE, MAX_USER_STAKE, [])
+          .accounts({
+            stakingPool: stakingPoolPda,
+            stakeAccount: userStakeAccount,
+            user: userWallet.publicKey,
+            userTokenAccount: userTokenAccount,
+            poolTokenAccount: poolTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected invalid allowlist proof");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAllowlistProof");
+      }
+    });
+
+    it("Should reject allowlisted stake-and-enroll with an invalid proof", async () => {
+      try {
+        await program.methods
+          .stakeAllowlistedAndEnroll(MINIMUM_STAKE, MAX_USER_STAKE, [])
+          .accounts({
+            stakingPool: stakingPoolPda,
+            stakeAccount: userStakeAccount,
+            user: userWallet.publicKey,
+            userTokenAccount: userTokenAccount,
+            poolTokenAccount: poolTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected invalid allowlist proof");
+      } catch (error) {
+        expect(error.toString()).to.include("InvalidAllowlistProof");
+      }
+    });
   });
 
   describe("Bonus Enrollment", () => {
@@ -211,10 +500,84 @@ describe("QST Staking Protocol", () => {
         stakeAccount.principalUnlockTime.toNumber()
       );
     });
+
+    it("Should unenroll and re-enroll while enrollment is open", async () => {
+      await program.methods
+        .unenrollFromBonus()
+        .accounts({
+          stakingPool: stakingPoolPda,
+          stakeAccount: userStakeAccount,
+          user: userWallet.publicKey,
+        })
+        .signers([userWallet])
+        .rpc();
+
+      let stakeAccount = await program.account.stakeAccount.fetch(userStakeAccount);
+      let poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(stakeAccount.enrolledInBonus).to.be.false;
+      expect(stakeAccount.bonusUnlockTime.toNumber()).to.equal(0);
+      expect(poolAccount.totalEnrolledStake.toNumber()).to.equal(0);
+
+      await program.methods
+        .enrollInBonus()
+        .accounts({
+          stakingPool: stakingPoolPda,
+          stakeAccount: userStakeAccount,
+          user: userWallet.publicKey,
+        })
+        .signers([userWallet])
+        .rpc();
+
+      stakeAccount = await program.account.stakeAccount.fetch(userStakeAccount);
+      expect(stakeAccount.enrolledInBonus).to.be.true;
+    });
+
+    it("Should reject stake-and-enroll for an already enrolled position", async () => {
+      try {
+        await program.methods
+          .stakeAndEnroll(MINIMUM_STAKE)
+          .accounts({
+            stakingPool: stakingPoolPda,
+            stakeAccount: userStakeAccount,
+            user: userWallet.publicKey,
+            userTokenAccount: userTokenAccount,
+            poolTokenAccount: poolTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected stake-and-enroll while enrolled");
+      } catch (error) {
+        expect(error.toString()).to.include("AlreadyEnrolledInBonus");
+      }
+    });
+
+    it("Should reject enrolled early exit while stake window is active", async () => {
+      try {
+        await program.methods
+          .exitEnrolled()
+          .accounts({
+            stakingPool: stakingPoolPda,
+            stakeAccount: userStakeAccount,
+            user: userWallet.publicKey,
+            userTokenAccount: userTokenAccount,
+            poolTokenAccount: poolTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected exit during stake window");
+      } catch (error) {
+        expect(error.toString()).to.include("StakeWindowStillActive");
+      }
+    });
   });
 
   describe("Stake Info Retrieval", () => {
-    it("Should return comprehensive stake information", async () => {
+    it("Should return stake information for any wallet without its signature", async () => {
       const stakeInfo = await program.methods
         .getStakeInfo()
         .accounts({
@@ -222,12 +585,229 @@ describe("QST Staking Protocol", () => {
           stakeAccount: userStakeAccount,
           user: userWallet.publicKey,
         })
+        .view();
+
+      expect(stakeInfo.amount.toString()).to.equal(MINIMUM_STAKE.toString());
+      expect(stakeInfo.user.toString()).to.equal(userWallet.publicKey.toString());
+    });
+
+    it("Should return stake information for a batch of wallets", async () => {
+      const stakeInfos = await program.methods
+        .getStakeInfos()
+        .accounts({
+          stakingPool: stakingPoolPda,
+        })
+        .remainingAccounts([
+          { pubkey: userStakeAccount, isWritable: false, isSigner: false },
+        ])
+        .view();
+
+      expect(stakeInfos).to.have.lengthOf(1);
+      expect(stakeInfos[0].user.toString()).to.equal(userWallet.publicKey.toString());
+    });
+  });
+
+  describe("Reward Emissions", () => {
+    let rewardVaultPda: anchor.web3.PublicKey;
+    const REWARD_AMOUNT = new anchor.BN(50_000 * 1_000_000); // 50k QST
+
+    before(() => {
+      [rewardVaultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("reward_vault")],
+        program.programId
+      );
+    });
+
+    it("Should fund a reward stream into the reward vault as admin", async () => {
+      const adminTokenAccount = await createAccount(
+        provider.connection,
+        adminWallet,
+        qstMint,
+        adminWallet.publicKey
+      );
+      await mintTo(provider.connection, adminWallet, qstMint, adminTokenAccount, adminWallet, 50_000 * 1_000_000);
+
+      const start = Math.floor(Date.now() / 1000) + 60 * 60;
+      await program.methods
+        .fundRewards(REWARD_AMOUNT, new anchor.BN(start), new anchor.BN(start + 30 * 24 * 60 * 60))
+        .accounts({
+          stakingPool: stakingPoolPda,
+          rewardVault: rewardVaultPda,
+          qstMint: qstMint,
+          admin: adminWallet.publicKey,
+          adminTokenAccount: adminTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      const poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.rewardStreamAmount.toString()).to.equal(REWARD_AMOUNT.toString());
+      expect(poolAccount.rewardStart.toNumber()).to.equal(start);
+      const rewardVault = await getAccount(provider.connection, rewardVaultPda);
+      expect(Number(rewardVault.amount)).to.equal(50_000 * 1_000_000);
+    });
+
+    it("Should report no accrued rewards before the stream starts", async () => {
+      const stakeInfo = await program.methods
+        .getStakeInfo()
+        .accounts({
+          stakingPool: stakingPoolPda,
+          stakeAccount: userStakeAccount,
+          user: userWallet.publicKey,
+        })
+        .view();
+      expect(stakeInfo.accruedRewards.toNumber()).to.equal(0);
+
+      try {
+        await program.methods
+          .claimRewards()
+          .accounts({
+            stakingPool: stakingPoolPda,
+            stakeAccount: userStakeAccount,
+            user: userWallet.publicKey,
+            userTokenAccount: userTokenAccount,
+            rewardVault: rewardVaultPda,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected an empty claim");
+      } catch (error) {
+        expect(error.toString()).to.include("NoRewardsToClaim");
+      }
+    });
+  });
+
+  describe("Unstake Quote", () => {
+    it("Should quote a blocked unstake while the stake window is open", async () => {
+      const quote = await program.methods
+        .quoteUnstake(MINIMUM_STAKE)
+        .accounts({
+          stakingPool: stakingPoolPda,
+          stakeAccount: userStakeAccount,
+          user: userWallet.publicKey,
+        })
+        .view();
+
+      expect(quote.tier).to.have.property("blocked");
+      expect(quote.unstakeAllowed).to.be.false;
+      expect(quote.timeUntilNextTier.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("Should reject unstaking while the stake window is open", async () => {
+      try {
+        await program.methods
+          .unstakeTokens(MINIMUM_STAKE, new anchor.BN(0))
+          .accounts({
+            stakingPool: stakingPoolPda,
+            stakeAccount: userStakeAccount,
+            user: userWallet.publicKey,
+            userTokenAccount: userTokenAccount,
+            poolTokenAccount: poolTokenAccount,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected unstake during stake window");
+      } catch (error) {
+        expect(error.toString()).to.include("BonusEnrolledCannotUnstake");
+      }
+    });
+  });
+
+  describe("Pool Info Retrieval", () => {
+    it("Should return derived pool phases and deadlines", async () => {
+      const poolInfo = await program.methods
+        .getPoolInfo()
+        .accounts({
+          stakingPool: stakingPoolPda,
+        })
+        .view();
+
+      expect(poolInfo.windowPhase).to.have.property("open");
+      expect(poolInfo.bonusEnrollmentOpen).to.be.true;
+      expect(poolInfo.bonusWithdrawalAvailable).to.be.false;
+      // HAL-01: unstaking stays blocked while the window is open
+      expect(poolInfo.currentPenaltyTier).to.have.property("blocked");
+      expect(poolInfo.bonusWithdrawalTime.toNumber()).to.equal(
+        poolInfo.latestBonusUnlockTime.toNumber() + 24 * 60 * 60
+      );
+    });
+  });
+
+  describe("Pool Audit", () => {
+    it("Should pass the audit and emit AuditEvent", async () => {
+      await program.methods
+        .auditPool()
+        .accounts({
+          stakingPool: stakingPoolPda,
+          poolTokenAccount: poolTokenAccount,
+          auditor: userWallet.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: userStakeAccount, isWritable: false, isSigner: false },
+        ])
         .signers([userWallet])
         .rpc();
+    });
+
+    it("Should reject a stake account passed twice", async () => {
+      try {
+        await program.methods
+          .auditPool()
+          .accounts({
+            stakingPool: stakingPoolPda,
+            poolTokenAccount: poolTokenAccount,
+            auditor: userWallet.publicKey,
+          })
+          .remainingAccounts([
+            { pubkey: userStakeAccount, isWritable: false, isSigner: false },
+            { pubkey: userStakeAccount, isWritable: false, isSigner: false },
+          ])
+          .signers([userWallet])
+          .rpc();
+
+        expect.fail("Should have rejected the duplicate");
+      } catch (error) {
+        expect(error.toString()).to.include("DuplicateStakeAccount");
+      }
+    });
+  });
+
+  describe("Abandoned-Pool Settlement", () => {
+    it("Should record admin heartbeat", async () => {
+      await program.methods
+        .adminHeartbeat()
+        .accounts({
+          stakingPool: stakingPoolPda,
+          admin: adminWallet.publicKey,
+        })
+        .signers([adminWallet])
+        .rpc();
+
+      const poolAccount = await program.account.stakingPool.fetch(stakingPoolPda);
+      expect(poolAccount.lastAdminAction.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("Should reject settlement while the admin is active", async () => {
+      try {
+        await program.methods
+          .triggerSettlement()
+          .accounts({
+            stakingPool: stakingPoolPda,
+            caller: userWallet.publicKey,
+          })
+          .signers([userWallet])
+          .rpc();
 
-      // This would return the StakeInfo struct if implemented
-      // For now, just verify the transaction succeeds
-      expect(stakeInfo).to.not.be.null;
+        expect.fail("Should have rejected settlement");
+      } catch (error) {
+        expect(error.toString()).to.include("SettlementNotAvailable");
+      }
     });
   });
 